@@ -0,0 +1,118 @@
+/*
+    RPC connection + signer shared by every command
+    transactions are paid by the signer and confirmed before the command prints
+*/
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::Discriminator;
+use anyhow::{anyhow, Context, Result};
+use craft_skins_client::pda::*;
+use craft_skins_client::{decode_manager, decode_recipe, Manager, Recipe};
+use serde_json::json;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use spl_token::state::Account as TokenAccount;
+use std::path::Path;
+
+use crate::rpc::{Rpc, SendRpc};
+
+pub struct Cluster {
+    pub rpc: Rpc,
+    pub payer: Keypair,
+}
+
+pub fn read_keypair(path: &Path) -> Result<Keypair> {
+    solana_sdk::signature::read_keypair_file(path)
+        .map_err(|err| anyhow!("failed to read keypair {}: {}", path.display(), err))
+}
+
+impl Cluster {
+    pub fn new(url: &str, keypair: &Path) -> Result<Self> {
+        Ok(Cluster {
+            rpc: Rpc::new(url),
+            payer: read_keypair(keypair)?,
+        })
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.payer.pubkey()
+    }
+
+    // payer signs + pays, signers are any other required signers
+    pub fn send(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Result<Signature> {
+        let mut all_signers: Vec<&dyn Signer> = vec![&self.payer];
+        all_signers.extend(signers.iter().map(|signer| *signer as &dyn Signer));
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer()),
+            &all_signers,
+            self.rpc.latest_blockhash()?,
+        );
+        self.rpc.send_and_confirm(&transaction)
+    }
+
+    pub fn account_exists(&self, key: &Pubkey) -> Result<bool> {
+        Ok(self.rpc.account_data(key)?.is_some())
+    }
+
+    pub fn recipe(&self, recipe_mint: &Pubkey) -> Result<Recipe> {
+        let key = recipe_pda(recipe_mint);
+        let data = self
+            .rpc
+            .account_data(&key)?
+            .with_context(|| format!("no Recipe account {} for mint {}", key, recipe_mint))?;
+        Ok(decode_recipe(&data)?)
+    }
+
+    // (address, Recipe) of every Recipe in the current layout, older layouts are skipped
+    pub fn recipes(&self) -> Result<Vec<(Pubkey, Recipe)>> {
+        let discriminator = solana_sdk::bs58::encode(Recipe::discriminator()).into_string();
+        let filters = json!([{"memcmp": {"offset": 0, "bytes": discriminator}}]);
+        let recipes = self
+            .rpc
+            .program_accounts(&craft_skins_client::id(), filters)?
+            .into_iter()
+            .filter_map(|(key, data)| match decode_recipe(&data) {
+                Ok(recipe) if recipe.version == Recipe::VERSION => Some((key, recipe)),
+                _ => {
+                    eprintln!("skipping Recipe {}, run recipe migrate", key);
+                    None
+                }
+            })
+            .collect();
+        Ok(recipes)
+    }
+
+    pub fn manager(&self) -> Result<Manager> {
+        let data = self
+            .rpc
+            .account_data(&manager_pda())?
+            .context("no Manager account, run `craft-skins init` first")?;
+        Ok(decode_manager(&data)?)
+    }
+
+    pub fn token_account(&self, key: &Pubkey) -> Result<TokenAccount> {
+        let data = self
+            .rpc
+            .account_data(key)?
+            .with_context(|| format!("no token account {}", key))?;
+        Ok(TokenAccount::unpack(&data)?)
+    }
+
+    // (address, token account) of every SPL token account owned by owner
+    pub fn token_accounts(&self, owner: &Pubkey) -> Result<Vec<(Pubkey, TokenAccount)>> {
+        let filters = json!([
+            {"dataSize": TokenAccount::LEN},
+            // token account owner follows the 32 byte mint
+            {"memcmp": {"offset": 32, "bytes": owner.to_string()}},
+        ]);
+        self.rpc
+            .program_accounts(&spl_token::id(), filters)?
+            .into_iter()
+            .map(|(key, data)| Ok((key, TokenAccount::unpack(&data)?)))
+            .collect()
+    }
+}
@@ -0,0 +1,106 @@
+/*
+    craft => craft_skin with the signer as user, to try a recipe on a test validator
+    remaining_accounts are built from the fetched Recipe, see client remaining.rs
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use clap::Args;
+use craft_skins_client::instruction::{self, CraftAccounts};
+use craft_skins_client::remaining::*;
+use solana_sdk::signature::Signer;
+use std::path::PathBuf;
+
+use crate::cluster::{read_keypair, Cluster};
+use crate::parse_hash;
+
+#[derive(Args)]
+pub struct CraftArgs {
+    /// Recipe NFT mint
+    #[clap(long)]
+    recipe: Pubkey,
+    /// Skin delivered to the signer, held by the skin owner
+    #[clap(long)]
+    skin: Pubkey,
+    /// Keypair of the skin owner, also signs the craft [default: signer]
+    #[clap(long)]
+    owner_keypair: Option<PathBuf>,
+    /// Holder of the Recipe NFT, receives the royalty [default: skin owner]
+    #[clap(long)]
+    holder: Option<Pubkey>,
+    /// Allowance of the signer in the recipe allowlist
+    #[clap(long, default_value = "0")]
+    allowance: u32,
+    /// Allowlist merkle proof node as 64 hex characters, repeat in order
+    #[clap(long = "proof", parse(try_from_str = parse_hash))]
+    proof: Vec<[u8; 32]>,
+    /// NFT held by the signer for a collection gated recipe
+    #[clap(long)]
+    gate_nft: Option<Pubkey>,
+    /// Input skin held by the signer, repeat in Recipe input_skins order
+    #[clap(long = "input-skin")]
+    input_skins: Vec<Pubkey>,
+    /// Recipe vault skin of a Skin output, repeat in Recipe outputs order
+    #[clap(long = "output-skin")]
+    output_skins: Vec<Pubkey>,
+}
+
+pub fn run(cluster: &Cluster, args: CraftArgs) -> Result<()> {
+    let owner_keypair = args
+        .owner_keypair
+        .as_deref()
+        .map(read_keypair)
+        .transpose()?;
+    let user = cluster.payer();
+    let owner = owner_keypair
+        .as_ref()
+        .map(|keypair| keypair.pubkey())
+        .unwrap_or(user);
+    let recipe = cluster.recipe(&args.recipe)?;
+    let craft = CraftAccounts {
+        owner,
+        holder: args.holder.unwrap_or(owner),
+        user,
+        admin: cluster.manager()?.admin,
+        recipe_mint: args.recipe,
+    };
+
+    let mut remaining = vec![];
+    if let Some(gate) = recipe.gate.as_ref() {
+        remaining.extend(
+            gate_accounts(gate, &user, args.gate_nft.as_ref())
+                .ok_or_else(|| anyhow!("recipe is collection gated, pass --gate-nft"))?,
+        );
+    }
+    // a craft job escrows the ingredients, its accounts come first
+    if recipe.craft_duration > 0 {
+        remaining.extend(craft_job_accounts(&args.recipe, &user, &args.skin));
+    }
+    remaining.extend(ingredient_accounts(&recipe, &args.recipe, &user));
+    if args.input_skins.len() != recipe.input_skins.len() {
+        return Err(anyhow!(
+            "recipe consumes {} input skins, got {}",
+            recipe.input_skins.len(),
+            args.input_skins.len()
+        ));
+    }
+    remaining.extend(input_skin_accounts(
+        &recipe,
+        &args.recipe,
+        &user,
+        &args.input_skins,
+    ));
+    if recipe.craft_duration == 0 {
+        remaining.extend(
+            output_accounts(&recipe, &args.recipe, &user, &args.output_skins)
+                .ok_or_else(|| anyhow!("pass an --output-skin for every Skin output"))?,
+        );
+        remaining.extend(salvage_record_accounts(&recipe, &args.recipe, &[args.skin]));
+    }
+
+    let ix = instruction::craft_skin(&craft, &args.skin, remaining, args.allowance, args.proof);
+    let signers: Vec<_> = owner_keypair.iter().collect();
+    let signature = cluster.send(&[ix], &signers)?;
+    println!("Crafted {} of recipe {}", args.skin, args.recipe);
+    println!("Signature {}", signature);
+    Ok(())
+}
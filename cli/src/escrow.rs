@@ -0,0 +1,171 @@
+/*
+    escrow list|deposit|withdraw
+    program_signer holds skins of craft jobs + ingredients escrowed before recipe vaults,
+    the Manager admin withdraws them
+    --recipe => vault of a Recipe (LargeRecipe with --large), holds its skin outputs + spent
+    ingredients, its Recipe NFT holder withdraws
+    deposit stocks a recipe vault with skin outputs held by the signer
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use clap::Subcommand;
+use craft_skins_client::instruction;
+use craft_skins_client::pda::{
+    escrow_token, large_recipe_pda, recipe_pda, recipe_vault_pda, signer_pda, vault_token,
+};
+use spl_associated_token_account::{create_associated_token_account, get_associated_token_address};
+
+use crate::cluster::Cluster;
+
+#[derive(Subcommand)]
+pub enum EscrowCommand {
+    /// List token accounts held by program_signer, or by a recipe vault
+    List {
+        /// Recipe NFT mint of the vault
+        #[clap(long)]
+        recipe: Option<Pubkey>,
+        /// Vault of the LargeRecipe of --recipe
+        #[clap(long, requires = "recipe")]
+        large: bool,
+    },
+    /// Move tokens held by the signer into a recipe vault, e.g. skins of Skin outputs
+    Deposit {
+        #[clap(long)]
+        mint: Pubkey,
+        /// Recipe NFT mint of the vault
+        #[clap(long)]
+        recipe: Pubkey,
+        /// Vault of the LargeRecipe of --recipe
+        #[clap(long)]
+        large: bool,
+        /// Amount in base units
+        #[clap(long, default_value = "1")]
+        amount: u64,
+    },
+    /// Withdraw escrowed tokens as the Manager admin, or vault tokens as the Recipe NFT holder
+    Withdraw {
+        #[clap(long)]
+        mint: Pubkey,
+        /// Recipe NFT mint of the vault
+        #[clap(long)]
+        recipe: Option<Pubkey>,
+        /// Vault of the LargeRecipe of --recipe
+        #[clap(long, requires = "recipe")]
+        large: bool,
+        /// Amount in base units [default: whole escrow balance]
+        #[clap(long)]
+        amount: Option<u64>,
+        /// Destination token account [default: signer associated token account]
+        #[clap(long)]
+        destination: Option<Pubkey>,
+    },
+}
+
+pub fn run(cluster: &Cluster, command: EscrowCommand) -> Result<()> {
+    match command {
+        EscrowCommand::List { recipe, large } => {
+            let holder = match recipe {
+                Some(recipe_mint) => {
+                    let vault = recipe_vault_pda(&vault_recipe(&recipe_mint, large));
+                    println!("recipe vault {}", vault);
+                    vault
+                }
+                None => {
+                    println!("program_signer {}", signer_pda());
+                    signer_pda()
+                }
+            };
+            for (key, token) in cluster.token_accounts(&holder)? {
+                println!("{} {} {}", key, token.mint, token.amount);
+            }
+        }
+        EscrowCommand::Deposit {
+            mint,
+            recipe,
+            large,
+            amount,
+        } => {
+            let payer = cluster.payer();
+            let vault = recipe_vault_pda(&vault_recipe(&recipe, large));
+            let vault_token = get_associated_token_address(&vault, &mint);
+            let mut instructions = vec![];
+            if !cluster.account_exists(&vault_token)? {
+                instructions.push(create_associated_token_account(&payer, &vault, &mint));
+            }
+            instructions.push(spl_token::instruction::transfer(
+                &spl_token::id(),
+                &get_associated_token_address(&payer, &mint),
+                &vault_token,
+                &payer,
+                &[],
+                amount,
+            )?);
+            let signature = cluster.send(&instructions, &[])?;
+            println!("Deposited {} of {} to {}", amount, mint, vault_token);
+            println!("Signature {}", signature);
+        }
+        EscrowCommand::Withdraw {
+            mint,
+            recipe,
+            large,
+            amount,
+            destination,
+        } => {
+            let admin = cluster.payer();
+            let source = match recipe {
+                Some(recipe_mint) => vault_token(&vault_recipe(&recipe_mint, large), &mint),
+                None => {
+                    let manager = cluster.manager()?;
+                    if manager.admin != admin {
+                        return Err(anyhow!(
+                            "signer {} is not the Manager admin {}",
+                            admin,
+                            manager.admin
+                        ));
+                    }
+                    escrow_token(&mint)
+                }
+            };
+            let amount = match amount {
+                Some(amount) => amount,
+                None => cluster.token_account(&source)?.amount,
+            };
+
+            let mut instructions = vec![];
+            let destination = match destination {
+                Some(destination) => destination,
+                None => {
+                    let destination = get_associated_token_address(&admin, &mint);
+                    if !cluster.account_exists(&destination)? {
+                        instructions.push(create_associated_token_account(&admin, &admin, &mint));
+                    }
+                    destination
+                }
+            };
+            instructions.push(match recipe {
+                Some(recipe_mint) => instruction::withdraw_vault(
+                    &admin,
+                    &vault_recipe(&recipe_mint, large),
+                    &recipe_mint,
+                    &mint,
+                    &destination,
+                    amount,
+                ),
+                None => instruction::withdraw_escrow(&admin, &mint, &destination, amount),
+            });
+            let signature = cluster.send(&instructions, &[])?;
+            println!("Withdrew {} of {} to {}", amount, mint, destination);
+            println!("Signature {}", signature);
+        }
+    }
+    Ok(())
+}
+
+// Recipe or LargeRecipe PDA of recipe_mint owning a vault
+fn vault_recipe(recipe_mint: &Pubkey, large: bool) -> Pubkey {
+    if large {
+        large_recipe_pda(recipe_mint)
+    } else {
+        recipe_pda(recipe_mint)
+    }
+}
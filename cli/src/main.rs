@@ -0,0 +1,108 @@
+/*
+    craft-skins => admin CLI of the craft_skins program
+      init                             => create the Manager, signer becomes admin
+      migrate-manager                  => rewrite the Manager in the current layout
+      recipe create|show|update|close|migrate => Recipe of a Recipe NFT held by the signer
+      recipe list                      => every Recipe by category + sort order
+      skin add|list                    => skins delivered by a Recipe
+      escrow list|deposit|withdraw     => tokens held by program_signer or a recipe vault
+      craft                            => craft_skin as the signer, for testing recipes
+    keypairs are read from files, default cluster is a local test validator
+*/
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+mod cluster;
+mod craft;
+mod escrow;
+mod recipe;
+mod rpc;
+mod skin;
+
+use cluster::Cluster;
+
+#[derive(Parser)]
+#[clap(
+    name = "craft-skins",
+    version,
+    about = "Manage craft_skins recipes, skins and escrows"
+)]
+struct Cli {
+    /// RPC url of the cluster
+    #[clap(long, short, global = true, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// Signer and fee payer keypair file [default: ~/.config/solana/id.json]
+    #[clap(long, short, global = true)]
+    keypair: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create the program Manager with the signer as admin
+    Init,
+    /// Rewrite a Manager created by an older program version in the current layout
+    MigrateManager,
+    #[clap(subcommand)]
+    Recipe(recipe::RecipeCommand),
+    #[clap(subcommand)]
+    Skin(skin::SkinCommand),
+    #[clap(subcommand)]
+    Escrow(escrow::EscrowCommand),
+    /// Craft a skin of a recipe as the signer
+    Craft(craft::CraftArgs),
+}
+
+fn default_keypair() -> Result<PathBuf> {
+    let home =
+        std::env::var_os("HOME").ok_or_else(|| anyhow!("HOME is not set, pass --keypair"))?;
+    Ok(PathBuf::from(home).join(".config/solana/id.json"))
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let keypair = match cli.keypair {
+        Some(keypair) => keypair,
+        None => default_keypair()?,
+    };
+    let cluster = Cluster::new(&cli.url, &keypair)?;
+
+    match cli.command {
+        Command::Init => {
+            let instruction = craft_skins_client::instruction::initialize(&cluster.payer());
+            let signature = cluster.send(&[instruction], &[])?;
+            println!("Manager {}", craft_skins_client::pda::manager_pda());
+            println!("Signature {}", signature);
+            Ok(())
+        }
+        Command::MigrateManager => {
+            let instruction = craft_skins_client::instruction::migrate_manager(&cluster.payer());
+            let signature = cluster.send(&[instruction], &[])?;
+            println!("Signature {}", signature);
+            Ok(())
+        }
+        Command::Recipe(command) => recipe::run(&cluster, command),
+        Command::Skin(command) => skin::run(&cluster, command),
+        Command::Escrow(command) => escrow::run(&cluster, command),
+        Command::Craft(args) => craft::run(&cluster, args),
+    }
+}
+
+// 64 hex chars => 32 bytes, used by allowlist roots + proofs
+pub fn parse_hash(value: &str) -> Result<[u8; 32]> {
+    let value = value.trim_start_matches("0x");
+    if value.len() != 64 {
+        return Err(anyhow!("expected 64 hex characters, got {}", value.len()));
+    }
+    let mut hash = [0u8; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&value[i * 2..i * 2 + 2], 16)?;
+    }
+    Ok(hash)
+}
+
+pub fn format_hash(hash: &[u8; 32]) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,393 @@
+/*
+    recipe create|show|update|close|migrate|list
+    create starts from a Fixed mode recipe without fee, update from the fetched Recipe,
+    both only replace the settings passed as flags
+    list reads every Recipe from getProgramAccounts, ordered by category, sort order, name
+
+    recipe validate|export|apply work on manifests (client manifest.rs),
+    .json files are read as JSON, anything else as TOML
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use craft_skins_client::instruction;
+use craft_skins_client::manifest::RecipeManifest;
+use craft_skins_client::pda::recipe_pda;
+use craft_skins_client::{OutputKind, Recipe, RecipeCategory, RecipeGate, RecipeMode, SkinConsume};
+use std::path::{Path, PathBuf};
+
+use crate::cluster::Cluster;
+use crate::{format_hash, parse_hash};
+
+#[derive(Subcommand)]
+pub enum RecipeCommand {
+    /// Create the Recipe of a Recipe NFT held by the signer
+    Create {
+        recipe_mint: Pubkey,
+        #[clap(flatten)]
+        settings: RecipeSettings,
+    },
+    /// Print a Recipe
+    Show { recipe_mint: Pubkey },
+    /// List every Recipe by category and sort order
+    List {
+        /// Only list recipes of this category
+        #[clap(long, parse(try_from_str = parse_category))]
+        category: Option<RecipeCategory>,
+    },
+    /// Replace settings of a Recipe, others are kept
+    Update {
+        recipe_mint: Pubkey,
+        #[clap(flatten)]
+        settings: RecipeSettings,
+    },
+    /// Close a Recipe, rent goes back to the signer
+    Close { recipe_mint: Pubkey },
+    /// Rewrite a Recipe created by an older program version in the current layout
+    Migrate { recipe_mint: Pubkey },
+    /// Check a manifest against the create_recipe rules without sending anything
+    Validate { manifest: PathBuf },
+    /// Print a Recipe as a manifest
+    Export {
+        recipe_mint: Pubkey,
+        /// Print JSON instead of TOML
+        #[clap(long)]
+        json: bool,
+    },
+    /// Create or update the Recipe of a manifest, settings not in it are reset
+    Apply {
+        manifest: PathBuf,
+        /// Print the changes without sending the transaction
+        #[clap(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct RecipeSettings {
+    /// Name shown in recipe listings
+    #[clap(long)]
+    name: Option<String>,
+    /// Description shown in recipe listings
+    #[clap(long)]
+    description: Option<String>,
+    /// Category of recipe listings: uncategorized, weapon, armor, character, vehicle,
+    /// cosmetic, consumable or bundle
+    #[clap(long, parse(try_from_str = parse_category))]
+    category: Option<RecipeCategory>,
+    /// Ascending position of the recipe within its category
+    #[clap(long)]
+    sort_order: Option<u16>,
+    /// Ingredient as MINT:AMOUNT, repeat for each ingredient (replaces all ingredients)
+    #[clap(long = "ingredient", parse(try_from_str = parse_ingredient))]
+    ingredients: Vec<(Pubkey, u64)>,
+    /// Lamports paid by the user on every craft
+    #[clap(long)]
+    craft_fee: Option<u64>,
+    /// Share of craft_fee paid to the Recipe NFT holder
+    #[clap(long)]
+    royalty_bps: Option<u16>,
+    /// Share of each ingredient paid back by uncraft_skin, 0 disables uncraft
+    #[clap(long)]
+    salvage_bps: Option<u16>,
+    /// Share of escrowed ingredients kept when a pending craft is canceled
+    #[clap(long)]
+    cancel_penalty_bps: Option<u16>,
+    /// Seconds from craft_skin until claim_craft, 0 delivers the skin on craft
+    #[clap(long)]
+    craft_duration: Option<i64>,
+    /// Merkle root of the allowlist as 64 hex characters
+    #[clap(long, parse(try_from_str = parse_hash), conflicts_with = "no-allowlist")]
+    allowlist_root: Option<[u8; 32]>,
+    /// Remove the allowlist
+    #[clap(long)]
+    no_allowlist: bool,
+}
+
+impl RecipeSettings {
+    fn apply(self, args: &mut craft_skins_client::RecipeSettings) {
+        if let Some(name) = self.name {
+            args.name = name;
+        }
+        if let Some(description) = self.description {
+            args.description = description;
+        }
+        if let Some(category) = self.category {
+            args.category = category;
+        }
+        if let Some(sort_order) = self.sort_order {
+            args.sort_order = sort_order;
+        }
+        if !self.ingredients.is_empty() {
+            let (mints, amounts) = self.ingredients.into_iter().unzip();
+            args.ingredient_mints = mints;
+            args.ingredient_amounts = amounts;
+        }
+        if let Some(craft_fee) = self.craft_fee {
+            args.craft_fee = craft_fee;
+        }
+        if let Some(royalty_bps) = self.royalty_bps {
+            args.royalty_bps = royalty_bps;
+        }
+        if let Some(salvage_bps) = self.salvage_bps {
+            args.salvage_bps = salvage_bps;
+        }
+        if let Some(cancel_penalty_bps) = self.cancel_penalty_bps {
+            args.cancel_penalty_bps = cancel_penalty_bps;
+        }
+        if let Some(craft_duration) = self.craft_duration {
+            args.craft_duration = craft_duration;
+        }
+        if self.allowlist_root.is_some() {
+            args.allowlist_root = self.allowlist_root;
+        }
+        if self.no_allowlist {
+            args.allowlist_root = None;
+        }
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<RecipeManifest> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("failed to read manifest {}", path.display()))?;
+    let manifest = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => RecipeManifest::from_json(&text)?,
+        _ => RecipeManifest::from_toml(&text)?,
+    };
+    Ok(manifest)
+}
+
+fn parse_ingredient(value: &str) -> Result<(Pubkey, u64)> {
+    let (mint, amount) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected MINT:AMOUNT, got {}", value))?;
+    Ok((mint.parse()?, amount.parse()?))
+}
+
+const CATEGORIES: [(RecipeCategory, &str); 8] = [
+    (RecipeCategory::Uncategorized, "uncategorized"),
+    (RecipeCategory::Weapon, "weapon"),
+    (RecipeCategory::Armor, "armor"),
+    (RecipeCategory::Character, "character"),
+    (RecipeCategory::Vehicle, "vehicle"),
+    (RecipeCategory::Cosmetic, "cosmetic"),
+    (RecipeCategory::Consumable, "consumable"),
+    (RecipeCategory::Bundle, "bundle"),
+];
+
+fn parse_category(value: &str) -> Result<RecipeCategory> {
+    CATEGORIES
+        .iter()
+        .find(|(_, name)| *name == value)
+        .map(|(category, _)| *category)
+        .ok_or_else(|| anyhow!("unknown category {}", value))
+}
+
+fn category_name(category: RecipeCategory) -> &'static str {
+    CATEGORIES
+        .iter()
+        .find(|(known, _)| *known == category)
+        .map(|(_, name)| *name)
+        .expect("every category is named")
+}
+
+pub fn run(cluster: &Cluster, command: RecipeCommand) -> Result<()> {
+    match command {
+        RecipeCommand::Create {
+            recipe_mint,
+            settings,
+        } => {
+            let mut args = instruction::recipe_args(vec![], vec![]);
+            settings.apply(&mut args);
+            let ix = instruction::create_recipe(&cluster.payer(), &recipe_mint, args);
+            let signature = cluster.send(&[ix], &[])?;
+            println!("Recipe {}", recipe_pda(&recipe_mint));
+            println!("Signature {}", signature);
+        }
+        RecipeCommand::Show { recipe_mint } => {
+            print_recipe(&recipe_mint, &cluster.recipe(&recipe_mint)?);
+        }
+        RecipeCommand::List { category } => {
+            let mut recipes = cluster.recipes()?;
+            recipes
+                .retain(|(_, recipe)| category.is_none_or(|category| recipe.category == category));
+            recipes.sort_by(|(_, a), (_, b)| {
+                (a.category as u8, a.sort_order, &a.name).cmp(&(
+                    b.category as u8,
+                    b.sort_order,
+                    &b.name,
+                ))
+            });
+            for (address, recipe) in recipes.iter() {
+                println!(
+                    "{}  {:<13} {:>5}  {}",
+                    address,
+                    category_name(recipe.category),
+                    recipe.sort_order,
+                    recipe.name
+                );
+            }
+        }
+        RecipeCommand::Update {
+            recipe_mint,
+            settings,
+        } => {
+            let mut args = instruction::recipe_args_from(&cluster.recipe(&recipe_mint)?);
+            settings.apply(&mut args);
+            let ix = instruction::update_recipe(&cluster.payer(), &recipe_mint, args);
+            let signature = cluster.send(&[ix], &[])?;
+            println!("Signature {}", signature);
+        }
+        RecipeCommand::Close { recipe_mint } => {
+            let ix = instruction::close_recipe(&cluster.payer(), &recipe_mint);
+            let signature = cluster.send(&[ix], &[])?;
+            println!("Signature {}", signature);
+        }
+        RecipeCommand::Migrate { recipe_mint } => {
+            let ix = instruction::migrate_recipe(&cluster.payer(), &recipe_mint);
+            let signature = cluster.send(&[ix], &[])?;
+            println!("Signature {}", signature);
+        }
+        RecipeCommand::Validate { manifest } => {
+            read_manifest(&manifest)?.validate()?;
+            println!("{} is valid", manifest.display());
+        }
+        RecipeCommand::Export { recipe_mint, json } => {
+            let manifest =
+                RecipeManifest::from_recipe(&recipe_mint, &cluster.recipe(&recipe_mint)?);
+            if json {
+                println!("{}", manifest.to_json());
+            } else {
+                print!("{}", manifest.to_toml());
+            }
+        }
+        RecipeCommand::Apply { manifest, dry_run } => {
+            let manifest = read_manifest(&manifest)?;
+            let on_chain = match cluster.account_exists(&recipe_pda(&manifest.recipe_mint))? {
+                true => Some(cluster.recipe(&manifest.recipe_mint)?),
+                false => None,
+            };
+            let diff = manifest.diff(&cluster.payer(), on_chain.as_ref())?;
+            match &on_chain {
+                _ if diff.instructions.is_empty() => {
+                    println!("Recipe {} is up to date", manifest.recipe_mint)
+                }
+                None => println!("create_recipe {}", manifest.recipe_mint),
+                Some(recipe) => {
+                    if recipe.version < Recipe::VERSION {
+                        println!("migrate_recipe {}", manifest.recipe_mint);
+                    }
+                    if !diff.changes.is_empty() {
+                        println!("update_recipe {}", manifest.recipe_mint);
+                    }
+                }
+            }
+            for change in diff.changes.iter() {
+                println!(
+                    "  {}: {} => {}",
+                    change.field, change.on_chain, change.manifest
+                );
+            }
+            if !diff.instructions.is_empty() && !dry_run {
+                let signature = cluster.send(&diff.instructions, &[])?;
+                println!("Signature {}", signature);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn output_kind(kind: OutputKind) -> &'static str {
+    match kind {
+        OutputKind::Skin => "skin",
+        OutputKind::Fungible => "fungible",
+    }
+}
+
+fn print_recipe(recipe_mint: &Pubkey, recipe: &Recipe) {
+    println!("Recipe {}", recipe_pda(recipe_mint));
+    println!("  recipe mint         {}", recipe_mint);
+    if !recipe.name.is_empty() {
+        println!("  name                {}", recipe.name);
+    }
+    if !recipe.description.is_empty() {
+        println!("  description         {}", recipe.description);
+    }
+    println!(
+        "  category            {} (sort order {})",
+        category_name(recipe.category),
+        recipe.sort_order
+    );
+    println!("  revision            {}", recipe.revision);
+    if recipe.version < Recipe::VERSION {
+        println!(
+            "  version             {} (run recipe migrate)",
+            recipe.version
+        );
+    }
+    println!("  ingredients");
+    for (mint, amount) in recipe.mints.iter().zip(recipe.amounts.iter()) {
+        println!("    {} x {}", mint, amount);
+    }
+    println!("  craft fee           {} lamports", recipe.craft_fee);
+    println!("  royalty             {} bps", recipe.royalty_bps);
+    let mode = match recipe.mode {
+        RecipeMode::Fixed => "fixed",
+        RecipeMode::Random => "random",
+    };
+    println!("  mode                {}", mode);
+    for output in recipe.outputs.iter() {
+        println!(
+            "  output              {} {} x {}",
+            output_kind(output.kind),
+            output.mint,
+            output.amount
+        );
+    }
+    for entry in recipe.loot_table.iter() {
+        println!(
+            "  loot                weight {} => {} {} x {}",
+            entry.weight,
+            output_kind(entry.output.kind),
+            entry.output.mint,
+            entry.output.amount
+        );
+    }
+    if let Some(randomness_program) = recipe.randomness_program {
+        println!("  randomness program  {}", randomness_program);
+    }
+    if recipe.reveal_delay > 0 {
+        println!("  reveal delay        {} slots", recipe.reveal_delay);
+        println!("  reveal timeout      {} slots", recipe.reveal_timeout);
+    }
+    if recipe.craft_duration > 0 {
+        println!("  craft duration      {} s", recipe.craft_duration);
+    }
+    if let Some(speed_up_mint) = recipe.speed_up_mint {
+        println!(
+            "  speed up            {} x {}",
+            speed_up_mint, recipe.speed_up_amount
+        );
+    }
+    println!("  cancel penalty      {} bps", recipe.cancel_penalty_bps);
+    println!("  salvage             {} bps", recipe.salvage_bps);
+    for input in recipe.input_skins.iter() {
+        let consume = match input.consume {
+            SkinConsume::Burn => "burn",
+            SkinConsume::Escrow => "escrow",
+        };
+        println!("  input skin          {} ({})", input.collection, consume);
+    }
+    match recipe.gate {
+        Some(RecipeGate::TokenBalance { mint, min_amount }) => {
+            println!("  gate                {} >= {}", mint, min_amount)
+        }
+        Some(RecipeGate::Collection { collection }) => {
+            println!("  gate                collection {}", collection)
+        }
+        None => {}
+    }
+    if let Some(root) = recipe.allowlist_root {
+        println!("  allowlist root      {}", format_hash(&root));
+    }
+}
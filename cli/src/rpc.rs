@@ -0,0 +1,60 @@
+/*
+    transactions sent by the CLI over craft_skins_client::rpc::Rpc, commitment is always confirmed
+    account reads come with Rpc itself
+*/
+use anyhow::{anyhow, Result};
+use serde_json::json;
+use solana_sdk::{hash::Hash, signature::Signature, transaction::Transaction};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+pub use craft_skins_client::rpc::{Rpc, MAX_MULTIPLE_ACCOUNTS};
+
+const CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+const CONFIRM_POLL: Duration = Duration::from_millis(500);
+
+pub trait SendRpc {
+    fn latest_blockhash(&self) -> Result<Hash>;
+    // sends + polls the signature status until confirmed, transaction errors are returned
+    fn send_and_confirm(&self, transaction: &Transaction) -> Result<Signature>;
+}
+
+impl SendRpc for Rpc {
+    fn latest_blockhash(&self) -> Result<Hash> {
+        let result = self.call("getLatestBlockhash", json!([{"commitment": "confirmed"}]))?;
+        let blockhash = result["value"]["blockhash"]
+            .as_str()
+            .ok_or_else(|| anyhow!("getLatestBlockhash returned no blockhash"))?;
+        Ok(blockhash.parse()?)
+    }
+
+    fn send_and_confirm(&self, transaction: &Transaction) -> Result<Signature> {
+        let encoded = base64::encode(bincode::serialize(transaction)?);
+        let config = json!({"encoding": "base64", "preflightCommitment": "confirmed"});
+        let signature: Signature = self
+            .call("sendTransaction", json!([encoded, config]))?
+            .as_str()
+            .ok_or_else(|| anyhow!("sendTransaction returned no signature"))?
+            .parse()?;
+
+        let started = Instant::now();
+        while started.elapsed() < CONFIRM_TIMEOUT {
+            let result = self.call("getSignatureStatuses", json!([[signature.to_string()]]))?;
+            let status = &result["value"][0];
+            if !status.is_null() {
+                if !status["err"].is_null() {
+                    return Err(anyhow!(
+                        "transaction {} failed: {}",
+                        signature,
+                        status["err"]
+                    ));
+                }
+                if let Some("confirmed" | "finalized") = status["confirmationStatus"].as_str() {
+                    return Ok(signature);
+                }
+            }
+            sleep(CONFIRM_POLL);
+        }
+        Err(anyhow!("transaction {} was not confirmed", signature))
+    }
+}
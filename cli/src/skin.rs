@@ -0,0 +1,92 @@
+/*
+    skin add|list
+    skins stay with the Recipe NFT holder until crafted, list scans the holder's NFTs
+    for metadata verified in the recipe collection
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use clap::Subcommand;
+use craft_skins_client::instruction;
+use craft_skins_client::pda::metadata_pda;
+use mpl_token_metadata::state::{Key, Metadata, MAX_METADATA_LEN};
+use mpl_token_metadata::utils::try_from_slice_checked;
+
+use crate::cluster::Cluster;
+use crate::rpc::MAX_MULTIPLE_ACCOUNTS;
+
+#[derive(Subcommand)]
+pub enum SkinCommand {
+    /// Verify a skin held by the signer into the recipe collection
+    Add {
+        recipe_mint: Pubkey,
+        skin_mint: Pubkey,
+    },
+    /// List skins of a recipe held by the Recipe NFT holder
+    List {
+        recipe_mint: Pubkey,
+        /// Holder of the skins [default: signer]
+        #[clap(long)]
+        owner: Option<Pubkey>,
+    },
+}
+
+pub fn run(cluster: &Cluster, command: SkinCommand) -> Result<()> {
+    match command {
+        SkinCommand::Add {
+            recipe_mint,
+            skin_mint,
+        } => {
+            let ix = instruction::add_skin(&cluster.payer(), &recipe_mint, &skin_mint);
+            let signature = cluster.send(&[ix], &[])?;
+            println!("Signature {}", signature);
+        }
+        SkinCommand::List { recipe_mint, owner } => {
+            let owner = owner.unwrap_or_else(|| cluster.payer());
+            for (mint, metadata) in recipe_skins(cluster, &recipe_mint, &owner)? {
+                println!(
+                    "{} {} {}",
+                    mint,
+                    metadata.data.name.trim_end_matches('\0'),
+                    metadata.data.uri.trim_end_matches('\0')
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+// (mint, metadata) of NFTs held by owner and verified in the recipe_mint collection
+fn recipe_skins(
+    cluster: &Cluster,
+    recipe_mint: &Pubkey,
+    owner: &Pubkey,
+) -> Result<Vec<(Pubkey, Metadata)>> {
+    let mints: Vec<Pubkey> = cluster
+        .token_accounts(owner)?
+        .into_iter()
+        .filter(|(_, token)| token.amount == 1)
+        .map(|(_, token)| token.mint)
+        .collect();
+
+    let mut skins = vec![];
+    for chunk in mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+        let keys: Vec<Pubkey> = chunk.iter().map(metadata_pda).collect();
+        let accounts = cluster.rpc.multiple_account_data(&keys)?;
+        for (mint, data) in chunk.iter().zip(accounts) {
+            let metadata: Metadata = match data.and_then(|data| {
+                try_from_slice_checked(&data, Key::MetadataV1, MAX_METADATA_LEN).ok()
+            }) {
+                Some(metadata) => metadata,
+                None => continue,
+            };
+            let verified = matches!(
+                &metadata.collection,
+                Some(collection) if collection.verified && collection.key == *recipe_mint
+            );
+            if verified {
+                skins.push((*mint, metadata));
+            }
+        }
+    }
+    Ok(skins)
+}
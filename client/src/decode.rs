@@ -0,0 +1,116 @@
+/*
+    craft_skins accounts + events as read by indexers
+      decode_account    => any program account, type picked from its Anchor discriminator
+      parse_craft_events => CraftEvents of a transaction's log messages (meta.logMessages)
+    discriminators of types not known to this crate decode as None, so indexers can
+    skip accounts added by newer program versions
+*/
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+
+use craft_skins::utils::{CraftEvent, CraftJob, LargeRecipe, PendingCraft, PlayerProfile, Recipe};
+use craft_skins::Manager;
+
+pub enum CraftSkinsAccount {
+    Manager(Manager),
+    Recipe(Recipe),
+    LargeRecipe(Box<LargeRecipe>),
+    PendingCraft(PendingCraft),
+    CraftJob(CraftJob),
+    PlayerProfile(PlayerProfile),
+}
+
+impl CraftSkinsAccount {
+    // account type name as in the program (and IDL)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CraftSkinsAccount::Manager(_) => "Manager",
+            CraftSkinsAccount::Recipe(_) => "Recipe",
+            CraftSkinsAccount::LargeRecipe(_) => "LargeRecipe",
+            CraftSkinsAccount::PendingCraft(_) => "PendingCraft",
+            CraftSkinsAccount::CraftJob(_) => "CraftJob",
+            CraftSkinsAccount::PlayerProfile(_) => "PlayerProfile",
+        }
+    }
+}
+
+/*
+    data of an account owned by craft_skins
+    Ok(None)  => data too short for a discriminator, or discriminator of an unknown type
+    Err       => known discriminator but the data does not deserialize
+*/
+pub fn decode_account(data: &[u8]) -> anchor_lang::Result<Option<CraftSkinsAccount>> {
+    let discriminator = match data.get(..8) {
+        Some(discriminator) => discriminator,
+        None => return Ok(None),
+    };
+    let data = &mut &*data;
+    let account = if discriminator == Manager::discriminator() {
+        CraftSkinsAccount::Manager(Manager::try_deserialize(data)?)
+    } else if discriminator == Recipe::discriminator() {
+        CraftSkinsAccount::Recipe(Recipe::try_deserialize(data)?)
+    } else if discriminator == LargeRecipe::discriminator() {
+        // zero-copy, read as is from the (unaligned) data after the discriminator
+        let body = data
+            .get(8..LargeRecipe::LEN)
+            .ok_or(anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+        CraftSkinsAccount::LargeRecipe(Box::new(bytemuck::pod_read_unaligned(body)))
+    } else if discriminator == PendingCraft::discriminator() {
+        CraftSkinsAccount::PendingCraft(PendingCraft::try_deserialize(data)?)
+    } else if discriminator == CraftJob::discriminator() {
+        CraftSkinsAccount::CraftJob(CraftJob::try_deserialize(data)?)
+    } else if discriminator == PlayerProfile::discriminator() {
+        CraftSkinsAccount::PlayerProfile(PlayerProfile::try_deserialize(data)?)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(account))
+}
+
+/*
+    emit! logs "Program data: <base64 of discriminator || event>"
+    only data logged while craft_skins is the innermost running program is read,
+    so events of other programs (or CPIs into them) are ignored
+      "Program <id> invoke [depth]"       => push id
+      "Program <id> success" / "failed"   => pop
+    logs of a failed transaction still hold events of the instructions run before
+    the failure, callers skip transactions with meta.err set
+*/
+pub fn parse_craft_events(logs: &[String]) -> Vec<CraftEvent> {
+    let program_id = craft_skins::id().to_string();
+    let mut stack: Vec<&str> = vec![];
+    let mut events = vec![];
+    for log in logs.iter() {
+        if let Some(data) = log.strip_prefix("Program data: ") {
+            if stack.last() == Some(&program_id.as_str()) {
+                events.extend(data.split(' ').filter_map(decode_craft_event));
+            }
+            continue;
+        }
+        let mut words = log.split(' ');
+        if let (Some("Program"), Some(id), Some(status)) =
+            (words.next(), words.next(), words.next())
+        {
+            // "Program log: ..." lines of programs can look like invoke / success lines
+            if id.parse::<Pubkey>().is_err() {
+                continue;
+            }
+            match status {
+                "invoke" => stack.push(id),
+                "success" | "failed:" => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+    events
+}
+
+fn decode_craft_event(data: &str) -> Option<CraftEvent> {
+    let data = base64::decode(data).ok()?;
+    if data.get(..8)? != CraftEvent::discriminator() {
+        return None;
+    }
+    CraftEvent::deserialize(&mut &data[8..]).ok()
+}
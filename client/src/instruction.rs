@@ -0,0 +1,471 @@
+/*
+    typed builders of craft_skins instructions
+    remaining_accounts are passed through, see remaining.rs for their layout
+*/
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::solana_program::{instruction::Instruction, system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use craft_skins::utils::{Recipe, RecipeCategory, RecipeMode, RecipeOutput, RecipeSettings};
+
+use crate::pda::*;
+
+pub fn initialize(admin: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::Initialize {
+            manager: *admin,
+            program_manager: manager_pda(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::Initialize {}.data(),
+    }
+}
+
+// args of an unnamed, Uncategorized Fixed mode recipe without fee, outputs, timing or gates
+pub fn recipe_args(ingredient_mints: Vec<Pubkey>, ingredient_amounts: Vec<u64>) -> RecipeSettings {
+    RecipeSettings {
+        ingredient_mints,
+        ingredient_amounts,
+        craft_fee: 0,
+        royalty_bps: 0,
+        outputs: vec![],
+        mode: RecipeMode::Fixed,
+        loot_table: vec![],
+        randomness_program: None,
+        reveal_delay: 0,
+        reveal_timeout: 0,
+        craft_duration: 0,
+        speed_up_mint: None,
+        speed_up_amount: 0,
+        cancel_penalty_bps: 0,
+        salvage_bps: 0,
+        input_skins: vec![],
+        gate: None,
+        allowlist_root: None,
+        name: String::new(),
+        description: String::new(),
+        category: RecipeCategory::Uncategorized,
+        sort_order: 0,
+    }
+}
+
+// settings of a fetched Recipe, edited before update_recipe
+pub fn recipe_args_from(recipe: &Recipe) -> RecipeSettings {
+    RecipeSettings {
+        ingredient_mints: recipe.mints.clone(),
+        ingredient_amounts: recipe.amounts.clone(),
+        craft_fee: recipe.craft_fee,
+        royalty_bps: recipe.royalty_bps,
+        outputs: recipe.outputs.clone(),
+        mode: recipe.mode,
+        loot_table: recipe.loot_table.clone(),
+        randomness_program: recipe.randomness_program,
+        reveal_delay: recipe.reveal_delay,
+        reveal_timeout: recipe.reveal_timeout,
+        craft_duration: recipe.craft_duration,
+        speed_up_mint: recipe.speed_up_mint,
+        speed_up_amount: recipe.speed_up_amount,
+        cancel_penalty_bps: recipe.cancel_penalty_bps,
+        salvage_bps: recipe.salvage_bps,
+        input_skins: recipe.input_skins.clone(),
+        gate: recipe.gate,
+        allowlist_root: recipe.allowlist_root,
+        name: recipe.name.clone(),
+        description: recipe.description.clone(),
+        category: recipe.category,
+        sort_order: recipe.sort_order,
+    }
+}
+
+// owner holds the Recipe NFT of recipe_mint
+pub fn create_recipe(owner: &Pubkey, recipe_mint: &Pubkey, args: RecipeSettings) -> Instruction {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::CreateRecipe {
+            owner: *owner,
+            recipe: recipe_pda(recipe_mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            rent_account: sysvar::rent::id(),
+            token_metadata_program: mpl_token_metadata::id(),
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::CreateRecipe { settings: args }.data(),
+    }
+}
+
+// every setting is replaced by args, recipe revision is bumped
+pub fn update_recipe(owner: &Pubkey, recipe_mint: &Pubkey, args: RecipeSettings) -> Instruction {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::UpdateRecipe {
+            owner: *owner,
+            recipe: recipe_pda(recipe_mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::UpdateRecipe { settings: args }.data(),
+    }
+}
+
+pub fn close_recipe(owner: &Pubkey, recipe_mint: &Pubkey) -> Instruction {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::CloseRecipe {
+            owner: *owner,
+            recipe: recipe_pda(recipe_mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::CloseRecipe {}.data(),
+    }
+}
+
+// owner holds the Recipe NFT and the skin verified in its collection
+pub fn add_skin(owner: &Pubkey, recipe_mint: &Pubkey, skin_mint: &Pubkey) -> Instruction {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    let skin_nft = NftAccounts::new(skin_mint, owner);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::AddSkin {
+            owner: *owner,
+            recipe: recipe_pda(recipe_mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            skin_token_account: skin_nft.token,
+            skin_mint: skin_nft.mint,
+            skin_metadata: skin_nft.metadata,
+            rent_account: sysvar::rent::id(),
+            token_metadata_program: mpl_token_metadata::id(),
+            token_program: anchor_spl::token::ID,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::AddSkin { _recipe_bump: 0 }.data(),
+    }
+}
+
+/*
+    signers and fee recipients of a craft
+      owner  => creator + holder of the skins delivered, signs their transfer
+      holder => holder of the Recipe NFT (ATA), receives royalty, owner until the NFT is sold
+      user   => crafter, pays ingredients, fee and rent
+      admin  => Manager admin, receives craft fee minus royalty
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct CraftAccounts {
+    pub owner: Pubkey,
+    pub holder: Pubkey,
+    pub user: Pubkey,
+    pub admin: Pubkey,
+    pub recipe_mint: Pubkey,
+}
+
+// allowance + proof are ignored unless the recipe has an allowlist
+pub fn craft_skin(
+    craft: &CraftAccounts,
+    skin_mint: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+    allowance: u32,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let recipe_nft = NftAccounts::new(&craft.recipe_mint, &craft.holder);
+    let skin_nft = NftAccounts::new(skin_mint, &craft.owner);
+    let mut accounts = craft_skins::accounts::CraftSkin {
+        owner: craft.owner,
+        user: craft.user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: craft.admin,
+        recipe_holder: craft.holder,
+        player_profile: profile_pda(&craft.user),
+        recipe: recipe_pda(&craft.recipe_mint),
+        recipe_vault: recipe_vault_pda(&recipe_pda(&craft.recipe_mint)),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        user_skin_token_account: get_associated_token_address(&craft.user, skin_mint),
+        skin_token_account: skin_nft.token,
+        skin_mint: skin_nft.mint,
+        skin_metadata: skin_nft.metadata,
+        rent_account: sysvar::rent::id(),
+        token_metadata_program: mpl_token_metadata::id(),
+        token_program: anchor_spl::token::ID,
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(remaining_accounts);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CraftSkin { allowance, proof }.data(),
+    }
+}
+
+// skins are passed in remaining_accounts, see remaining::batch_skin_accounts
+pub fn craft_skin_batch(
+    craft: &CraftAccounts,
+    quantity: u8,
+    remaining_accounts: Vec<AccountMeta>,
+    allowance: u32,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let recipe_nft = NftAccounts::new(&craft.recipe_mint, &craft.holder);
+    let mut accounts = craft_skins::accounts::CraftSkinBatch {
+        owner: craft.owner,
+        user: craft.user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: craft.admin,
+        recipe_holder: craft.holder,
+        player_profile: profile_pda(&craft.user),
+        recipe: recipe_pda(&craft.recipe_mint),
+        recipe_vault: recipe_vault_pda(&recipe_pda(&craft.recipe_mint)),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        rent_account: sysvar::rent::id(),
+        token_metadata_program: mpl_token_metadata::id(),
+        token_program: anchor_spl::token::ID,
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(remaining_accounts);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CraftSkinBatch {
+            quantity,
+            allowance,
+            proof,
+        }
+        .data(),
+    }
+}
+
+// admin moves amount of escrowed mint to destination token account
+pub fn withdraw_escrow(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::WithdrawEscrow {
+            admin: *admin,
+            program_manager: manager_pda(),
+            program_signer: signer_pda(),
+            mint: *mint,
+            escrow_token_account: escrow_token(mint),
+            destination: *destination,
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::WithdrawEscrow { amount }.data(),
+    }
+}
+
+/*
+    Recipe NFT holder moves amount of mint out of a recipe vault to destination token account
+    recipe is the Recipe or LargeRecipe PDA of recipe_mint owning the vault
+*/
+pub fn withdraw_vault(
+    owner: &Pubkey,
+    recipe: &Pubkey,
+    recipe_mint: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::WithdrawVault {
+            owner: *owner,
+            recipe: *recipe,
+            recipe_vault: recipe_vault_pda(recipe),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            mint: *mint,
+            vault_token_account: vault_token(recipe, mint),
+            destination: *destination,
+            token_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::WithdrawVault { amount }.data(),
+    }
+}
+
+// rewrite the Recipe of recipe_mint in the current layout, payer funds any extra rent
+pub fn migrate_recipe(payer: &Pubkey, recipe_mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::MigrateRecipe {
+            payer: *payer,
+            recipe: recipe_pda(recipe_mint),
+            recipe_mint: *recipe_mint,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::MigrateRecipe {}.data(),
+    }
+}
+
+pub fn migrate_manager(payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::MigrateManager {
+            payer: *payer,
+            program_manager: manager_pda(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::MigrateManager {}.data(),
+    }
+}
+
+// owner holds the Recipe NFT of recipe_mint, LargeRecipe starts without ingredients
+pub fn create_large_recipe(
+    owner: &Pubkey,
+    recipe_mint: &Pubkey,
+    craft_fee: u64,
+    royalty_bps: u16,
+) -> Instruction {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::CreateLargeRecipe {
+            owner: *owner,
+            large_recipe: large_recipe_pda(recipe_mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::CreateLargeRecipe {
+            craft_fee,
+            royalty_bps,
+        }
+        .data(),
+    }
+}
+
+fn update_large_recipe_accounts(owner: &Pubkey, recipe_mint: &Pubkey) -> Vec<AccountMeta> {
+    let recipe_nft = NftAccounts::new(recipe_mint, owner);
+    craft_skins::accounts::UpdateLargeRecipe {
+        owner: *owner,
+        large_recipe: large_recipe_pda(recipe_mint),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+    }
+    .to_account_metas(None)
+}
+
+// ingredients + outputs are added after the current ones, within LargeRecipe::MAX_CRAFT_ACCOUNTS
+pub fn append_large_recipe(
+    owner: &Pubkey,
+    recipe_mint: &Pubkey,
+    ingredient_mints: Vec<Pubkey>,
+    ingredient_amounts: Vec<u64>,
+    outputs: Vec<RecipeOutput>,
+) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: update_large_recipe_accounts(owner, recipe_mint),
+        data: craft_skins::instruction::AppendLargeRecipe {
+            ingredient_mints,
+            ingredient_amounts,
+            outputs,
+        }
+        .data(),
+    }
+}
+
+// ingredients + outputs are emptied, fee settings replaced
+pub fn reset_large_recipe(
+    owner: &Pubkey,
+    recipe_mint: &Pubkey,
+    craft_fee: u64,
+    royalty_bps: u16,
+) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: update_large_recipe_accounts(owner, recipe_mint),
+        data: craft_skins::instruction::ResetLargeRecipe {
+            craft_fee,
+            royalty_bps,
+        }
+        .data(),
+    }
+}
+
+/*
+    remaining_accounts => remaining::large_ingredient_accounts + large_output_accounts
+    at most LargeRecipe::MAX_CRAFT_ACCOUNTS of them, the craft fits one legacy transaction
+*/
+pub fn craft_large_skin(
+    craft: &CraftAccounts,
+    skin_mint: &Pubkey,
+    remaining_accounts: Vec<AccountMeta>,
+) -> Instruction {
+    let recipe_nft = NftAccounts::new(&craft.recipe_mint, &craft.holder);
+    let skin_nft = NftAccounts::new(skin_mint, &craft.owner);
+    let mut accounts = craft_skins::accounts::CraftLargeSkin {
+        owner: craft.owner,
+        user: craft.user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: craft.admin,
+        recipe_holder: craft.holder,
+        large_recipe: large_recipe_pda(&craft.recipe_mint),
+        recipe_vault: recipe_vault_pda(&large_recipe_pda(&craft.recipe_mint)),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        user_skin_token_account: get_associated_token_address(&craft.user, skin_mint),
+        skin_token_account: skin_nft.token,
+        skin_mint: skin_nft.mint,
+        skin_metadata: skin_nft.metadata,
+        rent_account: sysvar::rent::id(),
+        token_metadata_program: mpl_token_metadata::id(),
+        token_program: anchor_spl::token::ID,
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(remaining_accounts);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CraftLargeSkin {}.data(),
+    }
+}
@@ -0,0 +1,42 @@
+/*
+    client helpers for craft_skins, used by backends building transactions in Rust
+      pda         => addresses of program accounts, escrows + token metadata accounts
+      instruction => typed builders of craft_skins instructions
+      remaining   => remaining_accounts layouts read by craft_skin / craft_skin_batch
+      manifest    => recipe settings in TOML / JSON, validated + diffed against a Recipe
+      decode      => any program account by discriminator + CraftEvents of transaction logs
+      simulate    => craft_skin rules run against a wallet before sending a transaction
+      rpc         => blocking JSON-RPC account reads shared by the CLI and the indexer
+*/
+// decoders + checks return anchor_lang::Result like the program, the size of its Error is set by anchor
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::AccountDeserialize;
+
+pub mod decode;
+pub mod instruction;
+pub mod manifest;
+pub mod pda;
+pub mod remaining;
+pub mod rpc;
+pub mod simulate;
+
+pub use craft_skins::utils::{
+    CraftEvent, CraftJob, LargeRecipe, LargeRecipeOutput, LootEntry, LootRoll, OutputKind,
+    PendingCraft, PlayerProfile, Recipe, RecipeCategory, RecipeGate, RecipeMode, RecipeOutput,
+    RecipeSettings, SkinConsume, SkinInput,
+};
+pub use craft_skins::{id, Manager};
+
+// Recipe account data fetched from RPC, discriminator is checked
+pub fn decode_recipe(data: &[u8]) -> anchor_lang::Result<Recipe> {
+    Recipe::try_deserialize(&mut &*data)
+}
+
+pub fn decode_manager(data: &[u8]) -> anchor_lang::Result<Manager> {
+    Manager::try_deserialize(&mut &*data)
+}
+
+pub fn decode_player_profile(data: &[u8]) -> anchor_lang::Result<PlayerProfile> {
+    PlayerProfile::try_deserialize(&mut &*data)
+}
@@ -0,0 +1,629 @@
+/*
+    recipe manifest => every setting of one Recipe in a TOML or JSON file
+      recipe_mint                        Recipe NFT the settings belong to
+      name, description, sort_order      listing fields stored on the Recipe
+      category                           "uncategorized" | "weapon" | "armor" | "character" |
+                                         "vehicle" | "cosmetic" | "consumable" | "bundle"
+      mode                               "fixed" | "random"
+      craft_fee, royalty_bps             lamports + holder share of each craft
+      [[ingredients]]   mint, amount
+      [[outputs]]       kind, mint, amount
+      [[loot]]          weight, kind, mint, amount (random mode)
+      [reveal]          delay, timeout, randomness_program (commit-reveal window)
+      [timing]          craft_duration, speed_up_mint, speed_up_amount (timed recipes)
+      cancel_penalty_bps, salvage_bps
+      [[input_skins]]   collection, consume (upgrade recipes)
+      [gate]            token + min_amount | collection
+      allowlist_root    64 hex characters
+    omitted settings take the create_recipe defaults, unknown keys are rejected
+
+    validate runs the same write_recipe_settings as create_recipe / update_recipe,
+    diff compares against the fetched Recipe and builds the instruction applying the manifest
+*/
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use craft_skins::utils::{
+    write_recipe_settings, LootEntry, OutputKind, Recipe, RecipeCategory, RecipeGate, RecipeMode,
+    RecipeOutput, RecipeSettings, SkinConsume, SkinInput,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::instruction;
+
+#[derive(Debug)]
+pub enum ManifestError {
+    // file is not valid TOML / JSON or does not match the schema
+    Parse(String),
+    // setting breaks a create_recipe rule, code is the craft_skins ErrorCode number
+    Rule {
+        code: u32,
+        name: String,
+        msg: String,
+    },
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManifestError::Parse(msg) => write!(f, "manifest is invalid: {}", msg),
+            ManifestError::Rule { name, msg, .. } => write!(f, "{}: {}", name, msg),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+impl From<anchor_lang::error::Error> for ManifestError {
+    fn from(err: anchor_lang::error::Error) -> Self {
+        match err {
+            anchor_lang::error::Error::AnchorError(err) => ManifestError::Rule {
+                code: err.error_code_number,
+                name: err.error_name,
+                msg: err.error_msg,
+            },
+            anchor_lang::error::Error::ProgramError(err) => {
+                ManifestError::Parse(err.program_error.to_string())
+            }
+        }
+    }
+}
+
+// plain values come before tables + empty lists are skipped so the manifest serializes to TOML
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecipeManifest {
+    #[serde(with = "pubkey_string")]
+    pub recipe_mint: Pubkey,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default)]
+    pub category: ManifestCategory,
+    #[serde(default)]
+    pub sort_order: u16,
+    #[serde(default)]
+    pub mode: ManifestMode,
+    #[serde(default)]
+    pub craft_fee: u64,
+    #[serde(default)]
+    pub royalty_bps: u16,
+    #[serde(default)]
+    pub cancel_penalty_bps: u16,
+    #[serde(default)]
+    pub salvage_bps: u16,
+    #[serde(default, with = "hash_string", skip_serializing_if = "Option::is_none")]
+    pub allowlist_root: Option<[u8; 32]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gate: Option<ManifestGate>,
+    #[serde(default)]
+    pub reveal: ManifestReveal,
+    #[serde(default)]
+    pub timing: ManifestTiming,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ingredients: Vec<ManifestIngredient>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<ManifestOutput>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub loot: Vec<ManifestLoot>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub input_skins: Vec<ManifestInputSkin>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestMode {
+    #[default]
+    Fixed,
+    Random,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestCategory {
+    #[default]
+    Uncategorized,
+    Weapon,
+    Armor,
+    Character,
+    Vehicle,
+    Cosmetic,
+    Consumable,
+    Bundle,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestIngredient {
+    #[serde(with = "pubkey_string")]
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestOutputKind {
+    Skin,
+    Fungible,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestOutput {
+    pub kind: ManifestOutputKind,
+    #[serde(with = "pubkey_string")]
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestLoot {
+    pub weight: u32,
+    pub kind: ManifestOutputKind,
+    #[serde(with = "pubkey_string")]
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+// commit-reveal window in slots, delay 0 => single transaction craft_random_skin
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestReveal {
+    #[serde(default)]
+    pub delay: u64,
+    #[serde(default)]
+    pub timeout: u64,
+    #[serde(
+        default,
+        with = "option_pubkey_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub randomness_program: Option<Pubkey>,
+}
+
+// craft_duration in seconds, 0 => skin delivered by craft_skin
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestTiming {
+    #[serde(default)]
+    pub craft_duration: i64,
+    #[serde(
+        default,
+        with = "option_pubkey_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub speed_up_mint: Option<Pubkey>,
+    #[serde(default)]
+    pub speed_up_amount: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestConsume {
+    Burn,
+    Escrow,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestInputSkin {
+    #[serde(with = "pubkey_string")]
+    pub collection: Pubkey,
+    pub consume: ManifestConsume,
+}
+
+// { token = "<mint>", min_amount = n } or { collection = "<mint>" }
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestGate {
+    TokenBalance {
+        #[serde(with = "pubkey_string")]
+        token: Pubkey,
+        min_amount: u64,
+    },
+    Collection {
+        #[serde(with = "pubkey_string")]
+        collection: Pubkey,
+    },
+}
+
+impl RecipeManifest {
+    pub fn from_toml(text: &str) -> Result<Self, ManifestError> {
+        toml::from_str(text).map_err(|err| ManifestError::Parse(err.to_string()))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, ManifestError> {
+        serde_json::from_str(text).map_err(|err| ManifestError::Parse(err.to_string()))
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("manifest serializes to TOML")
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("manifest serializes to JSON")
+    }
+
+    // manifest of a fetched Recipe, recipe_mint is not stored in the account
+    pub fn from_recipe(recipe_mint: &Pubkey, recipe: &Recipe) -> Self {
+        RecipeManifest {
+            recipe_mint: *recipe_mint,
+            name: recipe.name.clone(),
+            description: recipe.description.clone(),
+            category: recipe.category.into(),
+            sort_order: recipe.sort_order,
+            mode: match recipe.mode {
+                RecipeMode::Fixed => ManifestMode::Fixed,
+                RecipeMode::Random => ManifestMode::Random,
+            },
+            craft_fee: recipe.craft_fee,
+            royalty_bps: recipe.royalty_bps,
+            ingredients: recipe
+                .mints
+                .iter()
+                .zip(recipe.amounts.iter())
+                .map(|(mint, amount)| ManifestIngredient {
+                    mint: *mint,
+                    amount: *amount,
+                })
+                .collect(),
+            outputs: recipe
+                .outputs
+                .iter()
+                .map(|output| ManifestOutput {
+                    kind: output.kind.into(),
+                    mint: output.mint,
+                    amount: output.amount,
+                })
+                .collect(),
+            loot: recipe
+                .loot_table
+                .iter()
+                .map(|entry| ManifestLoot {
+                    weight: entry.weight,
+                    kind: entry.output.kind.into(),
+                    mint: entry.output.mint,
+                    amount: entry.output.amount,
+                })
+                .collect(),
+            reveal: ManifestReveal {
+                delay: recipe.reveal_delay,
+                timeout: recipe.reveal_timeout,
+                randomness_program: recipe.randomness_program,
+            },
+            timing: ManifestTiming {
+                craft_duration: recipe.craft_duration,
+                speed_up_mint: recipe.speed_up_mint,
+                speed_up_amount: recipe.speed_up_amount,
+            },
+            cancel_penalty_bps: recipe.cancel_penalty_bps,
+            salvage_bps: recipe.salvage_bps,
+            input_skins: recipe
+                .input_skins
+                .iter()
+                .map(|input| ManifestInputSkin {
+                    collection: input.collection,
+                    consume: match input.consume {
+                        SkinConsume::Burn => ManifestConsume::Burn,
+                        SkinConsume::Escrow => ManifestConsume::Escrow,
+                    },
+                })
+                .collect(),
+            gate: recipe.gate.map(|gate| match gate {
+                RecipeGate::TokenBalance { mint, min_amount } => ManifestGate::TokenBalance {
+                    token: mint,
+                    min_amount,
+                },
+                RecipeGate::Collection { collection } => ManifestGate::Collection { collection },
+            }),
+            allowlist_root: recipe.allowlist_root,
+        }
+    }
+
+    // create_recipe / update_recipe args, not validated
+    pub fn recipe_args(&self) -> RecipeSettings {
+        RecipeSettings {
+            ingredient_mints: self.ingredients.iter().map(|i| i.mint).collect(),
+            ingredient_amounts: self.ingredients.iter().map(|i| i.amount).collect(),
+            craft_fee: self.craft_fee,
+            royalty_bps: self.royalty_bps,
+            outputs: self
+                .outputs
+                .iter()
+                .map(|output| RecipeOutput {
+                    kind: output.kind.into(),
+                    mint: output.mint,
+                    amount: output.amount,
+                })
+                .collect(),
+            mode: match self.mode {
+                ManifestMode::Fixed => RecipeMode::Fixed,
+                ManifestMode::Random => RecipeMode::Random,
+            },
+            loot_table: self
+                .loot
+                .iter()
+                .map(|entry| LootEntry {
+                    weight: entry.weight,
+                    output: RecipeOutput {
+                        kind: entry.kind.into(),
+                        mint: entry.mint,
+                        amount: entry.amount,
+                    },
+                })
+                .collect(),
+            randomness_program: self.reveal.randomness_program,
+            reveal_delay: self.reveal.delay,
+            reveal_timeout: self.reveal.timeout,
+            craft_duration: self.timing.craft_duration,
+            speed_up_mint: self.timing.speed_up_mint,
+            speed_up_amount: self.timing.speed_up_amount,
+            cancel_penalty_bps: self.cancel_penalty_bps,
+            salvage_bps: self.salvage_bps,
+            input_skins: self
+                .input_skins
+                .iter()
+                .map(|input| SkinInput {
+                    collection: input.collection,
+                    consume: match input.consume {
+                        ManifestConsume::Burn => SkinConsume::Burn,
+                        ManifestConsume::Escrow => SkinConsume::Escrow,
+                    },
+                })
+                .collect(),
+            gate: self.gate.as_ref().map(|gate| match *gate {
+                ManifestGate::TokenBalance { token, min_amount } => RecipeGate::TokenBalance {
+                    mint: token,
+                    min_amount,
+                },
+                ManifestGate::Collection { collection } => RecipeGate::Collection { collection },
+            }),
+            allowlist_root: self.allowlist_root,
+            name: self.name.clone(),
+            description: self.description.clone(),
+            category: self.category.into(),
+            sort_order: self.sort_order,
+        }
+    }
+
+    // args accepted by create_recipe / update_recipe, checked by the program's own rules
+    pub fn validate(&self) -> Result<RecipeSettings, ManifestError> {
+        write_recipe_settings(&mut scratch_recipe(), self.recipe_args())?;
+        Ok(self.recipe_args())
+    }
+
+    /*
+        changes from on_chain to the manifest + the instructions applying them
+          None on chain   => create_recipe
+          older layout    => migrate_recipe first, update_recipe refuses it
+          changes         => update_recipe
+          up to date      => no instruction
+    */
+    pub fn diff(
+        &self,
+        owner: &Pubkey,
+        on_chain: Option<&Recipe>,
+    ) -> Result<RecipeDiff, ManifestError> {
+        let args = self.validate()?;
+        let recipe = match on_chain {
+            Some(recipe) => recipe,
+            None => {
+                return Ok(RecipeDiff {
+                    changes: vec![],
+                    instructions: vec![instruction::create_recipe(owner, &self.recipe_mint, args)],
+                })
+            }
+        };
+
+        let current = serde_json::to_value(RecipeManifest::from_recipe(&self.recipe_mint, recipe))
+            .expect("manifest serializes to JSON");
+        let wanted = serde_json::to_value(self).expect("manifest serializes to JSON");
+        let null = serde_json::Value::Null;
+        let mut fields: Vec<&String> = current
+            .as_object()
+            .into_iter()
+            .chain(wanted.as_object())
+            .flat_map(|object| object.keys())
+            .collect();
+        fields.sort();
+        fields.dedup();
+        let changes: Vec<FieldChange> = fields
+            .into_iter()
+            .filter_map(|field| {
+                let from = current.get(field).unwrap_or(&null);
+                let to = wanted.get(field).unwrap_or(&null);
+                (from != to).then(|| FieldChange {
+                    field: field.clone(),
+                    on_chain: from.to_string(),
+                    manifest: to.to_string(),
+                })
+            })
+            .collect();
+
+        let mut instructions = vec![];
+        if recipe.version < Recipe::VERSION {
+            instructions.push(instruction::migrate_recipe(owner, &self.recipe_mint));
+        }
+        if !changes.is_empty() {
+            instructions.push(instruction::update_recipe(owner, &self.recipe_mint, args));
+        }
+        Ok(RecipeDiff {
+            changes,
+            instructions,
+        })
+    }
+}
+
+// setting that differs between the on-chain Recipe and the manifest, values as JSON
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FieldChange {
+    pub field: String,
+    pub on_chain: String,
+    pub manifest: String,
+}
+
+#[derive(Debug)]
+pub struct RecipeDiff {
+    pub changes: Vec<FieldChange>,
+    // sent in order in one transaction, empty when the Recipe is up to date
+    pub instructions: Vec<Instruction>,
+}
+
+// Recipe written by validate, rules are applied off chain by writing settings into it
+fn scratch_recipe() -> Recipe {
+    Recipe {
+        mints: vec![],
+        amounts: vec![],
+        craft_fee: 0,
+        royalty_bps: 0,
+        outputs: vec![],
+        mode: RecipeMode::Fixed,
+        loot_table: vec![],
+        randomness_program: None,
+        reveal_delay: 0,
+        reveal_timeout: 0,
+        craft_duration: 0,
+        speed_up_mint: None,
+        speed_up_amount: 0,
+        cancel_penalty_bps: 0,
+        salvage_bps: 0,
+        input_skins: vec![],
+        revision: 0,
+        gate: None,
+        allowlist_root: None,
+        bump: 0,
+        vault_bump: 0,
+        version: Recipe::VERSION,
+        name: String::new(),
+        description: String::new(),
+        category: RecipeCategory::Uncategorized,
+        sort_order: 0,
+        open_crafts: 0,
+    }
+}
+
+impl From<RecipeCategory> for ManifestCategory {
+    fn from(category: RecipeCategory) -> Self {
+        match category {
+            RecipeCategory::Uncategorized => ManifestCategory::Uncategorized,
+            RecipeCategory::Weapon => ManifestCategory::Weapon,
+            RecipeCategory::Armor => ManifestCategory::Armor,
+            RecipeCategory::Character => ManifestCategory::Character,
+            RecipeCategory::Vehicle => ManifestCategory::Vehicle,
+            RecipeCategory::Cosmetic => ManifestCategory::Cosmetic,
+            RecipeCategory::Consumable => ManifestCategory::Consumable,
+            RecipeCategory::Bundle => ManifestCategory::Bundle,
+        }
+    }
+}
+
+impl From<ManifestCategory> for RecipeCategory {
+    fn from(category: ManifestCategory) -> Self {
+        match category {
+            ManifestCategory::Uncategorized => RecipeCategory::Uncategorized,
+            ManifestCategory::Weapon => RecipeCategory::Weapon,
+            ManifestCategory::Armor => RecipeCategory::Armor,
+            ManifestCategory::Character => RecipeCategory::Character,
+            ManifestCategory::Vehicle => RecipeCategory::Vehicle,
+            ManifestCategory::Cosmetic => RecipeCategory::Cosmetic,
+            ManifestCategory::Consumable => RecipeCategory::Consumable,
+            ManifestCategory::Bundle => RecipeCategory::Bundle,
+        }
+    }
+}
+
+impl From<OutputKind> for ManifestOutputKind {
+    fn from(kind: OutputKind) -> Self {
+        match kind {
+            OutputKind::Skin => ManifestOutputKind::Skin,
+            OutputKind::Fungible => ManifestOutputKind::Fungible,
+        }
+    }
+}
+
+impl From<ManifestOutputKind> for OutputKind {
+    fn from(kind: ManifestOutputKind) -> Self {
+        match kind {
+            ManifestOutputKind::Skin => OutputKind::Skin,
+            ManifestOutputKind::Fungible => OutputKind::Fungible,
+        }
+    }
+}
+
+// Pubkeys are written base58 as in explorers + spreadsheets
+mod pubkey_string {
+    use anchor_lang::prelude::Pubkey;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(key: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(key)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value
+            .parse()
+            .map_err(|_| D::Error::custom(format!("invalid pubkey {}", value)))
+    }
+}
+
+mod option_pubkey_string {
+    use anchor_lang::prelude::Pubkey;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        key: &Option<Pubkey>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match key {
+            Some(key) => super::pubkey_string::serialize(key, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pubkey>, D::Error> {
+        #[derive(Deserialize)]
+        struct Key(#[serde(with = "super::pubkey_string")] Pubkey);
+        Ok(Option::<Key>::deserialize(deserializer)?.map(|key| key.0))
+    }
+}
+
+mod hash_string {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        hash: &Option<[u8; 32]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match hash {
+            Some(hash) => {
+                let hex: String = hash.iter().map(|byte| format!("{:02x}", byte)).collect();
+                serializer.serialize_str(&hex)
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[u8; 32]>, D::Error> {
+        let value = match Option::<String>::deserialize(deserializer)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let hex = value.trim_start_matches("0x");
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(D::Error::custom("allowlist_root must be 64 hex characters"));
+        }
+        let mut hash = [0u8; 32];
+        for (i, byte) in hash.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+                .map_err(|_| D::Error::custom("allowlist_root must be 64 hex characters"))?;
+        }
+        Ok(Some(hash))
+    }
+}
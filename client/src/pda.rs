@@ -0,0 +1,104 @@
+/*
+    program derived addresses of craft_skins
+      ["manager"]                => Manager (admin + bumps)
+      ["signer"]                 => program_signer, owns craft job skins + legacy escrows
+      ["vault", recipe]          => recipe vault, stocks outputs + ingredients of a Recipe / LargeRecipe
+      ["recipe", recipe mint]    => Recipe
+      ["large_recipe", recipe mint] => LargeRecipe (zero-copy)
+      ["profile", user]          => PlayerProfile
+      ["pending", recipe, user]  => PendingCraft (commit-reveal)
+      ["job", recipe, user]      => CraftJob (timed recipes)
+      ["salvage", recipe, skin mint] => SalvageRecord (salvage owed for a crafted skin)
+    and token metadata / associated token addresses used next to them
+*/
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::get_associated_token_address;
+use mpl_token_metadata::state::{EDITION, PREFIX};
+
+pub fn manager_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"manager"], &craft_skins::id()).0
+}
+
+pub fn signer_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"signer"], &craft_skins::id()).0
+}
+
+pub fn recipe_pda(recipe_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"recipe", recipe_mint.as_ref()], &craft_skins::id()).0
+}
+
+pub fn large_recipe_pda(recipe_mint: &Pubkey) -> Pubkey {
+    let seeds = &[b"large_recipe".as_ref(), recipe_mint.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+pub fn profile_pda(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"profile", user.as_ref()], &craft_skins::id()).0
+}
+
+pub fn pending_craft_pda(recipe: &Pubkey, user: &Pubkey) -> Pubkey {
+    let seeds = &[b"pending".as_ref(), recipe.as_ref(), user.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+pub fn craft_job_pda(recipe: &Pubkey, user: &Pubkey) -> Pubkey {
+    let seeds = &[b"job".as_ref(), recipe.as_ref(), user.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+pub fn salvage_record_pda(recipe: &Pubkey, skin_mint: &Pubkey) -> Pubkey {
+    let seeds = &[b"salvage".as_ref(), recipe.as_ref(), skin_mint.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+// program_signer ATA of mint, craft job skins + ingredients escrowed before recipe vaults
+pub fn escrow_token(mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&signer_pda(), mint)
+}
+
+// recipe is the Recipe or LargeRecipe PDA
+pub fn recipe_vault_pda(recipe: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", recipe.as_ref()], &craft_skins::id()).0
+}
+
+// recipe vault ATA of mint, holds deposited skin outputs + ingredients of delivered crafts
+pub fn vault_token(recipe: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(&recipe_vault_pda(recipe), mint)
+}
+
+pub fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    let program_id = mpl_token_metadata::id();
+    let seeds = &[PREFIX.as_bytes(), program_id.as_ref(), mint.as_ref()];
+    Pubkey::find_program_address(seeds, &program_id).0
+}
+
+pub fn edition_pda(mint: &Pubkey) -> Pubkey {
+    let program_id = mpl_token_metadata::id();
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        mint.as_ref(),
+        EDITION.as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, &program_id).0
+}
+
+// accounts of an NFT held by holder in its ATA
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NftAccounts {
+    pub mint: Pubkey,
+    pub token: Pubkey,
+    pub metadata: Pubkey,
+    pub edition: Pubkey,
+}
+
+impl NftAccounts {
+    pub fn new(mint: &Pubkey, holder: &Pubkey) -> Self {
+        NftAccounts {
+            mint: *mint,
+            token: get_associated_token_address(holder, mint),
+            metadata: metadata_pda(mint),
+            edition: edition_pda(mint),
+        }
+    }
+}
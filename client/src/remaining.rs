@@ -0,0 +1,249 @@
+/*
+    remaining_accounts of craft_skin, in order
+      gate accounts                  => gate_accounts (gated recipes)
+      timed recipe                   => craft_job_accounts
+      ingredient triplets            => ingredient_accounts
+      input skin accounts            => input_skin_accounts (upgrade recipes)
+      untimed recipe                 => output_accounts, then salvage_record_accounts
+
+    ingredients + escrowed input skins go to ATAs of escrow_owner,
+    the craft job of a timed recipe or the recipe vault
+
+    craft_large_skin reads large_ingredient_accounts, then large_output_accounts
+
+    craft_skin_batch reads the same gate + ingredient accounts, then
+      input skin accounts of every craft
+      batch_skin_accounts of every skin
+      output_accounts of every craft
+      salvage_record_accounts of every skin
+*/
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_spl::associated_token::get_associated_token_address;
+use craft_skins::utils::{
+    LargeRecipe, OutputKind, Recipe, RecipeGate, RecipeMode, RecipeOutput, SkinConsume,
+};
+
+use crate::pda::*;
+
+/*
+    gate held by user
+      TokenBalance => [user gate token]
+      Collection   => [user gate token, gate mint, gate metadata], gate_nft is the NFT held by user
+    None for a Collection gate without gate_nft
+*/
+pub fn gate_accounts(
+    gate: &RecipeGate,
+    user: &Pubkey,
+    gate_nft: Option<&Pubkey>,
+) -> Option<Vec<AccountMeta>> {
+    match gate {
+        RecipeGate::TokenBalance { mint, .. } => Some(vec![AccountMeta::new_readonly(
+            get_associated_token_address(user, mint),
+            false,
+        )]),
+        RecipeGate::Collection { .. } => {
+            let mint = gate_nft?;
+            Some(vec![
+                AccountMeta::new_readonly(get_associated_token_address(user, mint), false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(metadata_pda(mint), false),
+            ])
+        }
+    }
+}
+
+/*
+    owner of the escrow ATAs of one craft
+      timed recipe              => CraftJob of user
+      commit-reveal Random mode => PendingCraft of user
+      otherwise                 => recipe vault
+*/
+pub fn escrow_owner(recipe: &Recipe, recipe_mint: &Pubkey, user: &Pubkey) -> Pubkey {
+    let recipe_key = recipe_pda(recipe_mint);
+    if recipe.craft_duration > 0 {
+        craft_job_pda(&recipe_key, user)
+    } else if recipe.mode == RecipeMode::Random && recipe.reveal_delay > 0 {
+        pending_craft_pda(&recipe_key, user)
+    } else {
+        recipe_vault_pda(&recipe_key)
+    }
+}
+
+// [user token, mint, escrow token] of every Recipe ingredient, user tokens are ATAs
+pub fn ingredient_accounts(
+    recipe: &Recipe,
+    recipe_mint: &Pubkey,
+    user: &Pubkey,
+) -> Vec<AccountMeta> {
+    mint_triplets(
+        &recipe.mints,
+        &escrow_owner(recipe, recipe_mint, user),
+        user,
+    )
+}
+
+// ingredient_accounts of a LargeRecipe
+pub fn large_ingredient_accounts(
+    recipe: &LargeRecipe,
+    recipe_mint: &Pubkey,
+    user: &Pubkey,
+) -> Vec<AccountMeta> {
+    let recipe_vault = recipe_vault_pda(&large_recipe_pda(recipe_mint));
+    mint_triplets(recipe.mints(), &recipe_vault, user)
+}
+
+// escrow is the owner of the ATAs receiving the ingredients
+fn mint_triplets(mints: &[Pubkey], escrow: &Pubkey, user: &Pubkey) -> Vec<AccountMeta> {
+    mints
+        .iter()
+        .flat_map(|mint| {
+            [
+                AccountMeta::new(get_associated_token_address(user, mint), false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(get_associated_token_address(escrow, mint), false),
+            ]
+        })
+        .collect()
+}
+
+/*
+    input skins of one craft, skin_mints[i] is held by user for recipe.input_skins[i]
+      Burn   => [user skin token, skin mint, skin metadata]
+      Escrow => [user skin token, skin mint, skin metadata, escrow skin token]
+*/
+pub fn input_skin_accounts(
+    recipe: &Recipe,
+    recipe_mint: &Pubkey,
+    user: &Pubkey,
+    skin_mints: &[Pubkey],
+) -> Vec<AccountMeta> {
+    let escrow = escrow_owner(recipe, recipe_mint, user);
+    let mut accounts = vec![];
+    for (input, mint) in recipe.input_skins.iter().zip(skin_mints.iter()) {
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(user, mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(*mint, false));
+        accounts.push(AccountMeta::new_readonly(metadata_pda(mint), false));
+        if input.consume == SkinConsume::Escrow {
+            accounts.push(AccountMeta::new(
+                get_associated_token_address(&escrow, mint),
+                false,
+            ));
+        }
+    }
+    accounts
+}
+
+// [craft job PDA, program skin token] of a timed recipe craft
+pub fn craft_job_accounts(
+    recipe_mint: &Pubkey,
+    user: &Pubkey,
+    skin_mint: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(craft_job_pda(&recipe_pda(recipe_mint), user), false),
+        AccountMeta::new(escrow_token(skin_mint), false),
+    ]
+}
+
+/*
+    Recipe outputs of one craft, skin_mints are recipe vault skins for Skin outputs in order
+      Skin     => [skin mint, skin metadata, vault skin token, user skin token]
+      Fungible => [output mint, user token]
+    None when there are fewer skin_mints than Skin outputs
+*/
+pub fn output_accounts(
+    recipe: &Recipe,
+    recipe_mint: &Pubkey,
+    user: &Pubkey,
+    skin_mints: &[Pubkey],
+) -> Option<Vec<AccountMeta>> {
+    let recipe_key = recipe_pda(recipe_mint);
+    outputs_accounts(
+        recipe.outputs.iter().cloned(),
+        &recipe_key,
+        user,
+        skin_mints,
+    )
+}
+
+// output_accounts of a LargeRecipe
+pub fn large_output_accounts(
+    recipe: &LargeRecipe,
+    recipe_mint: &Pubkey,
+    user: &Pubkey,
+    skin_mints: &[Pubkey],
+) -> Option<Vec<AccountMeta>> {
+    let recipe_key = large_recipe_pda(recipe_mint);
+    outputs_accounts(recipe.outputs(), &recipe_key, user, skin_mints)
+}
+
+// recipe is the Recipe / LargeRecipe owning the vault
+fn outputs_accounts(
+    outputs: impl Iterator<Item = RecipeOutput>,
+    recipe: &Pubkey,
+    user: &Pubkey,
+    skin_mints: &[Pubkey],
+) -> Option<Vec<AccountMeta>> {
+    let mut skin_mints = skin_mints.iter();
+    let mut accounts = vec![];
+    for output in outputs {
+        match output.kind {
+            OutputKind::Skin => {
+                let mint = skin_mints.next()?;
+                accounts.push(AccountMeta::new(*mint, false));
+                accounts.push(AccountMeta::new_readonly(metadata_pda(mint), false));
+                accounts.push(AccountMeta::new(vault_token(recipe, mint), false));
+                accounts.push(AccountMeta::new(
+                    get_associated_token_address(user, mint),
+                    false,
+                ));
+            }
+            OutputKind::Fungible => {
+                accounts.push(AccountMeta::new(output.mint, false));
+                accounts.push(AccountMeta::new(
+                    get_associated_token_address(user, &output.mint),
+                    false,
+                ));
+            }
+        }
+    }
+    Some(accounts)
+}
+
+// [owner skin token, skin mint, skin metadata, user skin token] of each skin of a batch
+pub fn batch_skin_accounts(
+    owner: &Pubkey,
+    user: &Pubkey,
+    skin_mints: &[Pubkey],
+) -> Vec<AccountMeta> {
+    skin_mints
+        .iter()
+        .flat_map(|mint| {
+            [
+                AccountMeta::new(get_associated_token_address(owner, mint), false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(metadata_pda(mint), false),
+                AccountMeta::new(get_associated_token_address(user, mint), false),
+            ]
+        })
+        .collect()
+}
+
+// [salvage record] of each crafted skin, none unless the recipe has salvage_bps
+pub fn salvage_record_accounts(
+    recipe: &Recipe,
+    recipe_mint: &Pubkey,
+    skin_mints: &[Pubkey],
+) -> Vec<AccountMeta> {
+    if recipe.salvage_bps == 0 {
+        return vec![];
+    }
+    let recipe_key = recipe_pda(recipe_mint);
+    skin_mints
+        .iter()
+        .map(|mint| AccountMeta::new(salvage_record_pda(&recipe_key, mint), false))
+        .collect()
+}
@@ -0,0 +1,88 @@
+/*
+    blocking JSON-RPC reads shared by the CLI and the indexer, commitment is always confirmed
+    account data is requested + decoded as base64
+    call is public, each binary adds the methods only it needs on top
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+
+// getMultipleAccounts takes at most 100 keys
+pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+pub struct Rpc {
+    url: String,
+}
+
+impl Rpc {
+    pub fn new(url: &str) -> Self {
+        Rpc {
+            url: url.to_string(),
+        }
+    }
+
+    pub fn call(&self, method: &str, params: Value) -> Result<Value> {
+        let request = json!({"jsonrpc": "2.0", "id": 1, "method": method, "params": params});
+        let mut response: Value = ureq::post(&self.url).send_json(request)?.into_json()?;
+        if let Some(error) = response.get("error") {
+            return Err(anyhow!("{} failed: {}", method, error));
+        }
+        Ok(response["result"].take())
+    }
+
+    pub fn account_data(&self, key: &Pubkey) -> Result<Option<Vec<u8>>> {
+        let config = json!({"encoding": "base64", "commitment": "confirmed"});
+        let result = self.call("getAccountInfo", json!([key.to_string(), config]))?;
+        decode_account(&result["value"])
+    }
+
+    pub fn multiple_account_data(&self, keys: &[Pubkey]) -> Result<Vec<Option<Vec<u8>>>> {
+        let keys: Vec<String> = keys.iter().map(Pubkey::to_string).collect();
+        let config = json!({"encoding": "base64", "commitment": "confirmed"});
+        let result = self.call("getMultipleAccounts", json!([keys, config]))?;
+        result["value"]
+            .as_array()
+            .ok_or_else(|| anyhow!("getMultipleAccounts returned no accounts"))?
+            .iter()
+            .map(decode_account)
+            .collect()
+    }
+
+    // filters as in the getProgramAccounts RPC method, e.g. [{"dataSize": 165}], [] for all
+    pub fn program_accounts(
+        &self,
+        program_id: &Pubkey,
+        filters: Value,
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>> {
+        let config = json!({"encoding": "base64", "commitment": "confirmed", "filters": filters});
+        let result = self.call(
+            "getProgramAccounts",
+            json!([program_id.to_string(), config]),
+        )?;
+        result
+            .as_array()
+            .ok_or_else(|| anyhow!("getProgramAccounts returned no accounts"))?
+            .iter()
+            .map(|keyed| {
+                let key = keyed["pubkey"]
+                    .as_str()
+                    .ok_or_else(|| anyhow!("getProgramAccounts returned no pubkey"))?
+                    .parse()?;
+                let data = decode_account(&keyed["account"])?
+                    .ok_or_else(|| anyhow!("getProgramAccounts returned no account"))?;
+                Ok((key, data))
+            })
+            .collect()
+    }
+}
+
+// account => data of ["<base64>", "base64"], null account => None
+pub fn decode_account(account: &Value) -> Result<Option<Vec<u8>>> {
+    if account.is_null() {
+        return Ok(None);
+    }
+    let data = account["data"][0]
+        .as_str()
+        .ok_or_else(|| anyhow!("account data is not base64"))?;
+    Ok(Some(base64::decode(data)?))
+}
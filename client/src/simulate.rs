@@ -0,0 +1,135 @@
+/*
+    simulate_craft => can user craft_skin recipe with their wallet, and why not
+    runs the program's own craft rules (craft_skins::rules) in craft_skin order
+      recipe version   => AccountNotMigrated (Recipe needs migrate_recipe)
+      recipe mode      => RecipeModeInvalid
+      allowlist        => NotAllowlisted / AllowanceExceeded
+      gate             => NotEnoughToken / CollectionUnverified
+      ingredients      => NotEnoughToken (user holds less than the recipe amount)
+      input skins      => NotEnoughToken / CollectionUnverified
+    ingredients + input skins are taken from a running balance, a mint listed twice
+    needs both amounts as on chain, where the first transfer lands before the second check
+    checks of the owner's side (Recipe NFT, skin, fee recipients) and lamports for
+    the craft fee + rent are not simulated
+
+    a mint missing from holdings is simulated as an empty token account, the chain
+    fails earlier on the missing account with a token metadata error instead
+*/
+use anchor_lang::prelude::Pubkey;
+use craft_skins::rules::*;
+
+pub use craft_skins::rules::NftCollection;
+use craft_skins::utils::{ErrorCode, Recipe, RecipeGate, RecipeMode};
+
+// token held by the user in its ATA, collection is read from the mint's metadata (NFTs)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HeldToken {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub collection: Option<NftCollection>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Holdings {
+    pub user: Pubkey,
+    pub tokens: Vec<HeldToken>,
+    // crafts of the recipe recorded on the user's PlayerProfile
+    pub crafted: u32,
+}
+
+impl Holdings {
+    fn token(&self, mint: &Pubkey) -> TokenState {
+        let amount = self
+            .tokens
+            .iter()
+            .find(|token| token.mint == *mint)
+            .map_or(0, |token| token.amount);
+        TokenState {
+            mint: *mint,
+            owner: self.user,
+            amount,
+        }
+    }
+
+    // amount of mint leaves the wallet, checked by the caller first
+    fn spend(&mut self, mint: &Pubkey, amount: u64) {
+        if let Some(token) = self.tokens.iter_mut().find(|token| token.mint == *mint) {
+            token.amount -= amount;
+        }
+    }
+}
+
+// NFTs picked from holdings, passed to remaining::gate_accounts / input_skin_accounts
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CraftPlan {
+    pub gate_nft: Option<Pubkey>,
+    pub input_skins: Vec<Pubkey>,
+}
+
+// allowance + proof are ignored unless the recipe has an allowlist
+pub fn simulate_craft(
+    recipe: &Recipe,
+    holdings: &Holdings,
+    allowance: u32,
+    proof: &[[u8; 32]],
+) -> Result<CraftPlan, ErrorCode> {
+    check_account_version(recipe.version, Recipe::VERSION)?;
+    check_recipe_mode(recipe, RecipeMode::Fixed)?;
+    if let Some(root) = &recipe.allowlist_root {
+        check_allowlist(root, &holdings.user, allowance, proof, holdings.crafted, 1)?;
+    }
+
+    let mut plan = CraftPlan::default();
+    match recipe.gate {
+        Some(RecipeGate::TokenBalance { mint, min_amount }) => {
+            check_token_balance(&holdings.token(&mint), &holdings.user, &mint, min_amount)?;
+        }
+        Some(RecipeGate::Collection { collection }) => {
+            let held = pick_nft(holdings, &collection)?;
+            check_token_balance(&holdings.token(&held), &holdings.user, &held, 1)?;
+            plan.gate_nft = Some(held);
+        }
+        None => {}
+    }
+
+    // gate tokens stay with the user, ingredients + input skins are escrowed one by one
+    let mut wallet = holdings.clone();
+    for (mint, amount) in recipe.mints.iter().zip(recipe.amounts.iter()) {
+        check_ingredient(&wallet.token(mint), mint, *amount)?;
+        wallet.spend(mint, *amount);
+    }
+
+    for input in recipe.input_skins.iter() {
+        // a skin consumed by one input is spent, it cannot fill another
+        // no NFT of the collection => empty token account, NotEnoughToken as on chain
+        let held = pick_nft(&wallet, &input.collection)?;
+        check_ingredient(&wallet.token(&held), &held, 1)?;
+        wallet.spend(&held, 1);
+        plan.input_skins.push(held);
+    }
+    Ok(plan)
+}
+
+/*
+    NFT of holdings verified in collection
+    an unverified member of collection => CollectionUnverified, none => NotEnoughToken
+*/
+fn pick_nft(holdings: &Holdings, collection: &Pubkey) -> Result<Pubkey, ErrorCode> {
+    let mut unverified = false;
+    for token in holdings.tokens.iter() {
+        if token.amount == 0 {
+            continue;
+        }
+        match check_collection(token.collection.as_ref(), collection) {
+            Ok(()) => return Ok(token.mint),
+            Err(ErrorCode::CollectionUnverified) => {
+                unverified |= matches!(token.collection, Some(c) if c.key == *collection)
+            }
+            Err(_) => {}
+        }
+    }
+    match unverified {
+        true => Err(ErrorCode::CollectionUnverified),
+        false => Err(ErrorCode::NotEnoughToken),
+    }
+}
@@ -0,0 +1,145 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountSerialize, Discriminator, Event};
+
+use craft_skins_client::decode::*;
+use craft_skins_client::*;
+
+fn account_data<T: AccountSerialize>(account: &T) -> Vec<u8> {
+    let mut data = vec![];
+    account.try_serialize(&mut data).unwrap();
+    data
+}
+
+fn event_log(user: Pubkey, roll: Option<LootRoll>) -> String {
+    let event = CraftEvent {
+        user,
+        recipe: Pubkey::new_unique(),
+        skin_mint: Pubkey::new_unique(),
+        roll,
+    };
+    format!("Program data: {}", base64::encode(event.data()))
+}
+
+#[test]
+fn accounts_are_decoded_by_discriminator() {
+    let admin = Pubkey::new_unique();
+    let manager = Manager {
+        admin,
+        bump: 254,
+        signer_bump: 253,
+        version: Manager::VERSION,
+    };
+    match decode_account(&account_data(&manager)).unwrap() {
+        Some(CraftSkinsAccount::Manager(decoded)) => {
+            assert_eq!(decoded.admin, admin);
+            assert_eq!(decoded.signer_bump, 253);
+        }
+        _ => panic!("expected a Manager"),
+    }
+
+    let user = Pubkey::new_unique();
+    let profile = PlayerProfile {
+        user,
+        total_crafts: 3,
+        first_craft_at: 10,
+        last_craft_at: 20,
+        recipes: vec![],
+        bump: 255,
+    };
+    let account = decode_account(&account_data(&profile)).unwrap().unwrap();
+    assert_eq!(account.kind(), "PlayerProfile");
+    match account {
+        CraftSkinsAccount::PlayerProfile(decoded) => assert_eq!(decoded.user, user),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn unknown_and_corrupt_accounts() {
+    assert!(decode_account(&[1, 2, 3]).unwrap().is_none());
+    assert!(decode_account(&[0; 64]).unwrap().is_none());
+
+    // Manager discriminator with the data cut short
+    let manager = Manager {
+        admin: Pubkey::new_unique(),
+        bump: 1,
+        signer_bump: 2,
+        version: Manager::VERSION,
+    };
+    let data = account_data(&manager);
+    assert!(decode_account(&data[..20]).is_err());
+}
+
+#[test]
+fn craft_events_only_come_from_craft_skins() {
+    let program = id().to_string();
+    let other = Pubkey::new_unique().to_string();
+    let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let logs = vec![
+        format!("Program {} invoke [1]", program),
+        "Program log: Instruction: CraftSkin".to_string(),
+        format!("Program {} invoke [2]", other),
+        // event shaped data logged by a CPI'd program is not ours
+        event_log(Pubkey::new_unique(), None),
+        format!("Program {} success", other),
+        event_log(first, None),
+        format!("Program {} consumed 52000 of 200000 compute units", program),
+        format!("Program {} success", program),
+        format!("Program {} invoke [1]", other),
+        event_log(Pubkey::new_unique(), None),
+        format!("Program {} success", other),
+        format!("Program {} invoke [1]", program),
+        event_log(second, Some(LootRoll { roll: 42, entry: 2 })),
+        format!("Program {} success", program),
+    ];
+
+    let events = parse_craft_events(&logs);
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].user, first);
+    assert!(events[0].roll.is_none());
+    assert_eq!(events[1].user, second);
+    assert!(matches!(
+        events[1].roll,
+        Some(LootRoll { roll: 42, entry: 2 })
+    ));
+}
+
+#[test]
+fn program_logs_do_not_change_the_invoke_stack() {
+    let program = id().to_string();
+    let user = Pubkey::new_unique();
+    let logs = vec![
+        format!("Program {} invoke [1]", program),
+        "Program log: success".to_string(),
+        "Program data: not-base64".to_string(),
+        event_log(user, None),
+        format!("Program {} failed: custom program error: 0x1770", program),
+    ];
+    let events = parse_craft_events(&logs);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].user, user);
+}
+
+#[test]
+fn large_recipes_decode_without_borsh() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut large_recipe: LargeRecipe = bytemuck::Zeroable::zeroed();
+    large_recipe.append(&mints, &[3, 4], &[]).unwrap();
+    large_recipe.version = LargeRecipe::VERSION;
+
+    // zero-copy layout => discriminator || struct bytes
+    let mut data = LargeRecipe::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(&large_recipe));
+    assert_eq!(data.len(), LargeRecipe::LEN);
+
+    let account = decode_account(&data).unwrap().unwrap();
+    assert_eq!(account.kind(), "LargeRecipe");
+    match account {
+        CraftSkinsAccount::LargeRecipe(decoded) => {
+            assert_eq!(decoded.mints(), &mints);
+            assert_eq!(decoded.amounts(), &[3, 4]);
+        }
+        _ => unreachable!(),
+    }
+    assert!(decode_account(&data[..100]).is_err());
+}
@@ -0,0 +1,200 @@
+use anchor_lang::prelude::Pubkey;
+use craft_skins::utils::ErrorCode;
+use craft_skins_client::instruction::{create_recipe, migrate_recipe, update_recipe};
+use craft_skins_client::manifest::*;
+use craft_skins_client::*;
+
+const RECIPE_TOML: &str = include_str!("fixtures/recipe.toml");
+
+// Recipe account holding the settings of args, as create_recipe writes it
+fn recipe(args: RecipeSettings) -> Recipe {
+    Recipe {
+        mints: args.ingredient_mints,
+        amounts: args.ingredient_amounts,
+        craft_fee: args.craft_fee,
+        royalty_bps: args.royalty_bps,
+        outputs: args.outputs,
+        mode: args.mode,
+        loot_table: args.loot_table,
+        randomness_program: args.randomness_program,
+        reveal_delay: args.reveal_delay,
+        reveal_timeout: args.reveal_timeout,
+        craft_duration: args.craft_duration,
+        speed_up_mint: args.speed_up_mint,
+        speed_up_amount: args.speed_up_amount,
+        cancel_penalty_bps: args.cancel_penalty_bps,
+        salvage_bps: args.salvage_bps,
+        input_skins: args.input_skins,
+        revision: 1,
+        gate: args.gate,
+        allowlist_root: args.allowlist_root,
+        bump: 255,
+        vault_bump: 254,
+        version: Recipe::VERSION,
+        name: args.name,
+        description: args.description,
+        category: args.category,
+        sort_order: args.sort_order,
+        open_crafts: 0,
+    }
+}
+
+fn assert_rule(result: Result<RecipeSettings, ManifestError>, error: ErrorCode) {
+    match result {
+        Err(ManifestError::Rule { code, .. }) => assert_eq!(code, u32::from(error)),
+        Err(err) => panic!("expected {:?}, got {}", u32::from(error), err),
+        Ok(_) => panic!("manifest should be invalid"),
+    }
+}
+
+#[test]
+fn toml_manifest_becomes_recipe_args() {
+    let manifest = RecipeManifest::from_toml(RECIPE_TOML).unwrap();
+    let args = manifest.validate().unwrap();
+
+    assert_eq!(args.ingredient_amounts, vec![3, 1]);
+    assert_eq!(args.craft_fee, 10_000_000);
+    assert_eq!(args.royalty_bps, 500);
+    assert!(args.mode == RecipeMode::Fixed);
+    assert_eq!(args.craft_duration, 3600);
+    assert_eq!(args.speed_up_amount, 5);
+    assert_eq!(args.outputs.len(), 1);
+    assert!(args.outputs[0].kind == OutputKind::Fungible);
+    assert!(matches!(
+        args.gate,
+        Some(RecipeGate::TokenBalance {
+            min_amount: 1_000_000_000,
+            ..
+        })
+    ));
+    assert!(args.allowlist_root.is_none());
+    assert_eq!(args.name, "Scrap Blade");
+    assert!(args.category == RecipeCategory::Weapon);
+    assert_eq!(args.sort_order, 3);
+}
+
+#[test]
+fn manifest_round_trips_through_toml_and_json() {
+    let mut manifest = RecipeManifest::from_toml(RECIPE_TOML).unwrap();
+    manifest.allowlist_root = Some([0xab; 32]);
+
+    assert_eq!(
+        RecipeManifest::from_toml(&manifest.to_toml()).unwrap(),
+        manifest
+    );
+    assert_eq!(
+        RecipeManifest::from_json(&manifest.to_json()).unwrap(),
+        manifest
+    );
+}
+
+#[test]
+fn manifest_schema_errors() {
+    // typo of craft_fee
+    let text = RECIPE_TOML.replace("craft_fee", "craft_fees");
+    assert!(matches!(
+        RecipeManifest::from_toml(&text),
+        Err(ManifestError::Parse(_))
+    ));
+    let text = RECIPE_TOML.replace("kind = \"fungible\"", "kind = \"coin\"");
+    assert!(matches!(
+        RecipeManifest::from_toml(&text),
+        Err(ManifestError::Parse(_))
+    ));
+    let text = RECIPE_TOML.replace("\"weapon\"", "\"sword\"");
+    assert!(matches!(
+        RecipeManifest::from_toml(&text),
+        Err(ManifestError::Parse(_))
+    ));
+    let text = RECIPE_TOML.replace("amount = 3", "amount = -3");
+    assert!(matches!(
+        RecipeManifest::from_toml(&text),
+        Err(ManifestError::Parse(_))
+    ));
+}
+
+#[test]
+fn manifest_follows_create_recipe_rules() {
+    let manifest = RecipeManifest::from_toml(RECIPE_TOML).unwrap();
+
+    let mut invalid = manifest.clone();
+    invalid.royalty_bps = 10_001;
+    assert_rule(invalid.validate(), ErrorCode::RoyaltyInvalid);
+
+    let mut invalid = manifest.clone();
+    invalid.salvage_bps = 10_001;
+    assert_rule(invalid.validate(), ErrorCode::PenaltyInvalid);
+
+    // skins are delivered one at a time
+    let mut invalid = manifest.clone();
+    invalid.outputs[0].kind = ManifestOutputKind::Skin;
+    assert_rule(invalid.validate(), ErrorCode::OutputAmountInvalid);
+
+    // random recipes need a loot table and cannot be timed
+    let mut invalid = manifest.clone();
+    invalid.mode = ManifestMode::Random;
+    assert_rule(invalid.validate(), ErrorCode::LootTableInvalid);
+
+    // listing fields fit the space reserved in the Recipe account
+    let mut invalid = manifest.clone();
+    invalid.name = "x".repeat(Recipe::MAX_NAME_LEN + 1);
+    assert_rule(invalid.validate(), ErrorCode::RecipeNameTooLong);
+
+    let mut invalid = manifest.clone();
+    invalid.description = "x".repeat(Recipe::MAX_DESCRIPTION_LEN + 1);
+    assert_rule(invalid.validate(), ErrorCode::RecipeDescriptionTooLong);
+
+    let mut invalid = manifest;
+    invalid.ingredients = (0..Recipe::MAX_INGREDIENTS + 1)
+        .map(|_| ManifestIngredient {
+            mint: Pubkey::new_unique(),
+            amount: 1,
+        })
+        .collect();
+    assert_rule(invalid.validate(), ErrorCode::TooManyIngredients);
+}
+
+#[test]
+fn diff_against_on_chain_recipe() {
+    let owner = Pubkey::new_unique();
+    let manifest = RecipeManifest::from_toml(RECIPE_TOML).unwrap();
+
+    // no Recipe yet
+    let diff = manifest.diff(&owner, None).unwrap();
+    let expected = create_recipe(&owner, &manifest.recipe_mint, manifest.recipe_args());
+    assert_eq!(diff.instructions, vec![expected]);
+
+    // Recipe already holds the manifest settings
+    let on_chain = recipe(manifest.recipe_args());
+    assert_eq!(
+        RecipeManifest::from_recipe(&manifest.recipe_mint, &on_chain),
+        manifest
+    );
+    let diff = manifest.diff(&owner, Some(&on_chain)).unwrap();
+    assert!(diff.changes.is_empty());
+    assert!(diff.instructions.is_empty());
+
+    // designer raised the fee, dropped the gate + moved the recipe
+    let mut edited = manifest.clone();
+    edited.craft_fee = 20_000_000;
+    edited.gate = None;
+    edited.category = ManifestCategory::Bundle;
+    let diff = edited.diff(&owner, Some(&on_chain)).unwrap();
+    let fields: Vec<&str> = diff.changes.iter().map(|c| c.field.as_str()).collect();
+    assert_eq!(fields, vec!["category", "craft_fee", "gate"]);
+    assert_eq!(diff.changes[0].manifest, "\"bundle\"");
+    assert_eq!(diff.changes[1].on_chain, "10000000");
+    assert_eq!(diff.changes[1].manifest, "20000000");
+    assert_eq!(diff.changes[2].manifest, "null");
+    let expected = update_recipe(&owner, &edited.recipe_mint, edited.recipe_args());
+    assert_eq!(diff.instructions, vec![expected.clone()]);
+
+    // Recipe of an older layout is migrated before update_recipe, even without changes
+    let mut outdated = on_chain.clone();
+    outdated.version = 0;
+    let diff = manifest.diff(&owner, Some(&outdated)).unwrap();
+    let migrate = migrate_recipe(&owner, &manifest.recipe_mint);
+    assert_eq!(diff.instructions, vec![migrate.clone()]);
+    let diff = edited.diff(&owner, Some(&outdated)).unwrap();
+    assert_eq!(diff.instructions, vec![migrate, expected]);
+}
@@ -0,0 +1,259 @@
+use anchor_lang::prelude::{AccountMeta, Pubkey};
+use anchor_lang::{AccountSerialize, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use craft_skins_client::instruction::{craft_skin, CraftAccounts};
+use craft_skins_client::pda::*;
+use craft_skins_client::remaining::*;
+use craft_skins_client::*;
+
+fn recipe(mints: Vec<Pubkey>) -> Recipe {
+    Recipe {
+        amounts: vec![1; mints.len()],
+        mints,
+        craft_fee: 0,
+        royalty_bps: 0,
+        outputs: vec![],
+        mode: RecipeMode::Fixed,
+        loot_table: vec![],
+        randomness_program: None,
+        reveal_delay: 0,
+        reveal_timeout: 0,
+        craft_duration: 0,
+        speed_up_mint: None,
+        speed_up_amount: 0,
+        cancel_penalty_bps: 0,
+        salvage_bps: 0,
+        input_skins: vec![],
+        revision: 1,
+        gate: None,
+        allowlist_root: None,
+        bump: 255,
+        vault_bump: 254,
+        version: Recipe::VERSION,
+        name: String::new(),
+        description: String::new(),
+        category: RecipeCategory::Uncategorized,
+        sort_order: 0,
+        open_crafts: 0,
+    }
+}
+
+#[test]
+fn ingredient_triplets_follow_recipe_order() {
+    let user = Pubkey::new_unique();
+    let mints = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+
+    // Recipe as fetched from RPC
+    let mut data = vec![];
+    recipe(mints.clone()).try_serialize(&mut data).unwrap();
+    let fetched = decode_recipe(&data).unwrap();
+
+    let recipe_mint = Pubkey::new_unique();
+    let accounts = ingredient_accounts(&fetched, &recipe_mint, &user);
+    let expected: Vec<AccountMeta> = mints
+        .iter()
+        .flat_map(|mint| {
+            [
+                AccountMeta::new(get_associated_token_address(&user, mint), false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(vault_token(&recipe_pda(&recipe_mint), mint), false),
+            ]
+        })
+        .collect();
+    assert_eq!(accounts, expected);
+}
+
+#[test]
+fn open_crafts_escrow_into_their_craft_account() {
+    let user = Pubkey::new_unique();
+    let recipe_mint = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let recipe_key = recipe_pda(&recipe_mint);
+
+    let mut timed = recipe(vec![mint]);
+    timed.craft_duration = 60;
+    let job = craft_job_pda(&recipe_key, &user);
+    assert_eq!(escrow_owner(&timed, &recipe_mint, &user), job);
+    let accounts = ingredient_accounts(&timed, &recipe_mint, &user);
+    assert_eq!(
+        accounts[2].pubkey,
+        get_associated_token_address(&job, &mint)
+    );
+
+    let mut random = recipe(vec![mint]);
+    random.mode = RecipeMode::Random;
+    random.reveal_delay = 2;
+    assert_eq!(
+        escrow_owner(&random, &recipe_mint, &user),
+        pending_craft_pda(&recipe_key, &user)
+    );
+
+    // instant crafts pay the recipe vault
+    assert_eq!(
+        escrow_owner(&recipe(vec![mint]), &recipe_mint, &user),
+        recipe_vault_pda(&recipe_key)
+    );
+}
+
+#[test]
+fn salvage_records_only_for_salvage_recipes() {
+    let recipe_mint = Pubkey::new_unique();
+    let skins = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let mut recipe = recipe(vec![Pubkey::new_unique()]);
+    assert!(salvage_record_accounts(&recipe, &recipe_mint, &skins).is_empty());
+
+    recipe.salvage_bps = 5_000;
+    let accounts = salvage_record_accounts(&recipe, &recipe_mint, &skins);
+    let recipe_key = recipe_pda(&recipe_mint);
+    assert_eq!(
+        accounts,
+        vec![
+            AccountMeta::new(salvage_record_pda(&recipe_key, &skins[0]), false),
+            AccountMeta::new(salvage_record_pda(&recipe_key, &skins[1]), false),
+        ]
+    );
+}
+
+#[test]
+fn craft_skin_appends_remaining_accounts() {
+    let craft = CraftAccounts {
+        owner: Pubkey::new_unique(),
+        holder: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+        admin: Pubkey::new_unique(),
+        recipe_mint: Pubkey::new_unique(),
+    };
+    let skin_mint = Pubkey::new_unique();
+    let gate = RecipeGate::TokenBalance {
+        mint: Pubkey::new_unique(),
+        min_amount: 1,
+    };
+    let mut recipe = recipe(vec![Pubkey::new_unique()]);
+    recipe.gate = Some(gate);
+
+    let mut remaining = gate_accounts(&gate, &craft.user, None).unwrap();
+    remaining.extend(ingredient_accounts(
+        &recipe,
+        &craft.recipe_mint,
+        &craft.user,
+    ));
+    let instruction = craft_skin(&craft, &skin_mint, remaining.clone(), 0, vec![]);
+
+    assert_eq!(instruction.program_id, id());
+    let account_count = craft_skins::accounts::CraftSkin {
+        owner: craft.owner,
+        user: craft.user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: craft.admin,
+        recipe_holder: craft.owner,
+        player_profile: profile_pda(&craft.user),
+        recipe: recipe_pda(&craft.recipe_mint),
+        recipe_vault: Pubkey::default(),
+        recipe_token_account: Pubkey::default(),
+        recipe_mint: craft.recipe_mint,
+        recipe_metadata: metadata_pda(&craft.recipe_mint),
+        recipe_master_edition: edition_pda(&craft.recipe_mint),
+        user_skin_token_account: Pubkey::default(),
+        skin_token_account: Pubkey::default(),
+        skin_mint,
+        skin_metadata: metadata_pda(&skin_mint),
+        rent_account: Pubkey::default(),
+        token_metadata_program: Pubkey::default(),
+        token_program: Pubkey::default(),
+        ata_program: Pubkey::default(),
+        system_program: Pubkey::default(),
+    }
+    .to_account_metas(None)
+    .len();
+    assert_eq!(instruction.accounts.len(), account_count + remaining.len());
+    assert_eq!(instruction.accounts[account_count..], remaining[..]);
+    // owner + user sign, user pays
+    assert!(instruction.accounts[0].is_signer && instruction.accounts[0].pubkey == craft.owner);
+    assert!(instruction.accounts[1].is_signer && instruction.accounts[1].pubkey == craft.user);
+}
+
+#[test]
+fn collection_gate_needs_gate_nft() {
+    let user = Pubkey::new_unique();
+    let gate = RecipeGate::Collection {
+        collection: Pubkey::new_unique(),
+    };
+    assert!(gate_accounts(&gate, &user, None).is_none());
+
+    let gate_nft = Pubkey::new_unique();
+    let accounts = gate_accounts(&gate, &user, Some(&gate_nft)).unwrap();
+    let keys: Vec<Pubkey> = accounts.iter().map(|meta| meta.pubkey).collect();
+    assert_eq!(
+        keys,
+        vec![
+            get_associated_token_address(&user, &gate_nft),
+            gate_nft,
+            metadata_pda(&gate_nft),
+        ]
+    );
+}
+
+#[test]
+fn large_recipe_accounts_stop_at_counts() {
+    let user = Pubkey::new_unique();
+    let mints: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+    let fungible = Pubkey::new_unique();
+    let mut large_recipe: LargeRecipe = bytemuck::Zeroable::zeroed();
+    large_recipe
+        .append(
+            &mints,
+            &[1; 2],
+            &[RecipeOutput {
+                kind: OutputKind::Fungible,
+                mint: fungible,
+                amount: 5,
+            }],
+        )
+        .unwrap();
+
+    let recipe_mint = Pubkey::new_unique();
+    let accounts = large_ingredient_accounts(&large_recipe, &recipe_mint, &user);
+    assert_eq!(accounts.len(), 2 * 3);
+    assert_eq!(
+        accounts[3].pubkey,
+        get_associated_token_address(&user, &mints[1])
+    );
+    assert_eq!(accounts[4].pubkey, mints[1]);
+    assert_eq!(
+        accounts[5].pubkey,
+        vault_token(&large_recipe_pda(&recipe_mint), &mints[1])
+    );
+
+    let outputs = large_output_accounts(&large_recipe, &recipe_mint, &user, &[]).unwrap();
+    assert_eq!(
+        outputs.iter().map(|meta| meta.pubkey).collect::<Vec<_>>(),
+        vec![fungible, get_associated_token_address(&user, &fungible)]
+    );
+}
+
+#[test]
+fn skin_outputs_come_from_recipe_vault() {
+    let user = Pubkey::new_unique();
+    let recipe_mint = Pubkey::new_unique();
+    let skin = Pubkey::new_unique();
+    let mut recipe = recipe(vec![]);
+    recipe.outputs = vec![RecipeOutput {
+        kind: OutputKind::Skin,
+        mint: Pubkey::new_unique(),
+        amount: 1,
+    }];
+    assert!(output_accounts(&recipe, &recipe_mint, &user, &[]).is_none());
+
+    let accounts = output_accounts(&recipe, &recipe_mint, &user, &[skin]).unwrap();
+    let vault = recipe_vault_pda(&recipe_pda(&recipe_mint));
+    assert_eq!(
+        accounts[2].pubkey,
+        get_associated_token_address(&vault, &skin)
+    );
+    assert_eq!(
+        accounts[2].pubkey,
+        vault_token(&recipe_pda(&recipe_mint), &skin)
+    );
+    assert_ne!(vault, recipe_vault_pda(&large_recipe_pda(&recipe_mint)));
+}
@@ -0,0 +1,245 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::keccak;
+
+use craft_skins::utils::ErrorCode;
+use craft_skins_client::simulate::*;
+use craft_skins_client::*;
+
+fn recipe(mints: &[Pubkey], amounts: &[u64]) -> Recipe {
+    Recipe {
+        mints: mints.to_vec(),
+        amounts: amounts.to_vec(),
+        craft_fee: 0,
+        royalty_bps: 0,
+        outputs: vec![],
+        mode: RecipeMode::Fixed,
+        loot_table: vec![],
+        randomness_program: None,
+        reveal_delay: 0,
+        reveal_timeout: 0,
+        craft_duration: 0,
+        speed_up_mint: None,
+        speed_up_amount: 0,
+        cancel_penalty_bps: 0,
+        salvage_bps: 0,
+        input_skins: vec![],
+        revision: 1,
+        gate: None,
+        allowlist_root: None,
+        bump: 255,
+        vault_bump: 254,
+        version: Recipe::VERSION,
+        name: String::new(),
+        description: String::new(),
+        category: RecipeCategory::Uncategorized,
+        sort_order: 0,
+        open_crafts: 0,
+    }
+}
+
+fn token(mint: Pubkey, amount: u64) -> HeldToken {
+    HeldToken {
+        mint,
+        amount,
+        collection: None,
+    }
+}
+
+fn nft(collection: Pubkey, verified: bool) -> HeldToken {
+    HeldToken {
+        mint: Pubkey::new_unique(),
+        amount: 1,
+        collection: Some(NftCollection {
+            key: collection,
+            verified,
+        }),
+    }
+}
+
+fn assert_error(result: Result<CraftPlan, ErrorCode>, error: ErrorCode) {
+    match result {
+        Err(err) => assert_eq!(u32::from(err), u32::from(error)),
+        Ok(plan) => panic!("craft should fail, got {:?}", plan),
+    }
+}
+
+#[test]
+fn ingredients_need_at_least_recipe_amounts() {
+    let mints = [Pubkey::new_unique(), Pubkey::new_unique()];
+    let recipe = recipe(&mints, &[3, 1]);
+    let mut holdings = Holdings {
+        user: Pubkey::new_unique(),
+        tokens: vec![token(mints[0], 3), token(mints[1], 1)],
+        crafted: 0,
+    };
+    assert_eq!(
+        simulate_craft(&recipe, &holdings, 0, &[]).unwrap(),
+        CraftPlan::default()
+    );
+
+    // surplus stays with the user, only the recipe amount is escrowed
+    holdings.tokens[0].amount = 4;
+    assert_eq!(
+        simulate_craft(&recipe, &holdings, 0, &[]).unwrap(),
+        CraftPlan::default()
+    );
+    holdings.tokens[0].amount = 2;
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::NotEnoughToken,
+    );
+    holdings.tokens.remove(0);
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::NotEnoughToken,
+    );
+}
+
+#[test]
+fn ingredient_listed_twice_needs_both_amounts() {
+    let mint = Pubkey::new_unique();
+    let recipe = recipe(&[mint, mint], &[2, 1]);
+    let mut holdings = Holdings {
+        user: Pubkey::new_unique(),
+        tokens: vec![token(mint, 2)],
+        crafted: 0,
+    };
+    // the first triplet escrows 2, the second finds an empty account
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::NotEnoughToken,
+    );
+    holdings.tokens[0].amount = 3;
+    assert!(simulate_craft(&recipe, &holdings, 0, &[]).is_ok());
+}
+
+#[test]
+fn random_recipes_are_not_crafted_by_craft_skin() {
+    let mut recipe = recipe(&[], &[]);
+    recipe.mode = RecipeMode::Random;
+    assert_error(
+        simulate_craft(&recipe, &Holdings::default(), 0, &[]),
+        ErrorCode::RecipeModeInvalid,
+    );
+}
+
+#[test]
+fn recipes_created_before_versioning_need_migration() {
+    let mut recipe = recipe(&[], &[]);
+    recipe.version = 0;
+    assert_error(
+        simulate_craft(&recipe, &Holdings::default(), 0, &[]),
+        ErrorCode::AccountNotMigrated,
+    );
+}
+
+#[test]
+fn token_gate_needs_min_amount() {
+    let gate_mint = Pubkey::new_unique();
+    let mut recipe = recipe(&[], &[]);
+    recipe.gate = Some(RecipeGate::TokenBalance {
+        mint: gate_mint,
+        min_amount: 10,
+    });
+    let mut holdings = Holdings {
+        user: Pubkey::new_unique(),
+        tokens: vec![token(gate_mint, 9)],
+        crafted: 0,
+    };
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::NotEnoughToken,
+    );
+    // gate tokens are not consumed, holding more is fine
+    holdings.tokens[0].amount = 25;
+    assert!(simulate_craft(&recipe, &holdings, 0, &[]).is_ok());
+}
+
+#[test]
+fn collection_gate_picks_verified_nft() {
+    let collection = Pubkey::new_unique();
+    let mut recipe = recipe(&[], &[]);
+    recipe.gate = Some(RecipeGate::Collection { collection });
+    let mut holdings = Holdings {
+        user: Pubkey::new_unique(),
+        tokens: vec![nft(Pubkey::new_unique(), true), nft(collection, false)],
+        crafted: 0,
+    };
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::CollectionUnverified,
+    );
+    holdings.tokens.remove(1);
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::NotEnoughToken,
+    );
+
+    let member = nft(collection, true);
+    holdings.tokens.push(member);
+    let plan = simulate_craft(&recipe, &holdings, 0, &[]).unwrap();
+    assert_eq!(plan.gate_nft, Some(member.mint));
+}
+
+#[test]
+fn input_skins_use_distinct_nfts() {
+    let collection = Pubkey::new_unique();
+    let mut recipe = recipe(&[], &[]);
+    let input = SkinInput {
+        collection,
+        consume: SkinConsume::Burn,
+    };
+    recipe.input_skins = vec![input, input];
+    let first = nft(collection, true);
+    let mut holdings = Holdings {
+        user: Pubkey::new_unique(),
+        tokens: vec![first],
+        crafted: 0,
+    };
+    assert_error(
+        simulate_craft(&recipe, &holdings, 0, &[]),
+        ErrorCode::NotEnoughToken,
+    );
+
+    let second = nft(collection, true);
+    holdings.tokens.push(second);
+    let plan = simulate_craft(&recipe, &holdings, 0, &[]).unwrap();
+    assert_eq!(plan.input_skins, vec![first.mint, second.mint]);
+
+    // skins are checked as ingredients of amount 1, a mint held twice fills both inputs
+    let held_twice = HeldToken { amount: 2, ..first };
+    holdings.tokens = vec![held_twice];
+    let plan = simulate_craft(&recipe, &holdings, 0, &[]).unwrap();
+    assert_eq!(plan.input_skins, vec![first.mint, first.mint]);
+}
+
+#[test]
+fn allowlist_counts_recorded_crafts() {
+    let user = Pubkey::new_unique();
+    let other = Pubkey::new_unique();
+    // two leaf tree of (user, 2) + (other, 1)
+    let leaf = |user: &Pubkey, allowance: u32| {
+        keccak::hashv(&[&[0x00], user.as_ref(), &allowance.to_le_bytes()]).0
+    };
+    let (a, b) = (leaf(&user, 2), leaf(&other, 1));
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let root = keccak::hashv(&[&[0x01], &low, &high]).0;
+
+    let mut recipe = recipe(&[], &[]);
+    recipe.allowlist_root = Some(root);
+    let mut holdings = Holdings {
+        user,
+        tokens: vec![],
+        crafted: 1,
+    };
+    assert!(simulate_craft(&recipe, &holdings, 2, &[b]).is_ok());
+    assert_error(
+        simulate_craft(&recipe, &holdings, 3, &[b]),
+        ErrorCode::NotAllowlisted,
+    );
+    holdings.crafted = 2;
+    assert_error(
+        simulate_craft(&recipe, &holdings, 2, &[b]),
+        ErrorCode::AllowanceExceeded,
+    );
+}
@@ -0,0 +1,95 @@
+/*
+    craft-skins-indexer => reference indexer of craft_skins into SQLite (store.rs)
+      accounts => getProgramAccounts, each account decoded by its discriminator
+      events   => getSignaturesForAddress of the program since the last indexed
+                  signature, CraftEvents parsed from logs of successful transactions
+    one pass by default, --poll repeats passes, default cluster is a local test validator
+*/
+use anyhow::Result;
+use clap::Parser;
+use craft_skins_client::decode::{decode_account, parse_craft_events};
+use serde_json::json;
+use std::path::PathBuf;
+use std::thread::sleep;
+use std::time::Duration;
+
+mod rpc;
+mod store;
+
+use rpc::{HistoryRpc, Rpc};
+use store::Store;
+
+#[derive(Parser)]
+#[clap(
+    name = "craft-skins-indexer",
+    version,
+    about = "Index craft_skins accounts and craft events into SQLite"
+)]
+struct Cli {
+    /// RPC url of the cluster
+    #[clap(long, short, default_value = "http://127.0.0.1:8899")]
+    url: String,
+    /// SQLite database, created if missing
+    #[clap(long, short, default_value = "craft_skins.db")]
+    db: PathBuf,
+    /// Seconds between passes, runs a single pass when not set
+    #[clap(long)]
+    poll: Option<u64>,
+}
+
+fn index_accounts(rpc: &Rpc, store: &mut Store) -> Result<()> {
+    let accounts = rpc
+        .program_accounts(&craft_skins_client::id(), json!([]))?
+        .into_iter()
+        .map(|(address, data)| {
+            // accounts failing to decode are kept in accounts without a kind
+            let account = decode_account(&data).unwrap_or_else(|err| {
+                eprintln!("account {} does not decode: {}", address, err);
+                None
+            });
+            (address, data, account)
+        })
+        .collect::<Vec<_>>();
+    let decoded = store.replace_accounts(&accounts)?;
+    println!("{} accounts, {} decoded", accounts.len(), decoded);
+    Ok(())
+}
+
+fn index_events(rpc: &Rpc, store: &mut Store) -> Result<()> {
+    let until = store.last_signature()?;
+    let signatures = rpc.signatures_since(&craft_skins_client::id(), until.as_deref())?;
+    let mut events = 0;
+    // oldest first, so last_signature stays correct if a pass stops halfway
+    for info in signatures.iter() {
+        if info.failed {
+            store.insert_transaction(&info.signature, info.slot, true, None, &[])?;
+            continue;
+        }
+        let transaction = rpc.transaction_logs(&info.signature)?;
+        let craft_events = parse_craft_events(&transaction.logs);
+        store.insert_transaction(
+            &info.signature,
+            transaction.slot,
+            false,
+            transaction.block_time,
+            &craft_events,
+        )?;
+        events += craft_events.len();
+    }
+    println!("{} transactions, {} craft events", signatures.len(), events);
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let rpc = Rpc::new(&cli.url);
+    let mut store = Store::open(&cli.db)?;
+    loop {
+        index_accounts(&rpc, &mut store)?;
+        index_events(&rpc, &mut store)?;
+        match cli.poll {
+            Some(seconds) => sleep(Duration::from_secs(seconds)),
+            None => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,106 @@
+/*
+    transaction history read by the indexer over craft_skins_client::rpc::Rpc
+    commitment is always confirmed, account reads come with Rpc itself
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, Result};
+use serde_json::json;
+
+pub use craft_skins_client::rpc::Rpc;
+
+// getSignaturesForAddress returns at most 1000 signatures per call
+const SIGNATURES_LIMIT: usize = 1000;
+
+// signature of a transaction mentioning the program, err is set for failed transactions
+pub struct SignatureInfo {
+    pub signature: String,
+    pub slot: u64,
+    pub failed: bool,
+}
+
+pub struct TransactionLogs {
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    pub logs: Vec<String>,
+}
+
+pub trait HistoryRpc {
+    fn signatures_since(&self, address: &Pubkey, until: Option<&str>)
+        -> Result<Vec<SignatureInfo>>;
+    fn transaction_logs(&self, signature: &str) -> Result<TransactionLogs>;
+}
+
+impl HistoryRpc for Rpc {
+    /*
+        signatures of address newer than until (all of them without until), oldest first
+        pages of getSignaturesForAddress are walked back with before
+    */
+    fn signatures_since(
+        &self,
+        address: &Pubkey,
+        until: Option<&str>,
+    ) -> Result<Vec<SignatureInfo>> {
+        let mut signatures = vec![];
+        let mut before: Option<String> = None;
+        loop {
+            let config = json!({
+                "commitment": "confirmed",
+                "limit": SIGNATURES_LIMIT,
+                "until": until,
+                "before": before,
+            });
+            let result = self.call(
+                "getSignaturesForAddress",
+                json!([address.to_string(), config]),
+            )?;
+            let page = result
+                .as_array()
+                .ok_or_else(|| anyhow!("getSignaturesForAddress returned no signatures"))?;
+            for info in page.iter() {
+                signatures.push(SignatureInfo {
+                    signature: info["signature"]
+                        .as_str()
+                        .ok_or_else(|| anyhow!("getSignaturesForAddress returned no signature"))?
+                        .to_string(),
+                    slot: info["slot"]
+                        .as_u64()
+                        .ok_or_else(|| anyhow!("getSignaturesForAddress returned no slot"))?,
+                    failed: !info["err"].is_null(),
+                });
+            }
+            if page.len() < SIGNATURES_LIMIT {
+                break;
+            }
+            before = signatures.last().map(|info| info.signature.clone());
+        }
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    fn transaction_logs(&self, signature: &str) -> Result<TransactionLogs> {
+        let config = json!({
+            "encoding": "json",
+            "commitment": "confirmed",
+            "maxSupportedTransactionVersion": 0,
+        });
+        let result = self.call("getTransaction", json!([signature, config]))?;
+        if result.is_null() {
+            return Err(anyhow!("transaction {} not found", signature));
+        }
+        let logs = result["meta"]["logMessages"]
+            .as_array()
+            .map(|logs| {
+                logs.iter()
+                    .filter_map(|log| log.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(TransactionLogs {
+            slot: result["slot"]
+                .as_u64()
+                .ok_or_else(|| anyhow!("getTransaction returned no slot"))?,
+            block_time: result["blockTime"].as_i64(),
+            logs,
+        })
+    }
+}
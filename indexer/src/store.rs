@@ -0,0 +1,292 @@
+/*
+    SQLite tables of the indexer
+      accounts        => every craft_skins account, kind from its discriminator + raw data
+                         (kind is NULL for discriminators unknown to this indexer)
+      recipes, large_recipes, player_profiles, pending_crafts, craft_jobs, managers
+                      => typed columns of decoded accounts
+      transactions    => every signature of the program already processed
+      craft_events    => CraftEvents of successful transactions, (signature, idx) is unique
+    account tables are snapshots, replaced by each pass since closed accounts disappear
+    u64 values that can exceed i64 (loot rolls) are stored as TEXT
+*/
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use craft_skins_client::decode::CraftSkinsAccount;
+use craft_skins_client::{CraftEvent, RecipeMode};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS accounts (
+    address TEXT PRIMARY KEY,
+    kind TEXT,
+    data BLOB NOT NULL
+);
+CREATE TABLE IF NOT EXISTS managers (
+    address TEXT PRIMARY KEY,
+    admin TEXT NOT NULL,
+    version INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS recipes (
+    address TEXT PRIMARY KEY,
+    revision INTEGER NOT NULL,
+    version INTEGER NOT NULL,
+    mode TEXT NOT NULL,
+    ingredients INTEGER NOT NULL,
+    craft_fee INTEGER NOT NULL,
+    royalty_bps INTEGER NOT NULL,
+    craft_duration INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT NOT NULL,
+    category TEXT NOT NULL,
+    sort_order INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS recipes_category ON recipes (category, sort_order);
+CREATE TABLE IF NOT EXISTS large_recipes (
+    address TEXT PRIMARY KEY,
+    revision INTEGER NOT NULL,
+    version INTEGER NOT NULL,
+    ingredients INTEGER NOT NULL,
+    outputs INTEGER NOT NULL,
+    craft_fee INTEGER NOT NULL,
+    royalty_bps INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS player_profiles (
+    address TEXT PRIMARY KEY,
+    user TEXT NOT NULL,
+    total_crafts INTEGER NOT NULL,
+    first_craft_at INTEGER NOT NULL,
+    last_craft_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS pending_crafts (
+    address TEXT PRIMARY KEY,
+    user TEXT NOT NULL,
+    recipe TEXT NOT NULL,
+    commit_slot INTEGER NOT NULL,
+    reveal_slot INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS craft_jobs (
+    address TEXT PRIMARY KEY,
+    user TEXT NOT NULL,
+    recipe TEXT NOT NULL,
+    skin_mint TEXT NOT NULL,
+    started_at INTEGER NOT NULL,
+    completes_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    failed INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS craft_events (
+    signature TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    slot INTEGER NOT NULL,
+    block_time INTEGER,
+    user TEXT NOT NULL,
+    recipe TEXT NOT NULL,
+    skin_mint TEXT NOT NULL,
+    roll TEXT,
+    loot_entry INTEGER,
+    PRIMARY KEY (signature, idx)
+);
+CREATE INDEX IF NOT EXISTS craft_events_user ON craft_events (user);
+CREATE INDEX IF NOT EXISTS craft_events_recipe ON craft_events (recipe);
+";
+
+const ACCOUNT_TABLES: [&str; 7] = [
+    "accounts",
+    "managers",
+    "recipes",
+    "large_recipes",
+    "player_profiles",
+    "pending_crafts",
+    "craft_jobs",
+];
+
+pub struct Store {
+    connection: Connection,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Store { connection })
+    }
+
+    // replaces the account tables by accounts, returns the number of decoded accounts
+    pub fn replace_accounts(
+        &mut self,
+        accounts: &[(Pubkey, Vec<u8>, Option<CraftSkinsAccount>)],
+    ) -> Result<usize> {
+        let tx = self.connection.transaction()?;
+        for table in ACCOUNT_TABLES.iter() {
+            tx.execute(&format!("DELETE FROM {}", table), [])?;
+        }
+        let mut decoded = 0;
+        for (address, data, account) in accounts.iter() {
+            tx.execute(
+                "INSERT INTO accounts (address, kind, data) VALUES (?1, ?2, ?3)",
+                params![
+                    address.to_string(),
+                    account.as_ref().map(CraftSkinsAccount::kind),
+                    data
+                ],
+            )?;
+            if let Some(account) = account {
+                insert_account(&tx, address, account)?;
+                decoded += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(decoded)
+    }
+
+    // newest processed signature, transactions after it are fetched by the next pass
+    pub fn last_signature(&self) -> Result<Option<String>> {
+        let signature = self
+            .connection
+            .query_row(
+                "SELECT signature FROM transactions ORDER BY slot DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(signature)
+    }
+
+    // records a processed transaction with its events in one SQLite transaction
+    pub fn insert_transaction(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        failed: bool,
+        block_time: Option<i64>,
+        events: &[CraftEvent],
+    ) -> Result<()> {
+        let tx = self.connection.transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, failed) VALUES (?1, ?2, ?3)",
+            params![signature, slot, failed],
+        )?;
+        for (idx, event) in events.iter().enumerate() {
+            tx.execute(
+                "INSERT OR IGNORE INTO craft_events
+                    (signature, idx, slot, block_time, user, recipe, skin_mint, roll, loot_entry)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    signature,
+                    idx,
+                    slot,
+                    block_time,
+                    event.user.to_string(),
+                    event.recipe.to_string(),
+                    event.skin_mint.to_string(),
+                    event.roll.map(|roll| roll.roll.to_string()),
+                    event.roll.map(|roll| roll.entry),
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn insert_account(tx: &Transaction, address: &Pubkey, account: &CraftSkinsAccount) -> Result<()> {
+    let address = address.to_string();
+    match account {
+        CraftSkinsAccount::Manager(manager) => {
+            tx.execute(
+                "INSERT INTO managers (address, admin, version) VALUES (?1, ?2, ?3)",
+                params![address, manager.admin.to_string(), manager.version],
+            )?;
+        }
+        CraftSkinsAccount::Recipe(recipe) => {
+            let mode = match recipe.mode {
+                RecipeMode::Fixed => "fixed",
+                RecipeMode::Random => "random",
+            };
+            tx.execute(
+                "INSERT INTO recipes
+                    (address, revision, version, mode, ingredients, craft_fee, royalty_bps,
+                     craft_duration, name, description, category, sort_order)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    address,
+                    recipe.revision,
+                    recipe.version,
+                    mode,
+                    recipe.mints.len(),
+                    recipe.craft_fee,
+                    recipe.royalty_bps,
+                    recipe.craft_duration,
+                    recipe.name,
+                    recipe.description,
+                    // variant name in lowercase, as in recipe manifests
+                    format!("{:?}", recipe.category).to_lowercase(),
+                    recipe.sort_order
+                ],
+            )?;
+        }
+        CraftSkinsAccount::LargeRecipe(recipe) => {
+            tx.execute(
+                "INSERT INTO large_recipes
+                    (address, revision, version, ingredients, outputs, craft_fee, royalty_bps)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    address,
+                    recipe.revision,
+                    recipe.version,
+                    recipe.ingredient_count,
+                    recipe.output_count,
+                    recipe.craft_fee,
+                    recipe.royalty_bps
+                ],
+            )?;
+        }
+        CraftSkinsAccount::PlayerProfile(profile) => {
+            tx.execute(
+                "INSERT INTO player_profiles
+                    (address, user, total_crafts, first_craft_at, last_craft_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    address,
+                    profile.user.to_string(),
+                    profile.total_crafts,
+                    profile.first_craft_at,
+                    profile.last_craft_at
+                ],
+            )?;
+        }
+        CraftSkinsAccount::PendingCraft(pending) => {
+            tx.execute(
+                "INSERT INTO pending_crafts (address, user, recipe, commit_slot, reveal_slot)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    address,
+                    pending.user.to_string(),
+                    pending.recipe.to_string(),
+                    pending.commit_slot,
+                    pending.reveal_slot
+                ],
+            )?;
+        }
+        CraftSkinsAccount::CraftJob(job) => {
+            tx.execute(
+                "INSERT INTO craft_jobs
+                    (address, user, recipe, skin_mint, started_at, completes_at)
+                    VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    address,
+                    job.user.to_string(),
+                    job.recipe.to_string(),
+                    job.skin_mint.to_string(),
+                    job.started_at,
+                    job.completes_at
+                ],
+            )?;
+        }
+    }
+    Ok(())
+}
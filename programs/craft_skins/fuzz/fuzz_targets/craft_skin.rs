@@ -0,0 +1,207 @@
+/*
+    craft_skin against a solana-program-test bank, one bank per input
+      recipe      => up to MAX_INGREDIENTS ingredients of random amounts, mints can repeat
+      holdings    => random amount of each ingredient held by the user
+      remaining   => ingredient triplets swapped / cut short before craft_skin
+    invariants
+      craft_skins never panics (ProgramFailedToComplete)
+      ingredient tokens are conserved between user and recipe vault escrow
+      skin is delivered only when every ingredient amount moved to escrow,
+      a failed craft changes no balance
+
+    runs the craft_skins BPF build like tests/common, token metadata is native:
+      cargo build-bpf
+      cd programs/craft_skins/fuzz
+      BPF_OUT_DIR=../../../target/deploy cargo +nightly fuzz run craft_skin
+*/
+#![no_main]
+
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use arbitrary::Arbitrary;
+use common::*;
+use craft_skins::utils::Recipe;
+use libfuzzer_sys::fuzz_target;
+use solana_program_test::{tokio, BanksClient, BanksClientError};
+use solana_sdk::{
+    instruction::InstructionError, pubkey::Pubkey, signature::Signer, transaction::TransactionError,
+};
+use std::collections::BTreeMap;
+
+#[derive(Arbitrary, Debug)]
+struct Ingredient {
+    amount: u8,
+    held: u8,
+    // index of an earlier ingredient whose mint is reused
+    same_mint_as: Option<u8>,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Scenario {
+    ingredients: Vec<Ingredient>,
+    // swaps of remaining_accounts indexes, applied in order
+    swaps: Vec<(u8, u8)>,
+    // remaining_accounts kept, all of them when None
+    keep: Option<u8>,
+}
+
+// program panics surface as ProgramFailedToComplete, any other error is a rejected craft
+fn assert_no_panic(result: &Result<(), BanksClientError>) {
+    if let Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+        _,
+        InstructionError::ProgramFailedToComplete,
+    ))) = result
+    {
+        panic!("craft_skins panicked: {:?}", result);
+    }
+}
+
+async fn token_amount_or_zero(banks_client: &mut BanksClient, token: &Pubkey) -> u64 {
+    match banks_client.get_account(*token).await.unwrap() {
+        Some(_) => token_amount(banks_client, token).await,
+        None => 0,
+    }
+}
+
+// (user, escrow) amounts of every distinct mint, escrow is the recipe vault
+async fn balances(
+    banks_client: &mut BanksClient,
+    user: &Pubkey,
+    recipe: &Pubkey,
+    mints: &[Pubkey],
+) -> BTreeMap<Pubkey, (u64, u64)> {
+    let mut balances = BTreeMap::new();
+    for mint in mints.iter() {
+        let user_token = get_associated_token_address(user, mint);
+        let escrow_token = get_associated_token_address(&recipe_vault_pda(recipe), mint);
+        let amounts = (
+            token_amount_or_zero(banks_client, &user_token).await,
+            token_amount_or_zero(banks_client, &escrow_token).await,
+        );
+        balances.insert(*mint, amounts);
+    }
+    balances
+}
+
+async fn run(scenario: Scenario) {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let owner = add_wallet(&mut program_test);
+    let user = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let skin_nft = add_nft(
+        &mut program_test,
+        &owner.pubkey(),
+        &owner.pubkey(),
+        Some(recipe_nft.mint),
+    );
+
+    let mut mints: Vec<Pubkey> = vec![];
+    let mut amounts = vec![];
+    let mut held: BTreeMap<Pubkey, u64> = BTreeMap::new();
+    for ingredient in scenario.ingredients.iter().take(Recipe::MAX_INGREDIENTS) {
+        let reused = ingredient
+            .same_mint_as
+            .and_then(|index| mints.get(index as usize).copied());
+        let mint = reused.unwrap_or_else(|| add_mint(&mut program_test, &admin.pubkey(), 0));
+        mints.push(mint);
+        amounts.push(ingredient.amount as u64);
+        held.entry(mint).or_insert(ingredient.held as u64);
+    }
+    for (mint, amount) in held.iter() {
+        add_token_account(&mut program_test, mint, &user.pubkey(), *amount);
+    }
+
+    let (mut banks_client, _payer, _) = program_test.start().await;
+    process(
+        &mut banks_client,
+        &admin,
+        &[initialize_ix(&admin.pubkey())],
+        &[],
+    )
+    .await
+    .unwrap();
+    let result = process(
+        &mut banks_client,
+        &owner,
+        &[
+            create_recipe_ix(&owner.pubkey(), &recipe_nft, mints.clone(), amounts.clone()),
+            add_skin_ix(&owner.pubkey(), &recipe_nft, &skin_nft),
+        ],
+        &[],
+    )
+    .await;
+    assert_no_panic(&result);
+    if result.is_err() {
+        return;
+    }
+
+    let mut instruction = craft_skin_ix(
+        &owner.pubkey(),
+        &user.pubkey(),
+        &admin.pubkey(),
+        &recipe_nft,
+        &skin_nft,
+        &mints,
+    );
+    let named = instruction.accounts.len() - mints.len() * 3;
+    let remaining = &mut instruction.accounts[named..];
+    for (a, b) in scenario.swaps.iter() {
+        if !remaining.is_empty() {
+            let len = remaining.len();
+            remaining.swap(*a as usize % len, *b as usize % len);
+        }
+    }
+    if let Some(keep) = scenario.keep {
+        instruction
+            .accounts
+            .truncate(named + keep as usize % (mints.len() * 3 + 1));
+    }
+
+    let recipe = recipe_pda(&recipe_nft.mint);
+    let distinct: Vec<Pubkey> = held.keys().copied().collect();
+    let before = balances(&mut banks_client, &user.pubkey(), &recipe, &distinct).await;
+    let result = process(&mut banks_client, &user, &[instruction], &[&owner]).await;
+    assert_no_panic(&result);
+    let after = balances(&mut banks_client, &user.pubkey(), &recipe, &distinct).await;
+
+    for mint in distinct.iter() {
+        let (user_before, escrow_before) = before[mint];
+        let (user_after, escrow_after) = after[mint];
+        assert_eq!(
+            user_before + escrow_before,
+            user_after + escrow_after,
+            "tokens of {} not conserved",
+            mint
+        );
+    }
+
+    let user_skin = get_associated_token_address(&user.pubkey(), &skin_nft.mint);
+    let delivered = token_amount_or_zero(&mut banks_client, &user_skin).await == 1;
+    assert_eq!(delivered, result.is_ok(), "skin delivery != craft result");
+    if !delivered {
+        assert_eq!(before, after, "failed craft moved tokens");
+        assert_eq!(token_amount(&mut banks_client, &skin_nft.token).await, 1);
+        return;
+    }
+    // every ingredient entry paid its recipe amount into escrow
+    for mint in distinct.iter() {
+        let paid: u64 = mints
+            .iter()
+            .zip(amounts.iter())
+            .filter(|(ingredient, _)| *ingredient == mint)
+            .map(|(_, amount)| amount)
+            .sum();
+        assert_eq!(after[mint].1 - before[mint].1, paid, "escrow of {}", mint);
+    }
+}
+
+fuzz_target!(|scenario: Scenario| {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(run(scenario));
+});
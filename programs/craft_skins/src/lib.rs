@@ -1,11 +1,16 @@
+// handlers + helpers return anchor_lang::Result, the size of its Error is set by anchor
+#![allow(clippy::result_large_err)]
+
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::mint;
 use anchor_spl::token::{Mint, Token, TokenAccount};
 use mpl_token_metadata::assertions::collection::assert_collection_verify_is_valid;
 use mpl_token_metadata::state::Metadata;
 
+pub mod rules;
 pub mod utils;
+use rules::{check_account_version, check_recipe_mode};
+use utils::ErrorCode;
 use utils::*;
 
 declare_id!("34FUZfjWu2jMkBti3sKDrHH3rWRS3MjhWC5xjBps6cku");
@@ -19,6 +24,11 @@ pub mod craft_skins {
         let program_manager = &mut ctx.accounts.program_manager;
         let manager = &ctx.accounts.manager;
         program_manager.admin = manager.key();
+        program_manager.bump = *ctx.bumps.get("program_manager").unwrap();
+        // program_signer signs every craft, bump is found once here
+        let (_, signer_bump) = Pubkey::find_program_address(&[b"signer"], ctx.program_id);
+        program_manager.signer_bump = signer_bump;
+        program_manager.version = Manager::VERSION;
         Ok(())
     }
 
@@ -30,18 +40,21 @@ pub mod craft_skins {
     pub fn create_recipe(
         // CreateRecipe contains accounts to init Recipe NFT
         ctx: Context<CreateRecipe>,
-        ingredient_mints: Vec<Pubkey>,
-        ingredient_amounts: Vec<u64>,
+        settings: RecipeSettings, // ingredients, fee, outputs, timing, gates + listing fields
     ) -> Result<()> {
-        // loop through ingredient_mints / ingredient_amounts
-        // add to CreateRecipe.recipe, see "pub struct Recipe" below
+        // validate + write ingredient_mints / ingredient_amounts and settings
+        // to CreateRecipe.recipe, see "pub struct Recipe" below
         let recipe_account = &mut ctx.accounts.recipe;
-        for (i, mint) in ingredient_mints.iter().enumerate() {
-            recipe_account.mints.push(*mint);
-            recipe_account
-                .amounts
-                .push(*ingredient_amounts.get(i).unwrap());
-        }
+        write_recipe_settings(recipe_account, settings)?;
+        recipe_account.revision = 1;
+        recipe_account.bump = *ctx.bumps.get("recipe").unwrap();
+        // recipe vault signs every craft, bump is found once here
+        let (_, vault_bump) = Pubkey::find_program_address(
+            &[b"vault", recipe_account.key().as_ref()],
+            ctx.program_id,
+        );
+        recipe_account.vault_bump = vault_bump;
+        recipe_account.version = Recipe::VERSION;
         msg!("Done recipe iter");
 
         // validate accounts to create Recipe NFT
@@ -63,7 +76,7 @@ pub mod craft_skins {
       validate Collection mint can derive Recipe PDA
       validate Recipe PDA is init and owned by admin
     **/
-    pub fn add_skin<'info>(
+    pub fn add_skin(
         // CreateRecipe contains accounts to init Recipe NFT
         ctx: Context<AddSkin>,
         _recipe_bump: u8,
@@ -75,6 +88,7 @@ pub mod craft_skins {
             &ctx.accounts.recipe_mint,
             &ctx.accounts.skin_metadata, // metadata is specific data, Metaplex standard
             &ctx.accounts.owner,         // owner of Recipe NFT
+            Some(&ctx.accounts.owner.key()), // owner minted the skin
         )?;
         msg!("Done verify skin");
 
@@ -88,14 +102,6 @@ pub mod craft_skins {
         )?;
         msg!("Done verify skin recipe");
 
-        // validate recipe_account is correct PDA using recipe_mint as seed
-        assert_recipe_derivation(
-            &ctx.accounts.recipe,
-            &ctx.program_id,
-            &[b"recipe", &ctx.accounts.recipe_mint.key().as_ref()],
-        )?;
-        msg!("Done verify recipe account PDA");
-
         // validate collection
         let skin_metadata_account = &mut Metadata::from_account_info(&ctx.accounts.skin_metadata)?;
         let collection_metadata_account =
@@ -111,6 +117,56 @@ pub mod craft_skins {
         Ok(())
     }
 
+    /*
+        holder of the Recipe NFT replaces every recipe setting, same RecipeSettings as create_recipe
+        revision is bumped, skins crafted after the update are stamped with it
+        pending crafts + craft jobs keep the ingredients they escrowed
+    */
+    pub fn update_recipe(ctx: Context<UpdateRecipe>, settings: RecipeSettings) -> Result<()> {
+        // settings are written in the current layout only
+        check_account_version(ctx.accounts.recipe.version, Recipe::VERSION)?;
+        verify_recipe_nft(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+            &ctx.accounts.owner,
+        )?;
+        msg!("Done verify recipe NFT");
+
+        let recipe_account = &mut ctx.accounts.recipe;
+        write_recipe_settings(recipe_account, settings)?;
+        recipe_account.revision += 1;
+
+        // shorter lists or strings leave no stale bytes after the Recipe serialized on exit
+        ctx.accounts
+            .recipe
+            .to_account_info()
+            .try_borrow_mut_data()?[8..]
+            .fill(0);
+        Ok(())
+    }
+
+    /*
+        holder of the Recipe NFT closes the Recipe account, rent goes back to holder
+        refused while PendingCraft / CraftJob accounts of the recipe are open,
+        finish / claim / cancel / refund read the Recipe
+    */
+    pub fn close_recipe(ctx: Context<CloseRecipe>) -> Result<()> {
+        verify_recipe_nft(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+            &ctx.accounts.owner,
+        )?;
+        msg!("Done verify recipe NFT");
+        if ctx.accounts.recipe.open_crafts > 0 {
+            return Err(ErrorCode::RecipeHasOpenCrafts.into());
+        }
+        Ok(())
+    }
+
     /*
       SERVER
         receive skin mint from client
@@ -125,35 +181,33 @@ pub mod craft_skins {
     */
     pub fn craft_skin<'info>(
         ctx: Context<'_, '_, '_, 'info, CraftSkin<'info>>,
-        //program_signer_bump: u8,
+        allowance: u32, // crafts allowed by allowlist leaf, ignored without allowlist
+        proof: Vec<[u8; 32]>, // merkle proof of (user, allowance) leaf
     ) -> Result<()> {
+        // accounts of older layouts are migrated before any new craft
+        check_account_version(ctx.accounts.recipe.version, Recipe::VERSION)?;
+        check_account_version(ctx.accounts.program_manager.version, Manager::VERSION)?;
+
         // validate accounts for existing skin
         verify_skin(
             &ctx.accounts.skin_token_account, // token account holds everything
             &ctx.accounts.skin_mint,          // mint of skin
             &ctx.accounts.recipe_mint,
             &ctx.accounts.skin_metadata, // metadata is specific data, Metaplex standard
-            &ctx.accounts.owner,         // owner of Recipe NFT
+            &ctx.accounts.owner,         // holder of the skin
+            Some(&ctx.accounts.owner.key()), // owner minted the skin
         )?;
         msg!("Done verify skin");
 
-        // validate accounts for exisiting Recipe NFT
-        verify_recipe_nft(
+        // validate accounts for exisiting Recipe NFT, its holder need not sign
+        verify_recipe_token(
             &ctx.accounts.recipe_token_account,
             &ctx.accounts.recipe_mint,
             &ctx.accounts.recipe_metadata,
             &ctx.accounts.recipe_master_edition,
-            &ctx.accounts.owner,
         )?;
         msg!("Done verify skin recipe");
 
-        // validate recipe_account is correct PDA using recipe_mint as seed
-        assert_recipe_derivation(
-            &ctx.accounts.recipe,
-            &ctx.program_id,
-            &[b"recipe", &ctx.accounts.recipe_mint.key().as_ref()],
-        )?;
-
         // validate collection
         let skin_metadata_account = &mut Metadata::from_account_info(&ctx.accounts.skin_metadata)?;
         let collection_metadata_account =
@@ -165,58 +219,137 @@ pub mod craft_skins {
             &ctx.accounts.recipe_master_edition.to_account_info(),
         )?;
 
-        // validate each user token account holds required mint+amount defined in Recipe
-        let mut i = 0;
-        let iterator = &mut ctx.remaining_accounts.iter();
-        while i < ctx.remaining_accounts.len() {
-            // user ingredient token account
-            let user_token = next_account_info(iterator)?;
-            // user ingredient mint (should == ingredient_mint)
-            let user_mint = next_account_info(iterator)?;
-            // program escrow PDA to receive user ingredient
-            let escrow_token = next_account_info(iterator)?;
-            // expected ingredient mint -> defined in Recipe
-            let ingredient_mint = &ctx.accounts.recipe.mints[i];
-            // expected ingredient amount -> defined in Recipe
-            let ingredient_amount = &ctx.accounts.recipe.amounts[i];
-
-            // verify user ingredient token == required ingredient mint/amount defined in Recipe
-            verify_user_ingredient(
-                &user_token,                          // ingredient token account
-                &ctx.accounts.user.to_account_info(), // owner of user_token account
-                &ingredient_mint, // expected mint inside user_token, defined in Recipe
-                &ingredient_amount, // expect amount inside user_token, deefined in Recipe
+        // random recipes roll their skin in craft_random_skin
+        check_recipe_mode(&ctx.accounts.recipe, RecipeMode::Fixed)?;
+
+        // allowlisted recipe => user leaf proven against root, crafts counted on PlayerProfile
+        if let Some(root) = &ctx.accounts.recipe.allowlist_root {
+            let crafted = recipe_craft_count(
+                &ctx.accounts.player_profile,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.recipe.key(),
+                ctx.program_id,
+            )?;
+            verify_allowlist(
+                root,
+                &ctx.accounts.user.key(),
+                allowance,
+                &proof,
+                crafted,
+                1,
             )?;
+            msg!("Done verify allowlist");
+        }
+
+        // gated recipe => gate accounts lead remaining_accounts, checked before any transfer
+        let iterator = &mut ctx.remaining_accounts.iter();
+        if let Some(gate) = &ctx.accounts.recipe.gate {
+            verify_recipe_gate(gate, iterator, &ctx.accounts.user.to_account_info())?;
+            msg!("Done verify recipe gate");
+        }
+
+        // timed recipe => [craft job PDA, program skin token] follow the gate accounts
+        let job_accounts = match ctx.accounts.recipe.craft_duration > 0 {
+            true => Some((next_account_info(iterator)?, next_account_info(iterator)?)),
+            false => None,
+        };
+        // a craft job holds its own escrow until claimed or canceled, instant crafts pay the vault
+        let escrow: &AccountInfo<'info> = match job_accounts {
+            Some((craft_job, _)) => craft_job,
+            None => &ctx.accounts.recipe_vault,
+        };
+
+        // validate each user token account holds required mint+amount defined in Recipe
+        let input_skins = escrow_ingredients(
+            &ctx.accounts.recipe,
+            1,
+            iterator,
+            &ctx.accounts.user,
+            escrow,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done user ingredient validations & transfer to escrows");
+
+        // royalty -> current Recipe NFT holder, remainder -> program admin
+        verify_fee_recipients(
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.admin,
+            &ctx.accounts.program_manager,
+        )?;
+        pay_craft_fee(
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            ctx.accounts.recipe.craft_fee,
+            ctx.accounts.recipe.royalty_bps,
+        )?;
+        msg!("Done pay craft fee");
+
+        record_craft(
+            &ctx.accounts.player_profile,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+            &ctx.accounts.recipe.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        )?;
+        msg!("Done record craft");
 
-            // do some stuff
+        // timed recipe => skin moves to program custody, CraftJob opened for claim_craft
+        if let Some((craft_job, program_skin_token)) = job_accounts {
             create_escrow_account(
                 &ctx.accounts.user,
                 &ctx.accounts.program_signer,
-                escrow_token,
-                user_mint,
+                program_skin_token,
+                &ctx.accounts.skin_mint.to_account_info(),
                 &ctx.accounts.rent_account,
                 &ctx.accounts.token_program,
                 &ctx.accounts.ata_program,
                 &ctx.accounts.system_program,
             )?;
-
-            // transfer token from user to escrow
-            transfer_ingredient_to_escrow(
-                user_token,
-                escrow_token,
-                &ctx.accounts.user,
-                ingredient_amount,
+            transfer_skin_to_user(
+                &ctx.accounts.skin_token_account.to_account_info(),
+                program_skin_token,
+                &ctx.accounts.owner,
                 &ctx.accounts.token_program,
             )?;
-            msg!("transfer_ingredient_to_escrow");
+            msg!("transfer_skin_to_vault");
 
-            i += 3;
+            let started_at = Clock::get()?.unix_timestamp;
+            let completes_at = started_at
+                .checked_add(ctx.accounts.recipe.craft_duration)
+                .ok_or(ErrorCode::CraftDurationInvalid)?;
+            create_craft_job(
+                craft_job,
+                &ctx.accounts.user,
+                &ctx.accounts.system_program,
+                ctx.program_id,
+                CraftJob {
+                    user: ctx.accounts.user.key(),
+                    recipe: ctx.accounts.recipe.key(),
+                    skin_mint: ctx.accounts.skin_mint.key(),
+                    skin_owner: ctx.accounts.owner.key(),
+                    started_at,
+                    completes_at,
+                    mints: ctx.accounts.recipe.mints.clone(),
+                    amounts: ctx.accounts.recipe.amounts.clone(),
+                    bump: 0,
+                    input_skins,
+                },
+            )?;
+            ctx.accounts.recipe.open_craft();
+            msg!("Done open craft job");
+            return Ok(());
         }
-        msg!("Done user ingredient validations & transfer to escrows");
 
         create_user_token_account(
             &ctx.accounts.user,
-            &ctx.accounts.program_signer,
             &ctx.accounts.user_skin_token_account,
             &ctx.accounts.skin_mint.to_account_info(),
             &ctx.accounts.rent_account,
@@ -226,210 +359,2222 @@ pub mod craft_skins {
         )?;
         msg!("Create if not init user_skin_token_account");
 
-        // transfer skin to user
+        // transfer skin to user, skin_token_account is owned by owner
         transfer_skin_to_user(
             &ctx.accounts.skin_token_account.to_account_info(),
             &ctx.accounts.user_skin_token_account.to_account_info(),
-            &ctx.accounts.program_signer,
+            &ctx.accounts.owner,
             &ctx.accounts.token_program,
         )?;
         msg!("transfer_skin_to_user");
 
+        let signer_bump = ctx.accounts.program_manager.signer_bump;
+        stamp_skin_provenance(
+            &ctx.accounts.skin_metadata,
+            &ctx.accounts.program_signer,
+            &ctx.accounts.token_metadata_program,
+            signer_bump,
+            &ctx.accounts.user.key(),
+            Clock::get()?.unix_timestamp,
+            ctx.accounts.recipe.revision,
+        )?;
+        msg!("Done stamp skin provenance");
+
+        let recipe_key = ctx.accounts.recipe.key();
+        let vault_bump = [ctx.accounts.recipe.vault_bump];
+        let vault = vault_seeds(&recipe_key, &vault_bump);
+        // deliver each Recipe output after the ingredient + input skin accounts in remaining_accounts
+        for output in ctx.accounts.recipe.outputs.iter() {
+            let output_accounts = next_output_accounts(iterator, output.kind)?;
+            deliver_output(
+                output,
+                &output_accounts,
+                &ctx.accounts.user,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.recipe_vault,
+                &vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+            msg!("deliver_output");
+        }
+        msg!("Done deliver recipe outputs");
+
+        // salvage recipe => [salvage record] follows the output accounts
+        let recipe = &ctx.accounts.recipe;
+        if recipe.salvage_bps > 0 {
+            write_salvage_record(
+                next_account_info(iterator)?,
+                &ctx.accounts.user,
+                &ctx.accounts.system_program,
+                ctx.program_id,
+                SalvageRecord {
+                    recipe: recipe.key(),
+                    skin_mint: ctx.accounts.skin_mint.key(),
+                    mints: recipe.mints.clone(),
+                    amounts: salvage_amounts(&recipe.amounts, recipe.salvage_bps),
+                    bump: 0,
+                },
+            )?;
+            msg!("Done write salvage record");
+        }
+
+        emit!(CraftEvent {
+            user: ctx.accounts.user.key(),
+            recipe: ctx.accounts.recipe.key(),
+            skin_mint: ctx.accounts.skin_mint.key(),
+            roll: None,
+        });
+
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    // payer for accounts
-    #[account(mut)]
-    pub manager: Signer<'info>,
+    /*
+      craft quantity skins of a Fixed, untimed recipe in one instruction
+        escrow amount * quantity of each ingredient, one transfer per ingredient
+        pay craft_fee * quantity
+        deliver quantity skins held by owner + Recipe outputs of each craft
 
-    // init Manager account
-    #[account(
-    init,
-    payer = manager,
-    seeds = [b"manager"],
-    bump,
-    space = 240
-    )]
-    pub program_manager: Account<'info, Manager>,
-    // solana program
-    pub system_program: Program<'info, System>,
-}
+      remaining_accounts
+        gate accounts (gated recipes)
+        ingredient triplets, then input skin accounts of every craft (upgrade recipes)
+        per skin: [owner skin token, skin mint, skin metadata, user skin token], owner is its verified creator
+        accounts of every fixed Recipe output, repeated for every craft
+        salvage record of every skin (recipes with salvage_bps)
+    */
+    pub fn craft_skin_batch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CraftSkinBatch<'info>>,
+        quantity: u8,
+        allowance: u32, // crafts allowed by allowlist leaf, ignored without allowlist
+        proof: Vec<[u8; 32]>, // merkle proof of (user, allowance) leaf
+    ) -> Result<()> {
+        // accounts of older layouts are migrated before any new craft
+        check_account_version(ctx.accounts.recipe.version, Recipe::VERSION)?;
+        check_account_version(ctx.accounts.program_manager.version, Manager::VERSION)?;
+        let recipe = &ctx.accounts.recipe;
+        if quantity == 0 || quantity > MAX_BATCH_QUANTITY || recipe.craft_duration > 0 {
+            return Err(ErrorCode::BatchQuantityInvalid.into());
+        }
+        // random recipes roll their skin in craft_random_skin
+        check_recipe_mode(recipe, RecipeMode::Fixed)?;
 
-// admin is program authority (he who owns the private key)
-// signs for Account creation and manipulation
-#[account]
-pub struct Manager {
-    admin: Pubkey,
-}
+        // validate accounts for exisiting Recipe NFT, its holder need not sign
+        verify_recipe_token(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+        )?;
+        msg!("Done verify skin recipe");
 
-#[derive(Accounts)]
-pub struct CreateRecipe<'info> {
-    // owner of Recipe NFT
-    #[account(mut)]
-    pub owner: Signer<'info>,
+        if let Some(root) = &recipe.allowlist_root {
+            let crafted = recipe_craft_count(
+                &ctx.accounts.player_profile,
+                &ctx.accounts.user.key(),
+                &recipe.key(),
+                ctx.program_id,
+            )?;
+            verify_allowlist(
+                root,
+                &ctx.accounts.user.key(),
+                allowance,
+                &proof,
+                crafted,
+                quantity as u32,
+            )?;
+            msg!("Done verify allowlist");
+        }
 
-    /**
-      account defining recipe of mints+amounts
-      to craft a skin NFT
-    **/
-    #[account(
-        init,
-        payer = owner,
-        seeds = [b"recipe", recipe_mint.key().as_ref()],
-        bump,
-        space = 240
-    )]
-    pub recipe: Account<'info, Recipe>,
+        let iterator = &mut ctx.remaining_accounts.iter();
+        if let Some(gate) = &recipe.gate {
+            verify_recipe_gate(gate, iterator, &ctx.accounts.user.to_account_info())?;
+            msg!("Done verify recipe gate");
+        }
+        escrow_ingredients(
+            recipe,
+            quantity as u64,
+            iterator,
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_vault,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done user ingredient validations & transfer to escrows");
 
-    /**
-      required accounts for an NFT
-      should new Recipe NFT to mint
-    **/
-    #[account(mut)]
-    pub recipe_token_account: Account<'info, TokenAccount>,
-    pub recipe_mint: Account<'info, Mint>,
-    ///CHECK: verification is run in instruction
-    #[account(mut)]
-    pub recipe_metadata: AccountInfo<'info>,
+        verify_fee_recipients(
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.admin,
+            &ctx.accounts.program_manager,
+        )?;
+        pay_craft_fee(
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            recipe
+                .craft_fee
+                .checked_mul(quantity as u64)
+                .ok_or(ErrorCode::BatchQuantityInvalid)?,
+            recipe.royalty_bps,
+        )?;
+        msg!("Done pay craft fee");
 
-    ///CHECK: verification is run in instruction
-    pub recipe_master_edition: AccountInfo<'info>,
+        let crafted_at = Clock::get()?.unix_timestamp;
+        record_craft(
+            &ctx.accounts.player_profile,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+            &recipe.key(),
+            quantity as u32,
+            crafted_at,
+        )?;
+        msg!("Done record craft");
 
-    /**
-      required programs for an NFT
-    **/
-    // holds SOL to pay for all Account rent
-    pub rent_account: Sysvar<'info, Rent>,
-    // creates Metadata account within Token Account
-    #[account(address = mpl_token_metadata::ID)]
-    ///CHECK: verification is run in instruction
-    pub token_metadata_program: AccountInfo<'info>,
-    // creates Token Account of NFT
-    pub token_program: Program<'info, Token>,
-    // creates generic Account
-    pub system_program: Program<'info, System>,
-}
+        let signer_bump = ctx.accounts.program_manager.signer_bump;
+        let mut skin_mints = vec![];
+        for _ in 0..quantity {
+            let owner_skin_token = next_account_info(iterator)?;
+            let skin_mint = next_account_info(iterator)?;
+            let skin_metadata = next_account_info(iterator)?;
+            let user_skin_token = next_account_info(iterator)?;
 
-/*
-    validate necessary NFT accounts
-      verify_recipe_nft
-    find ingredients from recipe NFT account param
-    validate recipe account
-      seeds
-      init
-      owned by program?
-    create skin with recipe as Collection
-*/
-#[derive(Accounts)]
-pub struct AddSkin<'info> {
-    // owner of Recipe NFT
-    #[account(mut)]
-    pub owner: Signer<'info>,
+            // skin held by owner, verified in Recipe collection, owner minted the skin
+            verify_token_balance(
+                owner_skin_token,
+                &ctx.accounts.owner.to_account_info(),
+                skin_mint.key,
+                1,
+            )?;
+            verify_skin_collection(skin_mint, skin_metadata, &ctx.accounts.recipe_mint.key())?;
+            verify_creator(skin_metadata, &ctx.accounts.owner.key())?;
 
-    /**
-      account defining recipe of mints+amounts
-      to craft a skin NFT
-    **/
-    #[account(
-        seeds = [b"recipe", recipe_mint.key().as_ref()],
-        bump
-    )]
-    pub recipe: Account<'info, Recipe>,
+            create_user_token_account(
+                &ctx.accounts.user,
+                user_skin_token,
+                skin_mint,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+            transfer_skin_to_user(
+                owner_skin_token,
+                user_skin_token,
+                &ctx.accounts.owner,
+                &ctx.accounts.token_program,
+            )?;
+            stamp_skin_provenance(
+                skin_metadata,
+                &ctx.accounts.program_signer,
+                &ctx.accounts.token_metadata_program,
+                signer_bump,
+                &ctx.accounts.user.key(),
+                crafted_at,
+                recipe.revision,
+            )?;
+            msg!("transfer_skin_to_user");
 
-    /**
-      required accounts for an NFT
-      should be exisiting Recipe NFT
-    **/
-    #[account(mut)]
-    pub recipe_token_account: Account<'info, TokenAccount>,
-    pub recipe_mint: Account<'info, Mint>,
-    ///CHECK: verification is run in instruction
-    #[account(mut)]
-    pub recipe_metadata: AccountInfo<'info>,
-    ///CHECK: verification is run in instruction
-    pub recipe_master_edition: AccountInfo<'info>,
+            emit!(CraftEvent {
+                user: ctx.accounts.user.key(),
+                recipe: recipe.key(),
+                skin_mint: skin_mint.key(),
+                roll: None,
+            });
+            skin_mints.push(skin_mint.key());
+        }
 
-    /***
-      required accounts for an NFT
-      should be new skin to mint
-    **/
-    #[account(mut)]
-    pub skin_token_account: Account<'info, TokenAccount>,
+        let recipe_key = recipe.key();
+        let vault_bump = [ctx.accounts.recipe.vault_bump];
+        let vault = vault_seeds(&recipe_key, &vault_bump);
+        for _ in 0..quantity {
+            for output in recipe.outputs.iter() {
+                let output_accounts = next_output_accounts(iterator, output.kind)?;
+                deliver_output(
+                    output,
+                    &output_accounts,
+                    &ctx.accounts.user,
+                    &ctx.accounts.user.to_account_info(),
+                    &ctx.accounts.recipe_vault,
+                    &vault,
+                    &ctx.accounts.rent_account,
+                    &ctx.accounts.token_program,
+                    &ctx.accounts.ata_program,
+                    &ctx.accounts.system_program,
+                )?;
+            }
+        }
+        msg!("Done deliver recipe outputs");
+
+        if recipe.salvage_bps > 0 {
+            for skin_mint in skin_mints {
+                write_salvage_record(
+                    next_account_info(iterator)?,
+                    &ctx.accounts.user,
+                    &ctx.accounts.system_program,
+                    ctx.program_id,
+                    SalvageRecord {
+                        recipe: recipe.key(),
+                        skin_mint,
+                        mints: recipe.mints.clone(),
+                        amounts: salvage_amounts(&recipe.amounts, recipe.salvage_bps),
+                        bump: 0,
+                    },
+                )?;
+            }
+            msg!("Done write salvage records");
+        }
+
+        Ok(())
+    }
+
+    /*
+      craft a Random mode recipe
+        escrow ingredients + pay craft fee, allowlist + PlayerProfile (same as craft_skin)
+        roll randomness source, select weighted entry from Recipe loot_table
+        deliver rolled entry + fixed Recipe outputs
+
+      remaining_accounts
+        gate accounts (gated recipes)
+        ingredient triplets, then input skin accounts (upgrade recipes)
+        accounts of every loot_table entry (only rolled entry is delivered)
+        accounts of every fixed Recipe output
+    */
+    pub fn craft_random_skin<'info>(
+        ctx: Context<'_, '_, '_, 'info, CraftRandomSkin<'info>>,
+        allowance: u32, // crafts allowed by allowlist leaf, ignored without allowlist
+        proof: Vec<[u8; 32]>, // merkle proof of (user, allowance) leaf
+    ) -> Result<()> {
+        // accounts of older layouts are migrated before any new craft
+        check_account_version(ctx.accounts.recipe.version, Recipe::VERSION)?;
+        check_account_version(ctx.accounts.program_manager.version, Manager::VERSION)?;
+        check_recipe_mode(&ctx.accounts.recipe, RecipeMode::Random)?;
+        // rolled in a single transaction only from slot hashes without a reveal delay
+        if ctx.accounts.recipe.reveal_delay > 0 || ctx.accounts.recipe.randomness_program.is_some()
+        {
+            return Err(ErrorCode::CommitRevealRequired.into());
+        }
+
+        // allowlisted recipe => user leaf proven against root, crafts counted on PlayerProfile
+        if let Some(root) = &ctx.accounts.recipe.allowlist_root {
+            let crafted = recipe_craft_count(
+                &ctx.accounts.player_profile,
+                &ctx.accounts.user.key(),
+                &ctx.accounts.recipe.key(),
+                ctx.program_id,
+            )?;
+            verify_allowlist(
+                root,
+                &ctx.accounts.user.key(),
+                allowance,
+                &proof,
+                crafted,
+                1,
+            )?;
+            msg!("Done verify allowlist");
+        }
+
+        let iterator = &mut ctx.remaining_accounts.iter();
+        if let Some(gate) = &ctx.accounts.recipe.gate {
+            verify_recipe_gate(gate, iterator, &ctx.accounts.user.to_account_info())?;
+            msg!("Done verify recipe gate");
+        }
+        escrow_ingredients(
+            &ctx.accounts.recipe,
+            1,
+            iterator,
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_vault,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done user ingredient validations & transfer to escrows");
+
+        verify_fee_recipients(
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.admin,
+            &ctx.accounts.program_manager,
+        )?;
+        pay_craft_fee(
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            ctx.accounts.recipe.craft_fee,
+            ctx.accounts.recipe.royalty_bps,
+        )?;
+        msg!("Done pay craft fee");
+
+        record_craft(
+            &ctx.accounts.player_profile,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+            &ctx.accounts.recipe.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        )?;
+        msg!("Done record craft");
+
+        // roll loot table
+        let randomness = read_randomness(&ctx.accounts.randomness)?;
+        let roll = roll_loot(
+            &randomness,
+            &ctx.accounts.user.key(),
+            &ctx.accounts.recipe.key(),
+        );
+        let entry = select_loot_entry(&ctx.accounts.recipe.loot_table, roll)?;
+        msg!("rolled loot entry {}", entry);
+
+        let recipe_key = ctx.accounts.recipe.key();
+        let vault_bump = [ctx.accounts.recipe.vault_bump];
+        let vault = vault_seeds(&recipe_key, &vault_bump);
+        let mut skin_mint = Pubkey::default();
+        for (i, loot) in ctx.accounts.recipe.loot_table.iter().enumerate() {
+            let output_accounts = next_output_accounts(iterator, loot.output.kind)?;
+            if i != entry {
+                continue;
+            }
+            skin_mint = output_accounts.mint.key();
+            deliver_output(
+                &loot.output,
+                &output_accounts,
+                &ctx.accounts.user,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.recipe_vault,
+                &vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+        msg!("Done deliver loot entry");
+
+        for output in ctx.accounts.recipe.outputs.iter() {
+            let output_accounts = next_output_accounts(iterator, output.kind)?;
+            deliver_output(
+                output,
+                &output_accounts,
+                &ctx.accounts.user,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.recipe_vault,
+                &vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+        msg!("Done deliver recipe outputs");
+
+        emit!(CraftEvent {
+            user: ctx.accounts.user.key(),
+            recipe: ctx.accounts.recipe.key(),
+            skin_mint,
+            roll: Some(LootRoll {
+                roll,
+                entry: entry as u8,
+            }),
+        });
+
+        Ok(())
+    }
+
+    /*
+      claim skin + outputs of a timed craft once craft_duration has passed
+        speed_up => pay Recipe speed_up ingredient to claim early
+        escrow of the CraftJob moves to the recipe vault
+        close CraftJob, rent back to user
+
+      remaining_accounts
+        triplets of [job escrow token, mint, vault token] per escrowed ingredient, then input skin
+        speed up ingredient triplet (only when claiming early)
+        accounts of every fixed Recipe output
+        salvage record of the skin (recipes with salvage_bps)
+    */
+    pub fn claim_craft<'info>(
+        ctx: Context<'_, '_, '_, 'info, ClaimCraft<'info>>,
+        speed_up: bool,
+    ) -> Result<()> {
+        let iterator = &mut ctx.remaining_accounts.iter();
+        let recipe = &ctx.accounts.recipe;
+        let craft_job = &ctx.accounts.craft_job;
+
+        // escrow held by the job is spent once the skin is claimed
+        let recipe_key = recipe.key();
+        let user_key = ctx.accounts.user.key();
+        let job_bump = [craft_job.bump];
+        let job_seeds = craft_seeds(b"job", &recipe_key, &user_key, &job_bump);
+        let input_skin_amounts = vec![1; craft_job.input_skins.len()];
+        for (mints, amounts) in [
+            (&craft_job.mints, &craft_job.amounts),
+            (&craft_job.input_skins, &input_skin_amounts),
+        ] {
+            settle_escrow(
+                mints,
+                amounts,
+                iterator,
+                &craft_job.to_account_info(),
+                &job_seeds,
+                &ctx.accounts.user,
+                &ctx.accounts.recipe_vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+        msg!("Done settle job escrow");
+
+        if Clock::get()?.unix_timestamp < ctx.accounts.craft_job.completes_at {
+            let speed_up_mint = match (speed_up, recipe.speed_up_mint) {
+                (true, Some(mint)) => mint,
+                _ => return Err(ErrorCode::CraftNotReady.into()),
+            };
+            let user_token = next_account_info(iterator)?;
+            let user_mint = next_account_info(iterator)?;
+            let escrow_token = next_account_info(iterator)?;
+
+            verify_token_balance(
+                user_token,
+                &ctx.accounts.user.to_account_info(),
+                &speed_up_mint,
+                recipe.speed_up_amount,
+            )?;
+            create_escrow_account(
+                &ctx.accounts.user,
+                &ctx.accounts.recipe_vault,
+                escrow_token,
+                user_mint,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+            transfer_ingredient_to_escrow(
+                user_token,
+                escrow_token,
+                &ctx.accounts.user,
+                &recipe.speed_up_amount,
+                &ctx.accounts.token_program,
+            )?;
+            msg!("Done speed up craft");
+        }
+
+        create_user_token_account(
+            &ctx.accounts.user,
+            &ctx.accounts.user_skin_token_account,
+            &ctx.accounts.skin_mint.to_account_info(),
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        let signer_bump = ctx.accounts.program_manager.signer_bump;
+        transfer_from_program(
+            &ctx.accounts.program_skin_token_account.to_account_info(),
+            &ctx.accounts.user_skin_token_account,
+            &ctx.accounts.program_signer,
+            1,
+            &ctx.accounts.token_program,
+            &[b"signer", &[signer_bump]],
+        )?;
+        msg!("transfer_skin_to_user");
+
+        stamp_skin_provenance(
+            &ctx.accounts.skin_metadata,
+            &ctx.accounts.program_signer,
+            &ctx.accounts.token_metadata_program,
+            signer_bump,
+            &ctx.accounts.user.key(),
+            Clock::get()?.unix_timestamp,
+            recipe.revision,
+        )?;
+        msg!("Done stamp skin provenance");
+
+        let vault_bump = [ctx.accounts.recipe.vault_bump];
+        let vault = vault_seeds(&recipe_key, &vault_bump);
+        for output in recipe.outputs.iter() {
+            let output_accounts = next_output_accounts(iterator, output.kind)?;
+            deliver_output(
+                output,
+                &output_accounts,
+                &ctx.accounts.user,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.recipe_vault,
+                &vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+        msg!("Done deliver recipe outputs");
+
+        // salvage of the ingredients the job escrowed
+        if recipe.salvage_bps > 0 {
+            write_salvage_record(
+                next_account_info(iterator)?,
+                &ctx.accounts.user,
+                &ctx.accounts.system_program,
+                ctx.program_id,
+                SalvageRecord {
+                    recipe: recipe_key,
+                    skin_mint: ctx.accounts.skin_mint.key(),
+                    mints: craft_job.mints.clone(),
+                    amounts: salvage_amounts(&craft_job.amounts, recipe.salvage_bps),
+                    bump: 0,
+                },
+            )?;
+            msg!("Done write salvage record");
+        }
+
+        emit!(CraftEvent {
+            user: ctx.accounts.user.key(),
+            recipe: recipe.key(),
+            skin_mint: ctx.accounts.skin_mint.key(),
+            roll: None,
+        });
+        ctx.accounts.recipe.close_craft();
+
+        Ok(())
+    }
+
+    /*
+      commit step of a Random mode recipe with reveal_delay
+        escrow ingredients + pay craft fee, allowlist + PlayerProfile as in craft_skin
+        escrow is held by the PendingCraft, not the recipe vault, until finish / refund / cancel
+        open PendingCraft recording commit slot + randomness account, roll happens in finish_craft
+        VRF recipes commit to the unfulfilled result account of ["randomness", recipe, user, request]
+
+      remaining_accounts
+        gate accounts (gated recipes)
+        ingredient triplets, then input skin accounts (upgrade recipes)
+    */
+    pub fn begin_craft<'info>(
+        ctx: Context<'_, '_, '_, 'info, BeginCraft<'info>>,
+        request: u64,   // VRF request nonce of user, ignored for slot hashes recipes
+        allowance: u32, // crafts allowed by allowlist leaf, ignored without allowlist
+        proof: Vec<[u8; 32]>, // merkle proof of (user, allowance) leaf
+    ) -> Result<()> {
+        // accounts of older layouts are migrated before any new craft
+        check_account_version(ctx.accounts.recipe.version, Recipe::VERSION)?;
+        check_account_version(ctx.accounts.program_manager.version, Manager::VERSION)?;
+        let recipe = &ctx.accounts.recipe;
+        if recipe.mode != RecipeMode::Random || recipe.reveal_delay == 0 {
+            return Err(ErrorCode::RecipeModeInvalid.into());
+        }
+
+        if let Some(root) = &recipe.allowlist_root {
+            let crafted = recipe_craft_count(
+                &ctx.accounts.player_profile,
+                &ctx.accounts.user.key(),
+                &recipe.key(),
+                ctx.program_id,
+            )?;
+            verify_allowlist(
+                root,
+                &ctx.accounts.user.key(),
+                allowance,
+                &proof,
+                crafted,
+                1,
+            )?;
+            msg!("Done verify allowlist");
+        }
+        verify_randomness_request(
+            &ctx.accounts.randomness,
+            recipe,
+            &recipe.key(),
+            &ctx.accounts.user.key(),
+            request,
+        )?;
+
+        let iterator = &mut ctx.remaining_accounts.iter();
+        if let Some(gate) = &recipe.gate {
+            verify_recipe_gate(gate, iterator, &ctx.accounts.user.to_account_info())?;
+            msg!("Done verify recipe gate");
+        }
+        let input_skins = escrow_ingredients(
+            recipe,
+            1,
+            iterator,
+            &ctx.accounts.user,
+            &ctx.accounts.pending_craft.to_account_info(),
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done user ingredient validations & transfer to escrows");
+
+        verify_fee_recipients(
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.admin,
+            &ctx.accounts.program_manager,
+        )?;
+        pay_craft_fee(
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            recipe.craft_fee,
+            recipe.royalty_bps,
+        )?;
+        msg!("Done pay craft fee");
+
+        // allowance is spent at commit, refunds and cancels do not give it back
+        record_craft(
+            &ctx.accounts.player_profile,
+            &ctx.accounts.user,
+            &ctx.accounts.system_program,
+            ctx.program_id,
+            &ctx.accounts.recipe.key(),
+            1,
+            Clock::get()?.unix_timestamp,
+        )?;
+        msg!("Done record craft");
+
+        let commit_slot = Clock::get()?.slot;
+        let pending_craft = &mut ctx.accounts.pending_craft;
+        pending_craft.user = ctx.accounts.user.key();
+        pending_craft.recipe = ctx.accounts.recipe.key();
+        pending_craft.commit_slot = commit_slot;
+        pending_craft.reveal_slot = commit_slot + ctx.accounts.recipe.reveal_delay;
+        pending_craft.mints = ctx.accounts.recipe.mints.clone();
+        pending_craft.amounts = ctx.accounts.recipe.amounts.clone();
+        pending_craft.bump = *ctx.bumps.get("pending_craft").unwrap();
+        pending_craft.randomness = ctx.accounts.randomness.key();
+        pending_craft.input_skins = input_skins;
+        ctx.accounts.recipe.open_craft();
+        msg!(
+            "committed craft, reveal at slot {}",
+            pending_craft.reveal_slot
+        );
+
+        Ok(())
+    }
+
+    /*
+      reveal step of a commit-reveal craft, callable by anyone once reveal_slot has passed
+        roll from slot hash of reveal_slot (or VRF result recorded by begin_craft), deliver rolled entry
+        escrow of the PendingCraft moves to the recipe vault
+        close PendingCraft, rent back to user
+
+      remaining_accounts
+        triplets of [pending escrow token, mint, vault token] per escrowed ingredient, then input skin
+        accounts of the rolled loot_table entry only
+        accounts of every fixed Recipe output
+    */
+    pub fn finish_craft<'info>(
+        ctx: Context<'_, '_, '_, 'info, FinishCraft<'info>>,
+        entry: u8, // loot entry the caller expects, checked against the roll
+    ) -> Result<()> {
+        let pending_craft = &ctx.accounts.pending_craft;
+        if Clock::get()?.slot <= pending_craft.reveal_slot {
+            return Err(ErrorCode::CraftNotReady.into());
+        }
+
+        let randomness = read_reveal_randomness(
+            &ctx.accounts.randomness,
+            &ctx.accounts.recipe,
+            pending_craft,
+        )?;
+        let roll = roll_loot(&randomness, &pending_craft.user, &ctx.accounts.recipe.key());
+        let rolled = select_loot_entry(&ctx.accounts.recipe.loot_table, roll)?;
+        if rolled != entry as usize {
+            return Err(ErrorCode::LootEntryMismatch.into());
+        }
+        msg!("rolled loot entry {}", rolled);
+
+        let iterator = &mut ctx.remaining_accounts.iter();
+        let recipe_key = ctx.accounts.recipe.key();
+        let pending_bump = [pending_craft.bump];
+        let pending_seeds =
+            craft_seeds(b"pending", &recipe_key, &pending_craft.user, &pending_bump);
+        let input_skin_amounts = vec![1; pending_craft.input_skins.len()];
+        for (mints, amounts) in [
+            (&pending_craft.mints, &pending_craft.amounts),
+            (&pending_craft.input_skins, &input_skin_amounts),
+        ] {
+            settle_escrow(
+                mints,
+                amounts,
+                iterator,
+                &pending_craft.to_account_info(),
+                &pending_seeds,
+                &ctx.accounts.payer,
+                &ctx.accounts.recipe_vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+        msg!("Done settle pending escrow");
+
+        let vault_bump = [ctx.accounts.recipe.vault_bump];
+        let vault = vault_seeds(&recipe_key, &vault_bump);
+        let loot = &ctx.accounts.recipe.loot_table[rolled];
+        let output_accounts = next_output_accounts(iterator, loot.output.kind)?;
+        let skin_mint = output_accounts.mint.key();
+        deliver_output(
+            &loot.output,
+            &output_accounts,
+            &ctx.accounts.payer,
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_vault,
+            &vault,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done deliver loot entry");
+
+        for output in ctx.accounts.recipe.outputs.iter() {
+            let output_accounts = next_output_accounts(iterator, output.kind)?;
+            deliver_output(
+                output,
+                &output_accounts,
+                &ctx.accounts.payer,
+                &ctx.accounts.user,
+                &ctx.accounts.recipe_vault,
+                &vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+        }
+        msg!("Done deliver recipe outputs");
+
+        emit!(CraftEvent {
+            user: pending_craft.user,
+            recipe: ctx.accounts.recipe.key(),
+            skin_mint,
+            roll: Some(LootRoll {
+                roll,
+                entry: rolled as u8,
+            }),
+        });
+        ctx.accounts.recipe.close_craft();
+
+        Ok(())
+    }
+
+    /*
+      refund a commit-reveal craft that was never finished
+        allowed once reveal_timeout slots have passed after reveal_slot
+        return escrowed ingredients minus Recipe cancel_penalty_bps, close PendingCraft (craft fee is kept)
+        escrowed input skins are returned in full
+        roll is public after reveal_slot, the penalty is the cost of skipping a bad roll
+
+      remaining_accounts
+        [pending escrow token, mint, user token, vault token] per escrowed ingredient
+        pairs of [pending escrow skin token, user skin token] per escrowed input skin
+    */
+    pub fn refund_craft<'info>(ctx: Context<'_, '_, '_, 'info, RefundCraft<'info>>) -> Result<()> {
+        let pending_craft = &ctx.accounts.pending_craft;
+        let expires = pending_craft.reveal_slot + ctx.accounts.recipe.reveal_timeout;
+        if Clock::get()?.slot <= expires {
+            return Err(ErrorCode::CraftNotExpired.into());
+        }
+
+        let iterator = &mut ctx.remaining_accounts.iter();
+        let recipe_key = ctx.accounts.recipe.key();
+        let pending_bump = [pending_craft.bump];
+        let pending_seeds =
+            craft_seeds(b"pending", &recipe_key, &pending_craft.user, &pending_bump);
+        return_escrow(
+            &pending_craft.mints,
+            &pending_craft.amounts,
+            ctx.accounts.recipe.cancel_penalty_bps,
+            iterator,
+            &pending_craft.to_account_info(),
+            &pending_seeds,
+            &ctx.accounts.user,
+            &ctx.accounts.payer,
+            &ctx.accounts.recipe_vault,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done return ingredients");
+
+        return_input_skins(
+            &pending_craft.input_skins,
+            iterator,
+            &pending_craft.to_account_info(),
+            &pending_seeds,
+            &ctx.accounts.user,
+            &ctx.accounts.token_program,
+        )?;
+        msg!("Done return input skins");
+        ctx.accounts.recipe.close_craft();
+
+        Ok(())
+    }
+
+    /*
+      cancel a timed craft (CraftJob) or unrevealed commit-reveal craft (PendingCraft)
+        return escrowed ingredients minus Recipe cancel_penalty_bps, escrowed input skins in full
+        close pending account, rent back to user
+        skin of a CraftJob goes back to the holder craft_skin took it from, not the recipe vault
+
+      remaining_accounts
+        [craft escrow token, mint, user token, vault token] per escrowed ingredient
+        pairs of [craft escrow skin token, user skin token] per escrowed input skin
+        CraftJob only: [skin mint, program skin token, skin owner, owner skin token]
+    */
+    pub fn cancel_craft<'info>(ctx: Context<'_, '_, '_, 'info, CancelCraft<'info>>) -> Result<()> {
+        let craft = read_cancelable_craft(
+            &ctx.accounts.pending_craft,
+            &ctx.accounts.user.key(),
+            &ctx.accounts.recipe.key(),
+            ctx.program_id,
+        )?;
+
+        let iterator = &mut ctx.remaining_accounts.iter();
+        let signer_bump = ctx.accounts.program_manager.signer_bump;
+        let recipe_key = ctx.accounts.recipe.key();
+        let user_key = ctx.accounts.user.key();
+        let craft_bump = [craft.bump];
+        let escrow_seeds = craft_seeds(craft.seed_prefix, &recipe_key, &user_key, &craft_bump);
+        return_escrow(
+            &craft.mints,
+            &craft.amounts,
+            ctx.accounts.recipe.cancel_penalty_bps,
+            iterator,
+            &ctx.accounts.pending_craft,
+            &escrow_seeds,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_vault,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done return ingredients");
+
+        return_input_skins(
+            &craft.input_skins,
+            iterator,
+            &ctx.accounts.pending_craft,
+            &escrow_seeds,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        msg!("Done return input skins");
+
+        if let Some((skin_mint, skin_owner)) = craft.job_skin {
+            return_job_skin(
+                &skin_mint,
+                &skin_owner,
+                iterator,
+                &ctx.accounts.user,
+                &ctx.accounts.program_signer,
+                signer_bump,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+            msg!("Done return skin to skin owner");
+        }
+
+        close_program_account(
+            &ctx.accounts.pending_craft,
+            &ctx.accounts.user.to_account_info(),
+        )?;
+        ctx.accounts.recipe.close_craft();
+        msg!("Done close pending craft");
+
+        Ok(())
+    }
+
+    /*
+      salvage a skin back into ingredients
+        validate skin is verified in Recipe collection
+        skin returns to the recipe vault ATA
+        pay back the salvage recorded when the skin was crafted from the recipe vault, close the record
+
+      remaining_accounts
+        pairs of [vault token, user token] per salvage record ingredient
+    */
+    pub fn uncraft_skin<'info>(ctx: Context<'_, '_, '_, 'info, UncraftSkin<'info>>) -> Result<()> {
+        let recipe = &ctx.accounts.recipe;
+        if recipe.salvage_bps == 0 {
+            return Err(ErrorCode::UncraftDisabled.into());
+        }
+
+        // validate skin held by user
+        verify_skin(
+            &ctx.accounts.user_skin_token_account,
+            &ctx.accounts.skin_mint,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.skin_metadata,
+            &ctx.accounts.user,
+            None,
+        )?;
+        msg!("Done verify skin");
+
+        // validate collection
+        let skin_metadata_account = &mut Metadata::from_account_info(&ctx.accounts.skin_metadata)?;
+        let collection_metadata_account =
+            &mut Metadata::from_account_info(&ctx.accounts.recipe_metadata)?;
+        assert_collection_verify_is_valid(
+            skin_metadata_account,
+            collection_metadata_account,
+            &ctx.accounts.recipe_mint.to_account_info(),
+            &ctx.accounts.recipe_master_edition.to_account_info(),
+        )?;
+        msg!("Done verify collection");
+
+        // skin back to the recipe vault, withdraw_vault reaches it
+        create_escrow_account(
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_vault,
+            &ctx.accounts.vault_skin_token_account,
+            &ctx.accounts.skin_mint.to_account_info(),
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        transfer_ingredient_to_escrow(
+            &ctx.accounts.user_skin_token_account.to_account_info(),
+            &ctx.accounts.vault_skin_token_account,
+            &ctx.accounts.user,
+            &1,
+            &ctx.accounts.token_program,
+        )?;
+        msg!("transfer_skin_to_program");
+
+        // amounts of the craft, not current Recipe amounts, the rest stays in the vault
+        let recipe_key = recipe.key();
+        let vault_bump = [ctx.accounts.recipe.vault_bump];
+        pay_salvage(
+            &ctx.accounts.salvage_record,
+            &mut ctx.remaining_accounts.iter(),
+            &ctx.accounts.recipe_vault,
+            &vault_seeds(&recipe_key, &vault_bump),
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.token_program,
+        )?;
+        msg!("Done salvage ingredients");
+
+        Ok(())
+    }
+
+    /*
+        program admin moves amount of a token out of a program_signer ATA
+        ingredients escrowed before recipe vaults and craft job skins, recipe vaults use withdraw_vault
+    */
+    pub fn withdraw_escrow(ctx: Context<WithdrawEscrow>, amount: u64) -> Result<()> {
+        if amount > ctx.accounts.escrow_token_account.amount {
+            return Err(ErrorCode::NotEnoughToken.into());
+        }
+        transfer_from_program(
+            &ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &ctx.accounts.program_signer,
+            amount,
+            &ctx.accounts.token_program,
+            &[b"signer", &[ctx.accounts.program_manager.signer_bump]],
+        )?;
+        msg!("Done withdraw escrow");
+        Ok(())
+    }
+
+    /*
+        Recipe NFT holder moves amount of mint out of the vault of its Recipe or LargeRecipe
+        unused skin output stock is taken back this way
+        escrow of open crafts is held by their PendingCraft / CraftJob, out of reach here
+        bumps are read from the recipe, a closed one is created again to empty its vault
+    */
+    pub fn withdraw_vault(ctx: Context<WithdrawVault>, amount: u64) -> Result<()> {
+        verify_recipe_nft(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+            &ctx.accounts.owner,
+        )?;
+        let recipe_mint = ctx.accounts.recipe_mint.key();
+        let recipe_key = ctx.accounts.recipe.key();
+        let (prefix, recipe_bump, vault_bump) = read_vault_recipe(&ctx.accounts.recipe)?;
+        assert_stored_pda(
+            &ctx.accounts.recipe,
+            ctx.program_id,
+            &[prefix, recipe_mint.as_ref()],
+            recipe_bump,
+        )?;
+        assert_stored_pda(
+            &ctx.accounts.recipe_vault,
+            ctx.program_id,
+            &[b"vault", recipe_key.as_ref()],
+            vault_bump,
+        )?;
+
+        if amount > ctx.accounts.vault_token_account.amount {
+            return Err(ErrorCode::NotEnoughToken.into());
+        }
+        let vault_bump = [vault_bump];
+        transfer_from_program(
+            &ctx.accounts.vault_token_account.to_account_info(),
+            &ctx.accounts.destination.to_account_info(),
+            &ctx.accounts.recipe_vault,
+            amount,
+            &ctx.accounts.token_program,
+            &vault_seeds(&recipe_key, &vault_bump),
+        )?;
+        msg!("Done withdraw vault");
+        Ok(())
+    }
+
+    /*
+      rewrite a Recipe / the Manager in the current layout, anyone can pay for it
+        version 0 => account created before versioning (Recipe of mints + amounts, Manager of admin),
+          bumps are found again from the PDA seeds and stored, fields added since read as zero
+          (no listing name, Uncategorized, crafts opened before are not counted)
+      new crafts + update_recipe refuse older versions with AccountNotMigrated,
+      open crafts (finish / claim / cancel / refund) and uncraft_skin do not
+    */
+    pub fn migrate_recipe(ctx: Context<MigrateRecipe>) -> Result<()> {
+        let bump = *ctx.bumps.get("recipe").unwrap();
+        let (_, vault_bump) = Pubkey::find_program_address(
+            &[b"vault", ctx.accounts.recipe.key().as_ref()],
+            ctx.program_id,
+        );
+        migrate_account(
+            &ctx.accounts.recipe,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Recipe::LEN,
+            |recipe: &mut Recipe| {
+                match recipe.version {
+                    0 => {
+                        recipe.bump = bump;
+                        recipe.vault_bump = vault_bump;
+                        recipe.version = Recipe::VERSION;
+                    }
+                    Recipe::VERSION => msg!("Recipe is up to date"),
+                    _ => return Err(ErrorCode::AccountVersionUnknown.into()),
+                }
+                Ok(())
+            },
+        )?;
+        msg!("Done migrate recipe");
+        Ok(())
+    }
+
+    pub fn migrate_manager(ctx: Context<MigrateManager>) -> Result<()> {
+        let bump = *ctx.bumps.get("program_manager").unwrap();
+        let (_, signer_bump) = Pubkey::find_program_address(&[b"signer"], ctx.program_id);
+        migrate_account(
+            &ctx.accounts.program_manager,
+            &ctx.accounts.payer,
+            &ctx.accounts.system_program,
+            Manager::LEN,
+            |manager: &mut Manager| {
+                match manager.version {
+                    0 => {
+                        manager.bump = bump;
+                        manager.signer_bump = signer_bump;
+                        manager.version = Manager::VERSION;
+                    }
+                    Manager::VERSION => msg!("Manager is up to date"),
+                    _ => return Err(ErrorCode::AccountVersionUnknown.into()),
+                }
+                Ok(())
+            },
+        )?;
+        msg!("Done migrate manager");
+        Ok(())
+    }
+
+    /*
+      LargeRecipe => zero-copy recipe read in place, up to 3 ingredients + 4 outputs
+        create_large_recipe opens it empty, append_large_recipe adds ingredients + outputs
+        in chunks, reset_large_recipe empties it for a rewrite
+      a craft needs 3 accounts per ingredient + 2 or 4 per output, append_large_recipe keeps
+      them within LargeRecipe::MAX_CRAFT_ACCOUNTS so craft_large_skin fits one legacy transaction
+    */
+    pub fn create_large_recipe(
+        ctx: Context<CreateLargeRecipe>,
+        craft_fee: u64,
+        royalty_bps: u16,
+    ) -> Result<()> {
+        // rejects royalty_bps over 10000
+        royalty_amount(craft_fee, royalty_bps)?;
+        verify_recipe_nft(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+            &ctx.accounts.owner,
+        )?;
+        msg!("Done verify recipe NFT");
+
+        let mut large_recipe = ctx.accounts.large_recipe.load_init()?;
+        large_recipe.craft_fee = craft_fee;
+        large_recipe.royalty_bps = royalty_bps;
+        large_recipe.revision = 1;
+        large_recipe.bump = *ctx.bumps.get("large_recipe").unwrap();
+        let (_, vault_bump) = Pubkey::find_program_address(
+            &[b"vault", ctx.accounts.large_recipe.key().as_ref()],
+            ctx.program_id,
+        );
+        large_recipe.vault_bump = vault_bump;
+        large_recipe.version = LargeRecipe::VERSION;
+        Ok(())
+    }
+
+    // holder of the Recipe NFT adds ingredients + outputs, revision is bumped
+    pub fn append_large_recipe(
+        ctx: Context<UpdateLargeRecipe>,
+        ingredient_mints: Vec<Pubkey>,
+        ingredient_amounts: Vec<u64>,
+        outputs: Vec<RecipeOutput>,
+    ) -> Result<()> {
+        verify_recipe_nft(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+            &ctx.accounts.owner,
+        )?;
+        msg!("Done verify recipe NFT");
+
+        let mut large_recipe = ctx.accounts.large_recipe.load_mut()?;
+        check_account_version(large_recipe.version, LargeRecipe::VERSION)?;
+        large_recipe.append(&ingredient_mints, &ingredient_amounts, &outputs)?;
+        large_recipe.revision += 1;
+        Ok(())
+    }
+
+    // holder of the Recipe NFT empties the lists + replaces fee settings
+    pub fn reset_large_recipe(
+        ctx: Context<UpdateLargeRecipe>,
+        craft_fee: u64,
+        royalty_bps: u16,
+    ) -> Result<()> {
+        // rejects royalty_bps over 10000
+        royalty_amount(craft_fee, royalty_bps)?;
+        verify_recipe_nft(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+            &ctx.accounts.owner,
+        )?;
+        msg!("Done verify recipe NFT");
+
+        let mut large_recipe = ctx.accounts.large_recipe.load_mut()?;
+        check_account_version(large_recipe.version, LargeRecipe::VERSION)?;
+        large_recipe.clear();
+        large_recipe.craft_fee = craft_fee;
+        large_recipe.royalty_bps = royalty_bps;
+        large_recipe.revision += 1;
+        Ok(())
+    }
+
+    /*
+      craft_skin of a LargeRecipe, ingredients are read in place
+      remaining_accounts
+        ingredient triplets [user token, mint, vault token] in LargeRecipe order
+        accounts of every LargeRecipe output
+    */
+    pub fn craft_large_skin<'info>(
+        ctx: Context<'_, '_, '_, 'info, CraftLargeSkin<'info>>,
+    ) -> Result<()> {
+        let large_recipe = ctx.accounts.large_recipe.load()?;
+        check_account_version(large_recipe.version, LargeRecipe::VERSION)?;
+        check_account_version(ctx.accounts.program_manager.version, Manager::VERSION)?;
+
+        verify_skin(
+            &ctx.accounts.skin_token_account,
+            &ctx.accounts.skin_mint,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.skin_metadata,
+            &ctx.accounts.owner,
+            Some(&ctx.accounts.owner.key()),
+        )?;
+        msg!("Done verify skin");
+
+        verify_recipe_token(
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.recipe_mint,
+            &ctx.accounts.recipe_metadata,
+            &ctx.accounts.recipe_master_edition,
+        )?;
+        msg!("Done verify skin recipe");
+
+        let skin_metadata_account = &mut Metadata::from_account_info(&ctx.accounts.skin_metadata)?;
+        let collection_metadata_account =
+            &mut Metadata::from_account_info(&ctx.accounts.recipe_metadata)?;
+        assert_collection_verify_is_valid(
+            skin_metadata_account,
+            collection_metadata_account,
+            &ctx.accounts.recipe_mint.to_account_info(),
+            &ctx.accounts.recipe_master_edition.to_account_info(),
+        )?;
+
+        let iterator = &mut ctx.remaining_accounts.iter();
+        escrow_ingredient_list(
+            large_recipe.mints(),
+            large_recipe.amounts(),
+            1,
+            iterator,
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_vault,
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        msg!("Done user ingredient validations & transfer to escrows");
+
+        verify_fee_recipients(
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.recipe_token_account,
+            &ctx.accounts.admin,
+            &ctx.accounts.program_manager,
+        )?;
+        pay_craft_fee(
+            &ctx.accounts.user,
+            &ctx.accounts.recipe_holder,
+            &ctx.accounts.admin,
+            &ctx.accounts.system_program,
+            large_recipe.craft_fee,
+            large_recipe.royalty_bps,
+        )?;
+        msg!("Done pay craft fee");
+
+        create_user_token_account(
+            &ctx.accounts.user,
+            &ctx.accounts.user_skin_token_account,
+            &ctx.accounts.skin_mint.to_account_info(),
+            &ctx.accounts.rent_account,
+            &ctx.accounts.token_program,
+            &ctx.accounts.ata_program,
+            &ctx.accounts.system_program,
+        )?;
+        transfer_skin_to_user(
+            &ctx.accounts.skin_token_account.to_account_info(),
+            &ctx.accounts.user_skin_token_account.to_account_info(),
+            &ctx.accounts.owner,
+            &ctx.accounts.token_program,
+        )?;
+        msg!("transfer_skin_to_user");
+
+        let signer_bump = ctx.accounts.program_manager.signer_bump;
+        stamp_skin_provenance(
+            &ctx.accounts.skin_metadata,
+            &ctx.accounts.program_signer,
+            &ctx.accounts.token_metadata_program,
+            signer_bump,
+            &ctx.accounts.user.key(),
+            Clock::get()?.unix_timestamp,
+            large_recipe.revision,
+        )?;
+        msg!("Done stamp skin provenance");
+
+        let recipe_key = ctx.accounts.large_recipe.key();
+        let vault_bump = [large_recipe.vault_bump];
+        let vault = vault_seeds(&recipe_key, &vault_bump);
+        for output in large_recipe.outputs() {
+            let output_accounts = next_output_accounts(iterator, output.kind)?;
+            deliver_output(
+                &output,
+                &output_accounts,
+                &ctx.accounts.user,
+                &ctx.accounts.user.to_account_info(),
+                &ctx.accounts.recipe_vault,
+                &vault,
+                &ctx.accounts.rent_account,
+                &ctx.accounts.token_program,
+                &ctx.accounts.ata_program,
+                &ctx.accounts.system_program,
+            )?;
+            msg!("deliver_output");
+        }
+        msg!("Done deliver recipe outputs");
+
+        emit!(CraftEvent {
+            user: ctx.accounts.user.key(),
+            recipe: ctx.accounts.large_recipe.key(),
+            skin_mint: ctx.accounts.skin_mint.key(),
+            roll: None,
+        });
+
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    // payer for accounts
+    #[account(mut)]
+    pub manager: Signer<'info>,
+
+    // init Manager account
+    #[account(
+    init,
+    payer = manager,
+    seeds = [b"manager"],
+    bump,
+    space = Manager::LEN
+    )]
+    pub program_manager: Account<'info, Manager>,
+    // solana program
+    pub system_program: Program<'info, System>,
+}
+
+// admin is program authority (he who owns the private key)
+// signs for Account creation and manipulation
+#[account]
+pub struct Manager {
+    pub admin: Pubkey,
+    // bumps of ["manager"] and ["signer"], checked without find_program_address
+    pub bump: u8,
+    pub signer_bump: u8,
+    // layout version, 0 => created before versioning, migrate_manager rewrites older layouts
+    pub version: u8,
+}
+
+impl Manager {
+    pub const VERSION: u8 = 1;
+    // allocated with room to grow, accounts created before versioning have the same size
+    pub const LEN: usize = 240;
+}
+
+#[derive(Accounts)]
+pub struct CreateRecipe<'info> {
+    // owner of Recipe NFT
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /**
+      account defining recipe of mints+amounts
+      to craft a skin NFT
+    **/
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump,
+        space = Recipe::LEN
+    )]
+    pub recipe: Account<'info, Recipe>,
+
+    /**
+      required accounts for an NFT
+      should new Recipe NFT to mint
+    **/
+    #[account(mut)]
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    #[account(mut)]
+    pub recipe_metadata: AccountInfo<'info>,
+
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    /**
+      required programs for an NFT
+    **/
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    // creates Metadata account within Token Account
+    #[account(address = mpl_token_metadata::ID)]
+    ///CHECK: verification is run in instruction
+    pub token_metadata_program: AccountInfo<'info>,
+    // creates Token Account of NFT
+    pub token_program: Program<'info, Token>,
+    // creates generic Account
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRecipe<'info> {
+    // holder of Recipe NFT
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseRecipe<'info> {
+    // holder of Recipe NFT, receives Recipe rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+}
+
+/*
+    validate necessary NFT accounts
+      verify_recipe_nft
+    find ingredients from recipe NFT account param
+    validate recipe account
+      seeds
+      init
+      owned by program?
+    create skin with recipe as Collection
+*/
+#[derive(Accounts)]
+pub struct AddSkin<'info> {
+    // owner of Recipe NFT
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /**
+      account defining recipe of mints+amounts
+      to craft a skin NFT
+    **/
+    #[account(
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+
+    /**
+      required accounts for an NFT
+      should be exisiting Recipe NFT
+    **/
+    #[account(mut)]
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    #[account(mut)]
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    /***
+      required accounts for an NFT
+      should be new skin to mint
+    **/
+    #[account(mut)]
+    pub skin_token_account: Account<'info, TokenAccount>,
+    pub skin_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    #[account(mut)]
+    pub skin_metadata: AccountInfo<'info>,
+
+    /**
+      required programs for an NFT
+    **/
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    // creates Metadata account within Token Account
+    #[account(address = mpl_token_metadata::ID)]
+    ///CHECK: verification is run in instruction
+    pub token_metadata_program: AccountInfo<'info>,
+    // creates Token Account of NFT
+    pub token_program: Program<'info, Token>,
+    // creates generic Account
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CraftSkin<'info> {
+    // creator + holder of the skin, need not hold the Recipe NFT
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, seeds = [b"signer"], bump = program_manager.signer_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub program_signer: UncheckedAccount<'info>,
+
+    // program admin receives craft fee minus royalty
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+    #[account(mut)]
+    ///CHECK: validated against program_manager.admin in craft_skin
+    pub admin: UncheckedAccount<'info>,
+    // current holder of Recipe NFT receives royalty share of craft fee
+    #[account(mut)]
+    ///CHECK: validated against recipe_token_account.owner in craft_skin
+    pub recipe_holder: UncheckedAccount<'info>,
+    // crafting statistics of user, created on first craft
+    #[account(mut)]
+    ///CHECK: PDA of ["profile", user], checked in record_craft with stored bump
+    pub player_profile: UncheckedAccount<'info>,
+
+    // recipe accounts, open_crafts counts timed crafts
+    #[account(
+        mut,
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub recipe_token_account: Box<Account<'info, TokenAccount>>,
+    pub recipe_mint: Box<Account<'info, Mint>>,
+    ///CHECK: verification is run in instruction
+    #[account(mut)]
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    // ATA the user owns to receive the skin
+    #[account(mut)]
+    /// CHECK: validated in craft_skin
+    pub user_skin_token_account: UncheckedAccount<'info>,
+    // ATA the program owns the skin to transfer to user
+    #[account(mut)]
+    pub skin_token_account: Account<'info, TokenAccount>,
+    pub skin_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    #[account(mut)]
+    pub skin_metadata: AccountInfo<'info>,
+
+    /**
+      required programs for an NFT
+    **/
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    // creates Metadata account within Token Account
+    #[account(address = mpl_token_metadata::ID)]
+    ///CHECK: verification is run in instruction
+    pub token_metadata_program: AccountInfo<'info>,
+    // creates Token Account of NFT
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    // creates generic Account
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CraftSkinBatch<'info> {
+    // creator + holder of the skins delivered, need not hold the Recipe NFT
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, seeds = [b"signer"], bump = program_manager.signer_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub program_signer: UncheckedAccount<'info>,
+
+    // program admin receives craft fee minus royalty
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+    #[account(mut)]
+    ///CHECK: validated against program_manager.admin in craft_skin_batch
+    pub admin: UncheckedAccount<'info>,
+    // current holder of Recipe NFT receives royalty share of craft fee
+    #[account(mut)]
+    ///CHECK: validated against recipe_token_account.owner in craft_skin_batch
+    pub recipe_holder: UncheckedAccount<'info>,
+    // crafting statistics of user, created on first craft
+    #[account(mut)]
+    ///CHECK: PDA of ["profile", user], checked in record_craft with stored bump
+    pub player_profile: UncheckedAccount<'info>,
+
+    // recipe accounts
+    #[account(
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub recipe_token_account: Box<Account<'info, TokenAccount>>,
+    pub recipe_mint: Box<Account<'info, Mint>>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    pub rent_account: Sysvar<'info, Rent>,
+    #[account(address = mpl_token_metadata::ID)]
+    ///CHECK: verification is run in instruction
+    pub token_metadata_program: AccountInfo<'info>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CraftRandomSkin<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, seeds = [b"signer"], bump = program_manager.signer_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub program_signer: UncheckedAccount<'info>,
+
+    // program admin receives craft fee minus royalty
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+    #[account(mut)]
+    ///CHECK: validated against program_manager.admin in craft_random_skin
+    pub admin: UncheckedAccount<'info>,
+    // current holder of Recipe NFT receives royalty share of craft fee
+    #[account(mut)]
+    ///CHECK: validated against recipe_token_account.owner in craft_random_skin
+    pub recipe_holder: UncheckedAccount<'info>,
+    // crafting statistics of user, created on first craft
+    #[account(mut)]
+    ///CHECK: PDA of ["profile", user], checked in record_craft with stored bump
+    pub player_profile: UncheckedAccount<'info>,
+
+    // recipe accounts
+    #[account(
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Account<'info, Recipe>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+    // token account holding the Recipe NFT
+    #[account(
+        constraint = recipe_token_account.mint == recipe_mint.key() @ ErrorCode::TokenMintInvalid,
+        constraint = recipe_token_account.amount == 1 @ ErrorCode::TokenAmountInvalid
+    )]
+    pub recipe_token_account: Box<Account<'info, TokenAccount>>,
+    pub recipe_mint: Box<Account<'info, Mint>>,
+
+    // slot hashes sysvar
+    ///CHECK: validated in read_randomness
+    pub randomness: UncheckedAccount<'info>,
+
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BeginCraft<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    // program admin receives craft fee minus royalty
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+    #[account(mut)]
+    ///CHECK: validated against program_manager.admin in begin_craft
+    pub admin: UncheckedAccount<'info>,
+    // current holder of Recipe NFT receives royalty share of craft fee
+    #[account(mut)]
+    ///CHECK: validated against recipe_token_account.owner in begin_craft
+    pub recipe_holder: UncheckedAccount<'info>,
+    // crafting statistics of user, created on first craft
+    #[account(mut)]
+    ///CHECK: PDA of ["profile", user], checked in record_craft with stored bump
+    pub player_profile: UncheckedAccount<'info>,
+
+    // recipe accounts, open_crafts counts the PendingCraft
+    #[account(
+        mut,
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Box<Account<'info, Recipe>>,
+    // token account holding the Recipe NFT
+    #[account(
+        constraint = recipe_token_account.mint == recipe_mint.key() @ ErrorCode::TokenMintInvalid,
+        constraint = recipe_token_account.amount == 1 @ ErrorCode::TokenAmountInvalid
+    )]
+    pub recipe_token_account: Box<Account<'info, TokenAccount>>,
+    pub recipe_mint: Box<Account<'info, Mint>>,
+
+    // one pending craft per user per recipe, holds the escrow in its ATAs
+    #[account(
+        init,
+        payer = user,
+        seeds = [b"pending", recipe.key().as_ref(), user.key().as_ref()],
+        bump,
+        space = PendingCraft::LEN
+    )]
+    pub pending_craft: Account<'info, PendingCraft>,
+
+    // slot hashes sysvar, or VRF result account of the request
+    ///CHECK: validated in verify_randomness_request
+    pub randomness: UncheckedAccount<'info>,
+
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FinishCraft<'info> {
+    // anyone can finish a revealed craft, pays rent of new user token accounts
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // crafter, receives outputs + PendingCraft rent
+    #[account(mut)]
+    ///CHECK: validated by pending_craft has_one
+    pub user: UncheckedAccount<'info>,
+
+    // open_crafts is released as the craft closes
+    #[account(mut)]
+    pub recipe: Box<Account<'info, Recipe>>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = user,
+        has_one = recipe,
+        close = user,
+        seeds = [b"pending", recipe.key().as_ref(), user.key().as_ref()],
+        bump = pending_craft.bump
+    )]
+    pub pending_craft: Account<'info, PendingCraft>,
+
+    // account recorded in pending_craft by begin_craft
+    ///CHECK: validated in read_reveal_randomness
+    pub randomness: UncheckedAccount<'info>,
+
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RefundCraft<'info> {
+    // anyone can refund an expired craft, pays rent of new vault token accounts
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // crafter, receives ingredients + PendingCraft rent
+    #[account(mut)]
+    ///CHECK: validated by pending_craft has_one
+    pub user: UncheckedAccount<'info>,
+
+    // open_crafts is released as the craft closes
+    #[account(mut)]
+    pub recipe: Box<Account<'info, Recipe>>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = user,
+        has_one = recipe,
+        close = user,
+        seeds = [b"pending", recipe.key().as_ref(), user.key().as_ref()],
+        bump = pending_craft.bump
+    )]
+    pub pending_craft: Account<'info, PendingCraft>,
+
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimCraft<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, seeds = [b"signer"], bump = program_manager.signer_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub program_signer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+
+    // open_crafts is released as the craft closes
+    #[account(mut)]
+    pub recipe: Box<Account<'info, Recipe>>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+
+    // opened by craft_skin for timed recipes
+    #[account(
+        mut,
+        has_one = user,
+        has_one = recipe,
+        has_one = skin_mint,
+        close = user,
+        seeds = [b"job", recipe.key().as_ref(), user.key().as_ref()],
+        bump = craft_job.bump
+    )]
+    pub craft_job: Account<'info, CraftJob>,
+
+    // skin held by program until claim
     pub skin_mint: Account<'info, Mint>,
-    ///CHECK: verification is run in instruction
+    #[account(
+        mut,
+        associated_token::mint = skin_mint,
+        associated_token::authority = program_signer
+    )]
+    pub program_skin_token_account: Box<Account<'info, TokenAccount>>,
+    // ATA the user owns to receive the skin
     #[account(mut)]
+    /// CHECK: validated in claim_craft
+    pub user_skin_token_account: UncheckedAccount<'info>,
+    // provenance is stamped when program_signer is update authority
+    #[account(
+        mut,
+        seeds = [b"metadata", mpl_token_metadata::ID.as_ref(), skin_mint.key().as_ref()],
+        seeds::program = mpl_token_metadata::ID,
+        bump
+    )]
+    ///CHECK: metadata PDA of skin_mint
     pub skin_metadata: AccountInfo<'info>,
 
-    /**
-      required programs for an NFT
-    **/
     // holds SOL to pay for all Account rent
     pub rent_account: Sysvar<'info, Rent>,
-    // creates Metadata account within Token Account
     #[account(address = mpl_token_metadata::ID)]
     ///CHECK: verification is run in instruction
     pub token_metadata_program: AccountInfo<'info>,
-    // creates Token Account of NFT
     pub token_program: Program<'info, Token>,
-    // creates generic Account
+    pub ata_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct CraftSkin<'info> {
-    // owner of Recipe NFT
+pub struct CancelCraft<'info> {
+    // crafter, receives ingredients + pending account rent
     #[account(mut)]
-    pub owner: Signer<'info>,
+    pub user: Signer<'info>,
+
+    #[account(seeds = [b"signer"], bump = program_manager.signer_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub program_signer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+
+    // open_crafts is released as the craft closes
+    #[account(mut)]
+    pub recipe: Box<Account<'info, Recipe>>,
+    // recipe vault, keeps the cancel penalty
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+
+    // CraftJob or PendingCraft of user for recipe, holds the escrow in its ATAs
+    #[account(mut)]
+    ///CHECK: validated in read_cancelable_craft
+    pub pending_craft: UncheckedAccount<'info>,
+
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
 
+#[derive(Accounts)]
+pub struct UncraftSkin<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
-    #[account(mut,seeds = [b"signer"], bump)]
+    // recipe accounts
+    #[account(
+        seeds = [b"recipe", recipe_mint.key().as_ref()],
+        bump = recipe.bump
+    )]
+    pub recipe: Box<Account<'info, Recipe>>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(seeds = [b"vault", recipe.key().as_ref()], bump = recipe.vault_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
+    pub recipe_mint: Box<Account<'info, Mint>>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    // skin to salvage, held by user
+    #[account(mut)]
+    pub user_skin_token_account: Box<Account<'info, TokenAccount>>,
+    pub skin_mint: Box<Account<'info, Mint>>,
+    ///CHECK: verification is run in instruction
+    pub skin_metadata: AccountInfo<'info>,
+    // recipe vault ATA taking the skin back
+    #[account(mut)]
+    /// CHECK: validated in uncraft_skin
+    pub vault_skin_token_account: UncheckedAccount<'info>,
+    // salvage owed for the skin, written when it was crafted
+    #[account(
+        mut,
+        has_one = recipe,
+        has_one = skin_mint,
+        close = user,
+        seeds = [b"salvage", recipe.key().as_ref(), skin_mint.key().as_ref()],
+        bump = salvage_record.bump
+    )]
+    pub salvage_record: Box<Account<'info, SalvageRecord>>,
+
+    // holds SOL to pay for all Account rent
+    pub rent_account: Sysvar<'info, Rent>,
+    pub token_program: Program<'info, Token>,
+    pub ata_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawEscrow<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"manager"],
+        bump = program_manager.bump,
+        has_one = admin @ ErrorCode::AdminInvalid
+    )]
+    pub program_manager: Account<'info, Manager>,
+
+    #[account(seeds = [b"signer"], bump = program_manager.signer_bump)]
     ///CHECK: Is simply a pda - seeds will be from program
     pub program_signer: UncheckedAccount<'info>,
 
-    // recipe accounts
+    pub mint: Account<'info, Mint>,
+    // program_signer ATA holding escrowed ingredients of mint
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_signer
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVault<'info> {
+    // holder of Recipe NFT
+    pub owner: Signer<'info>,
+
+    // Recipe or LargeRecipe of recipe_mint
+    ///CHECK: derivation is checked in withdraw_vault
+    pub recipe: UncheckedAccount<'info>,
+    ///CHECK: derivation is checked in withdraw_vault
+    pub recipe_vault: UncheckedAccount<'info>,
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    pub mint: Account<'info, Mint>,
+    // recipe vault ATA holding mint
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = recipe_vault
+    )]
+    pub vault_token_account: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = mint)]
+    pub destination: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateRecipe<'info> {
+    // pays rent of the grown account
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    // older layouts do not deserialize as Recipe, read by migrate_account
+    // bump is found here, version 0 Recipes did not store it
     #[account(
+        mut,
+        owner = crate::ID,
         seeds = [b"recipe", recipe_mint.key().as_ref()],
         bump
     )]
-    pub recipe: Account<'info, Recipe>,
+    ///CHECK: discriminator is checked by migrate_account
+    pub recipe: UncheckedAccount<'info>,
+
+    ///CHECK: seed of the Recipe PDA only
+    pub recipe_mint: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateManager<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, seeds = [b"manager"], bump, owner = crate::ID)]
+    ///CHECK: discriminator is checked by migrate_account
+    pub program_manager: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateLargeRecipe<'info> {
+    // holder of Recipe NFT
     #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        seeds = [b"large_recipe", recipe_mint.key().as_ref()],
+        bump,
+        space = LargeRecipe::LEN
+    )]
+    pub large_recipe: AccountLoader<'info, LargeRecipe>,
+
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateLargeRecipe<'info> {
+    // holder of Recipe NFT
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"large_recipe", recipe_mint.key().as_ref()],
+        bump = large_recipe.load()?.bump
+    )]
+    pub large_recipe: AccountLoader<'info, LargeRecipe>,
+
+    pub recipe_token_account: Account<'info, TokenAccount>,
+    pub recipe_mint: Account<'info, Mint>,
+    ///CHECK: verification is run in instruction
+    pub recipe_metadata: AccountInfo<'info>,
+    ///CHECK: verification is run in instruction
+    pub recipe_master_edition: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CraftLargeSkin<'info> {
+    // creator + holder of the skin, need not hold the Recipe NFT
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(mut, seeds = [b"signer"], bump = program_manager.signer_bump)]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub program_signer: UncheckedAccount<'info>,
+
+    #[account(seeds = [b"manager"], bump = program_manager.bump)]
+    pub program_manager: Account<'info, Manager>,
+    #[account(mut)]
+    ///CHECK: validated against program_manager.admin in craft_large_skin
+    pub admin: UncheckedAccount<'info>,
+    #[account(mut)]
+    ///CHECK: validated against recipe_token_account.owner in craft_large_skin
+    pub recipe_holder: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"large_recipe", recipe_mint.key().as_ref()],
+        bump = large_recipe.load()?.bump
+    )]
+    pub large_recipe: AccountLoader<'info, LargeRecipe>,
+    // recipe vault, stocks skin outputs + spent ingredients, mint authority of fungible outputs
+    #[account(
+        seeds = [b"vault", large_recipe.key().as_ref()],
+        bump = large_recipe.load()?.vault_bump
+    )]
+    ///CHECK: Is simply a pda - seeds will be from program
+    pub recipe_vault: UncheckedAccount<'info>,
     pub recipe_token_account: Box<Account<'info, TokenAccount>>,
     pub recipe_mint: Box<Account<'info, Mint>>,
     ///CHECK: verification is run in instruction
-    #[account(mut)]
     pub recipe_metadata: AccountInfo<'info>,
     ///CHECK: verification is run in instruction
     pub recipe_master_edition: AccountInfo<'info>,
 
-    // ATA the user owns to receive the skin
     #[account(mut)]
-    /// CHECK: validated in craft_skin
+    /// CHECK: validated in craft_large_skin
     pub user_skin_token_account: UncheckedAccount<'info>,
-    // ATA the program owns the skin to transfer to user
     #[account(mut)]
-    pub skin_token_account: Account<'info, TokenAccount>,
-    pub skin_mint: Account<'info, Mint>,
+    pub skin_token_account: Box<Account<'info, TokenAccount>>,
+    pub skin_mint: Box<Account<'info, Mint>>,
     ///CHECK: verification is run in instruction
     #[account(mut)]
     pub skin_metadata: AccountInfo<'info>,
 
-    /**
-      required programs for an NFT
-    **/
-    // holds SOL to pay for all Account rent
     pub rent_account: Sysvar<'info, Rent>,
-    // creates Metadata account within Token Account
     #[account(address = mpl_token_metadata::ID)]
     ///CHECK: verification is run in instruction
     pub token_metadata_program: AccountInfo<'info>,
-    // creates Token Account of NFT
     pub token_program: Program<'info, Token>,
     pub ata_program: Program<'info, AssociatedToken>,
-    // creates generic Account
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,141 @@
+/*
+    craft rules on plain account contents, no AccountInfo, sysvars or CPIs
+    the program reads accounts then calls these, hosts call them from simulate_craft
+    with wallet holdings fetched over RPC, both get the same ErrorCode
+    uses ErrorCode / Recipe / RecipeMode of utils, Pubkey and keccak, none of them need the runtime
+*/
+use anchor_lang::prelude::Pubkey;
+use solana_program::keccak;
+
+use crate::utils::{ErrorCode, Recipe, RecipeMode};
+
+// fields of an SPL token account read by the craft rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TokenState {
+    pub mint: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+}
+
+// collection of an NFT's metadata, as in mpl_token_metadata::state::Collection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NftCollection {
+    pub key: Pubkey,
+    pub verified: bool,
+}
+
+// user token holds at least the ingredient amount of mint, only that amount is escrowed
+pub fn check_ingredient(token: &TokenState, mint: &Pubkey, amount: u64) -> Result<(), ErrorCode> {
+    if token.mint != *mint {
+        return Err(ErrorCode::TokenMintInvalid);
+    }
+    if token.amount < amount {
+        return Err(ErrorCode::NotEnoughToken);
+    }
+    Ok(())
+}
+
+// token of an NFT (Recipe NFT, skin) holds exactly the single token of mint
+pub fn check_nft_token(token: &TokenState, mint: &Pubkey) -> Result<(), ErrorCode> {
+    if token.amount != 1 {
+        return Err(ErrorCode::TokenAmountInvalid);
+    }
+    if token.mint != *mint {
+        return Err(ErrorCode::TokenMintInvalid);
+    }
+    Ok(())
+}
+
+// user token holds at least amount of mint
+pub fn check_token_balance(
+    token: &TokenState,
+    user: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<(), ErrorCode> {
+    if token.owner != *user {
+        return Err(ErrorCode::TokenOwnerInvalid);
+    }
+    if token.mint != *mint {
+        return Err(ErrorCode::TokenMintInvalid);
+    }
+    if token.amount < amount {
+        return Err(ErrorCode::NotEnoughToken);
+    }
+    Ok(())
+}
+
+// NFT metadata is verified in collection_mint
+pub fn check_collection(
+    collection: Option<&NftCollection>,
+    collection_mint: &Pubkey,
+) -> Result<(), ErrorCode> {
+    let collection = collection.ok_or(ErrorCode::CollectionUnverified)?;
+    if !collection.verified {
+        return Err(ErrorCode::CollectionUnverified);
+    }
+    if collection.key != *collection_mint {
+        return Err(ErrorCode::CollectionKeyInvalid);
+    }
+    Ok(())
+}
+
+// recipe can be crafted by an instruction supporting mode
+pub fn check_recipe_mode(recipe: &Recipe, mode: RecipeMode) -> Result<(), ErrorCode> {
+    if recipe.mode != mode {
+        return Err(ErrorCode::RecipeModeInvalid);
+    }
+    Ok(())
+}
+
+// account was written in the current layout version, older ones need a migrate_* first
+pub fn check_account_version(version: u8, current: u8) -> Result<(), ErrorCode> {
+    if version < current {
+        return Err(ErrorCode::AccountNotMigrated);
+    }
+    if version > current {
+        return Err(ErrorCode::AccountVersionUnknown);
+    }
+    Ok(())
+}
+
+// listing fields fit the space reserved in Recipe::LEN
+pub fn check_recipe_metadata(name: &str, description: &str) -> Result<(), ErrorCode> {
+    if name.len() > Recipe::MAX_NAME_LEN {
+        return Err(ErrorCode::RecipeNameTooLong);
+    }
+    if description.len() > Recipe::MAX_DESCRIPTION_LEN {
+        return Err(ErrorCode::RecipeDescriptionTooLong);
+    }
+    Ok(())
+}
+
+/*
+    user is on the recipe allowlist with quantity of allowance crafts left
+      leaf = keccak(0x00 || user || allowance as u32 LE)
+      node = keccak(0x01 || min(a, b) || max(a, b))
+*/
+pub fn check_allowlist(
+    root: &[u8; 32],
+    user: &Pubkey,
+    allowance: u32,
+    proof: &[[u8; 32]],
+    crafted: u32,
+    quantity: u32,
+) -> Result<(), ErrorCode> {
+    let leaf = keccak::hashv(&[&[0x00], user.as_ref(), &allowance.to_le_bytes()]).0;
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            keccak::hashv(&[&[0x01], &node, sibling]).0
+        } else {
+            keccak::hashv(&[&[0x01], sibling, &node]).0
+        }
+    });
+    if computed != *root {
+        return Err(ErrorCode::NotAllowlisted);
+    }
+    if crafted.saturating_add(quantity) > allowance {
+        return Err(ErrorCode::AllowanceExceeded);
+    }
+    Ok(())
+}
@@ -1,22 +1,59 @@
 use anchor_lang::context::CpiContext;
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_lang::Discriminator;
 use anchor_spl::associated_token::{create, get_associated_token_address};
 use anchor_spl::associated_token::{AssociatedToken, Create};
-use anchor_spl::token::transfer;
-use anchor_spl::token::{Mint, Token, TokenAccount, Transfer, ID as SPL_TOKEN_ID};
-use mpl_token_metadata::state::{Metadata, PREFIX};
+use anchor_spl::token::{burn, mint_to, transfer};
+use anchor_spl::token::{Burn, Mint, MintTo, Token, TokenAccount, Transfer, ID as SPL_TOKEN_ID};
+use mpl_token_metadata::instruction::update_metadata_accounts_v2;
+use mpl_token_metadata::state::{DataV2, Metadata, MAX_URI_LENGTH, PREFIX};
 use mpl_token_metadata::utils::{
     assert_derivation, assert_edition_valid, assert_initialized, assert_owned_by,
 };
 use solana_program::account_info::AccountInfo;
+use solana_program::keccak;
+use solana_program::program::invoke_signed;
+use solana_program::slot_hashes::MAX_ENTRIES as SLOT_HASHES_MAX_ENTRIES;
+use solana_program::sysvar::slot_hashes;
+use std::slice::Iter;
 
-// validate accounts needed to make Recipe NFT
-pub fn verify_recipe_nft<'info, 'a>(
+use crate::rules::{
+    check_account_version, check_allowlist, check_collection, check_ingredient, check_nft_token,
+    check_recipe_metadata, check_token_balance, NftCollection, TokenState,
+};
+use crate::Manager;
+
+// validate accounts needed to make Recipe NFT, owner holds it and is its verified creator
+pub fn verify_recipe_nft<'info>(
     token_account: &Account<'info, TokenAccount>,
     mint: &Account<'info, Mint>,
     metadata: &AccountInfo<'info>,
     edition: &AccountInfo<'info>,
     owner: &Signer<'info>,
+) -> Result<()> {
+    verify_recipe_token(token_account, mint, metadata, edition)?;
+    // check owner of token = owner param given to program
+    if token_account.owner != owner.key() {
+        return Err(ErrorCode::RecipeHolderInvalid.into());
+    }
+    // check owner is creator/signer for metadata account
+    verify_creator(metadata, &owner.key())?;
+
+    // all tests passed!
+    Ok(())
+}
+
+/*
+    validate Recipe NFT accounts read by a craft
+      whoever holds the token (checked against recipe_holder in verify_fee_recipients)
+      need not sign nor be a creator, the Recipe NFT is tradeable
+*/
+pub fn verify_recipe_token<'info>(
+    token_account: &Account<'info, TokenAccount>,
+    mint: &Account<'info, Mint>,
+    metadata: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
 ) -> Result<()> {
     // token acount -> Account Info (makes account contents readable)
     let token_info = &token_account.to_account_info();
@@ -24,16 +61,8 @@ pub fn verify_recipe_nft<'info, 'a>(
     let token: spl_token::state::Account = assert_initialized(token_info)?;
     // check token account is owned by Solana SPL Token Program
     assert_owned_by(token_info, &SPL_TOKEN_ID)?;
-    // check owner of token = owner param given to program
-    assert_eq!(token.owner, owner.key());
-    // check token account has a balance (skin == amount of 1)
-    if token.amount != 1 {
-        return Err(ErrorCode::TokenAmountInvalid.into());
-    }
-    // check token account's mint is mint account passed to program
-    if token.mint != mint.key() {
-        return Err(ErrorCode::TokenMintInvalid.into());
-    }
+    // check token account holds the single token of mint account passed to program
+    check_nft_token(&token_state(&token), &mint.key())?;
     // check metadata PDA was derived correctly
     assert_derivation(
         &mpl_token_metadata::id(), // TOKEN_METADATA_PROGRAM_ID
@@ -53,52 +82,24 @@ pub fn verify_recipe_nft<'info, 'a>(
     if metadata.data_is_empty() {
         return Err(ErrorCode::NotInitialized.into());
     };
-    // check owner is creator/signer for metadata account
-    let metadata_account = Metadata::from_account_info(&metadata)?;
-    let creators_found = metadata_account.data.creators.clone().unwrap();
-    creators_found
-        .iter()
-        .find(|c| c.verified && c.address == owner.key())
-        .unwrap();
-
-    // all tests passed!
     Ok(())
 }
 
-/*
-    recipe_account is a PDA of seeds
-    => ["recipe", recipe_mint], this.programId
-    check recipe_account address == result of PDA
-*/
-pub fn assert_recipe_derivation<'info, 'a>(
-    recipe_account: &Account<'info, Recipe>,
-    program_id: &Pubkey,
-    seeds: &[&[u8]],
-) -> Result<()> {
-    // derive recipe account PDA
-    let (key, _) = Pubkey::find_program_address(&seeds, program_id);
-
-    // if recipe_account doesn't match correct PDA, throw error
-    if key != recipe_account.key() {
-        return Err(ErrorCode::DerivedKeyInvalid.into());
+// check creator is a verified creator of metadata
+pub fn verify_creator(metadata: &AccountInfo, creator: &Pubkey) -> Result<()> {
+    let metadata_account = Metadata::from_account_info(metadata)?;
+    let verified = metadata_account
+        .data
+        .creators
+        .unwrap_or_default()
+        .iter()
+        .any(|c| c.verified && c.address == *creator);
+    if !verified {
+        return Err(ErrorCode::WrongCreators.into());
     }
     Ok(())
 }
 
-pub fn assert_escrow_derivation<'info, 'a>(
-    program_escrow: &AccountInfo<'a>,
-    program_id: &Pubkey,
-    seeds: &[&[u8]],
-) -> Result<u8> {
-    // derive recipe account PDA
-    let (key, bump) = Pubkey::find_program_address(&seeds, program_id);
-    // if recipe_account doesn't match correct PDA, throw error
-    if key != *program_escrow.key {
-        return Err(ErrorCode::DerivedKeyInvalid.into());
-    }
-    Ok(bump)
-}
-
 pub fn verify_token_account(
     escrow_token_account: &AccountInfo,
     owner: &AccountInfo,
@@ -111,9 +112,16 @@ pub fn verify_token_account(
 
     match acc {
         Ok(account) => {
-            assert_eq!(account.mint, mint.key());
-            assert_eq!(escrow_token_account.key(), check_escrow_key);
-            assert_eq!(account.owner, owner.key());
+            if account.mint != mint.key() {
+                return Err(ErrorCode::TokenMintInvalid.into());
+            }
+            // ATA of another owner, e.g. the vault of another recipe
+            if escrow_token_account.key() != check_escrow_key {
+                return Err(ErrorCode::DerivedKeyInvalid.into());
+            }
+            if account.owner != owner.key() {
+                return Err(ErrorCode::TokenOwnerInvalid.into());
+            }
             assert_owned_by(escrow_token_account, &SPL_TOKEN_ID)?;
             Ok(false)
         }
@@ -121,13 +129,14 @@ pub fn verify_token_account(
     }
 }
 
-pub fn assert_pda_derivation<'info, 'a>(
+// finds canonical bump, only used when creating an account (bump is stored after)
+pub fn assert_pda_derivation<'info>(
     account: &AccountInfo<'info>,
     program_id: &Pubkey,
     seeds: &[&[u8]],
 ) -> Result<u8> {
     // derive recipe account PDA
-    let (key, bump) = Pubkey::find_program_address(&seeds, program_id);
+    let (key, bump) = Pubkey::find_program_address(seeds, program_id);
 
     // if recipe_account doesn't match correct PDA, throw error
     if key != account.key() {
@@ -136,7 +145,55 @@ pub fn assert_pda_derivation<'info, 'a>(
     Ok(bump)
 }
 
-pub fn verify_user_ingredient<'info>(
+// check account is PDA of seeds + stored bump, no bump search
+pub fn assert_stored_pda(
+    account: &AccountInfo,
+    program_id: &Pubkey,
+    seeds: &[&[u8]],
+    bump: u8,
+) -> Result<()> {
+    let bump = [bump];
+    let seeds = [seeds, &[&bump[..]]].concat();
+    let key = Pubkey::create_program_address(&seeds, program_id)
+        .map_err(|_| ErrorCode::DerivedKeyInvalid)?;
+    if key != account.key() {
+        return Err(ErrorCode::DerivedKeyInvalid.into());
+    }
+    Ok(())
+}
+
+/*
+    seeds prefix, bump + vault bump stored by the Recipe or LargeRecipe owning a vault
+    a closed recipe stores none, DerivedKeyInvalid
+*/
+pub fn read_vault_recipe(recipe: &AccountInfo) -> Result<(&'static [u8], u8, u8)> {
+    if recipe.owner != &crate::ID {
+        return Err(ErrorCode::DerivedKeyInvalid.into());
+    }
+    let is_recipe = recipe
+        .try_borrow_data()?
+        .starts_with(&Recipe::discriminator());
+    if is_recipe {
+        let recipe = Account::<Recipe>::try_from(recipe)?;
+        check_account_version(recipe.version, Recipe::VERSION)?;
+        return Ok((b"recipe", recipe.bump, recipe.vault_bump));
+    }
+    let large_recipe =
+        AccountLoader::<LargeRecipe>::try_from(recipe).map_err(|_| ErrorCode::DerivedKeyInvalid)?;
+    let large_recipe = large_recipe.load()?;
+    Ok((b"large_recipe", large_recipe.bump, large_recipe.vault_bump))
+}
+
+// craft rules read token accounts as TokenState
+fn token_state(token: &spl_token::state::Account) -> TokenState {
+    TokenState {
+        mint: token.mint,
+        owner: token.owner,
+        amount: token.amount,
+    }
+}
+
+pub fn verify_user_ingredient(
     user_ingredient_token: &AccountInfo,
     user: &AccountInfo,
     expected_ingredient_mint: &Pubkey,
@@ -147,32 +204,28 @@ pub fn verify_user_ingredient<'info>(
     // check token account is owned by Solana SPL Token Program
     assert_owned_by(user_ingredient_token, &SPL_TOKEN_ID)?;
     // check owner of token = user
-    assert_eq!(token.owner, user.key());
-
-    // clone token account info
-    let info = user_ingredient_token.try_borrow_data().unwrap();
-    // construct account info into TokenAccount -> allows us to read contents/struct (AccountInfo)
-    let token_account = TokenAccount::try_deserialize(&mut &**info).unwrap();
-
-    // check user ingredient is required amount
-    if token_account.amount != *expected_ingredient_amount {
-        return Err(ErrorCode::TokenAmountInvalid.into());
-    }
-    // check user ingredient is required mint
-    if token_account.mint != expected_ingredient_mint.key() {
-        return Err(ErrorCode::TokenMintInvalid.into());
+    if token.owner != user.key() {
+        return Err(ErrorCode::TokenOwnerInvalid.into());
     }
+    // check user ingredient holds at least the required amount of required mint
+    check_ingredient(
+        &token_state(&token),
+        expected_ingredient_mint,
+        *expected_ingredient_amount,
+    )?;
 
     Ok(())
 }
 
 // validate accounts needed to make Recipe NFT
-pub fn verify_skin<'info, 'a>(
+pub fn verify_skin<'info>(
     token_account: &Account<'info, TokenAccount>,
     mint: &Account<'info, Mint>,
     collection_mint: &Account<'info, Mint>,
     metadata: &AccountInfo<'info>,
-    owner: &Signer<'info>,
+    owner: &AccountInfo<'info>,
+    // verified creator of skin metadata, None => skip creator check
+    creator: Option<&Pubkey>,
 ) -> Result<()> {
     // token acount -> Account Info (makes account contents readable)
     let token_info = &token_account.to_account_info();
@@ -181,15 +234,11 @@ pub fn verify_skin<'info, 'a>(
     // check token account is owned by Solana SPL Token Program
     assert_owned_by(token_info, &SPL_TOKEN_ID)?;
     // check owner of token = owner param given to program
-    assert_eq!(token.owner, owner.key());
-    // check token account has a balance (skin == amount of 1)
-    if token.amount != 1 {
-        return Err(ErrorCode::TokenAmountInvalid.into());
-    }
-    // check token account's mint is mint account passed to program
-    if token.mint != mint.key() {
-        return Err(ErrorCode::TokenMintInvalid.into());
+    if token.owner != owner.key() {
+        return Err(ErrorCode::TokenOwnerInvalid.into());
     }
+    // check token account holds the single token of mint account passed to program
+    check_nft_token(&token_state(&token), &mint.key())?;
     // check metadata PDA was derived correctly
     assert_derivation(
         &mpl_token_metadata::id(), // TOKEN_METADATA_PROGRAM_ID
@@ -207,16 +256,16 @@ pub fn verify_skin<'info, 'a>(
         return Err(ErrorCode::NotInitialized.into());
     };
 
-    // check owner is creator/signer for metadata account
-    let metadata_account = Metadata::from_account_info(&metadata)?;
-    let creators_found = metadata_account.data.creators.clone().unwrap();
-    creators_found
-        .iter()
-        .find(|c| c.verified && c.address == owner.key())
-        .unwrap();
+    // check creator is verified signer for metadata account
+    if let Some(creator) = creator {
+        verify_creator(metadata, creator)?;
+    }
 
     // check collection struct is set
-    let collection_found = &mut metadata_account.collection.clone().unwrap();
+    let metadata_account = Metadata::from_account_info(metadata)?;
+    let collection_found = metadata_account
+        .collection
+        .ok_or(ErrorCode::CollectionUnverified)?;
     // check collection is verified
     if !collection_found.verified {
         return Err(ErrorCode::CollectionUnverified.into());
@@ -230,6 +279,7 @@ pub fn verify_skin<'info, 'a>(
 }
 
 /// Creates associated token account using Program Derived Address for the given seeds
+#[allow(clippy::too_many_arguments)] // accounts of the associated token create CPI
 pub fn create_escrow_account<'info>(
     user: &Signer<'info>,
     program_signer: &AccountInfo<'info>,
@@ -244,8 +294,7 @@ pub fn create_escrow_account<'info>(
         escrow_token,   // token account to receive ingredient from user
         program_signer, // owner of escrow_token account (is also a PDA)
         mint,           // expected ingredient mint defined in Recipe
-    )
-    .unwrap();
+    )?;
 
     // escrow token account not initialized -> create account
     if not_init {
@@ -269,7 +318,6 @@ pub fn create_escrow_account<'info>(
 /// Creates associated token account using Program Derived Address for the given seeds
 pub fn create_user_token_account<'info>(
     user: &Signer<'info>,
-    program_signer: &AccountInfo<'info>,
     token: &AccountInfo<'info>,
     mint: &AccountInfo<'info>,
     rent_account: &Sysvar<'info, Rent>,
@@ -281,8 +329,7 @@ pub fn create_user_token_account<'info>(
         token, // token account to receive ingredient from user
         user,  // owner of escrow_token account (is also a PDA)
         mint,  // expected ingredient mint defined in Recipe
-    )
-    .unwrap();
+    )?;
 
     // escrow token account not initialized -> create account
     if not_init {
@@ -303,6 +350,37 @@ pub fn create_user_token_account<'info>(
     Ok(())
 }
 
+// creates associated token account of owner if not init, rent paid by payer
+#[allow(clippy::too_many_arguments)] // accounts of the associated token create CPI
+pub fn create_owner_token_account<'info>(
+    payer: &Signer<'info>,
+    owner: &AccountInfo<'info>,
+    token: &AccountInfo<'info>,
+    mint: &AccountInfo<'info>,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let not_init = verify_token_account(token, owner, mint)?;
+
+    if not_init {
+        let cpi_accounts = Create {
+            payer: payer.to_account_info(),
+            associated_token: token.to_account_info(),
+            authority: owner.to_account_info(),
+            mint: mint.clone(),
+            system_program: system_program.to_account_info(),
+            token_program: token_program.to_account_info(),
+            rent: rent_account.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new(ata_program.to_account_info(), cpi_accounts);
+        create(cpi_ctx)?;
+    }
+    Ok(())
+}
+
 pub fn transfer_ingredient_to_escrow<'info>(
     from: &AccountInfo<'info>,
     to: &AccountInfo<'info>,
@@ -355,44 +433,2133 @@ pub fn transfer_skin_to_user<'info>(
     Ok(())
 }
 
-#[account]
-pub struct Recipe {
-    pub mints: Vec<Pubkey>,
-    pub amounts: Vec<u64>,
+// validate accounts receiving craft fee
+pub fn verify_fee_recipients<'info>(
+    recipe_holder: &AccountInfo<'info>,
+    recipe_token_account: &Account<'info, TokenAccount>,
+    admin: &AccountInfo<'info>,
+    program_manager: &Account<'info, Manager>,
+) -> Result<()> {
+    // royalty goes to whoever currently holds the Recipe NFT
+    if recipe_holder.key() != recipe_token_account.owner {
+        return Err(ErrorCode::RecipeHolderInvalid.into());
+    }
+    // remainder goes to program admin
+    if admin.key() != program_manager.admin {
+        return Err(ErrorCode::AdminInvalid.into());
+    }
+    Ok(())
 }
 
-#[error_code]
-pub enum ErrorCode {
-    #[msg("Token account requires balance of 1")]
-    TokenAmountInvalid,
+/*
+    pay craft fee (lamports) from user
+      royalty_bps share -> current Recipe NFT holder
+      remainder -> program admin
+*/
+pub fn pay_craft_fee<'info>(
+    user: &Signer<'info>,
+    recipe_holder: &AccountInfo<'info>,
+    admin: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    craft_fee: u64,
+    royalty_bps: u16,
+) -> Result<()> {
+    let royalty = royalty_amount(craft_fee, royalty_bps)?;
+    let remainder = craft_fee - royalty;
 
-    #[msg("Token account owner is invalid")]
-    TokenOwnerInvalid,
+    for (to, amount) in [(recipe_holder, royalty), (admin, remainder)] {
+        if amount == 0 {
+            continue;
+        }
+        let cpi_accounts = system_program::Transfer {
+            from: user.to_account_info(),
+            to: to.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+        system_program::transfer(cpi_ctx, amount)?;
+    }
+    Ok(())
+}
 
-    #[msg("Token account mint != expected Recipe mint")]
-    TokenMintInvalid,
+// royalty_bps share of craft_fee, rounded down
+pub fn royalty_amount(craft_fee: u64, royalty_bps: u16) -> Result<u64> {
+    if royalty_bps > MAX_BASIS_POINTS {
+        return Err(ErrorCode::RoyaltyInvalid.into());
+    }
+    Ok(bps_share(craft_fee, royalty_bps))
+}
 
-    #[msg("Metadata account not initialized")]
-    NotInitialized,
+// basis points share of amount, rounded down
+pub fn bps_share(amount: u64, bps: u16) -> u64 {
+    (amount as u128 * bps.min(MAX_BASIS_POINTS) as u128 / MAX_BASIS_POINTS as u128) as u64
+}
 
-    #[msg("Wrong creators or already signed for program signer")]
-    WrongCreators,
+// 100% in basis points
+pub const MAX_BASIS_POINTS: u16 = 10_000;
 
-    #[msg("Not enough tokens")]
-    NotEnoughToken,
+// most skins crafted by one craft_skin_batch
+pub const MAX_BATCH_QUANTITY: u8 = 10;
 
-    #[msg("Derived key is invalid PDA")]
-    DerivedKeyInvalid,
+// validate + transfer each Recipe ingredient to escrow, quantity crafts at once
+// escrow is the recipe vault (instant crafts) or the PendingCraft / CraftJob of an open craft
+// accounts are triplets in remaining_accounts: [user token, mint, escrow token]
+// returns the mints of escrowed input skins
+#[allow(clippy::too_many_arguments)] // recipe ingredient lists + accounts of the escrow transfers
+pub fn escrow_ingredients<'a, 'info>(
+    recipe: &Recipe,
+    quantity: u64,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    user: &Signer<'info>,
+    escrow: &AccountInfo<'info>,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<Vec<Pubkey>> {
+    escrow_ingredient_list(
+        &recipe.mints,
+        &recipe.amounts,
+        quantity,
+        iterator,
+        user,
+        escrow,
+        rent_account,
+        token_program,
+        ata_program,
+        system_program,
+    )?;
 
-    #[msg("Collection is unverified")]
-    CollectionUnverified,
+    // skins from other recipes consumed by upgrade recipes, accounts repeat for each craft
+    let mut escrowed_skins = vec![];
+    for _ in 0..quantity {
+        escrowed_skins.extend(consume_input_skins(
+            recipe,
+            iterator,
+            user,
+            escrow,
+            rent_account,
+            token_program,
+            ata_program,
+            system_program,
+        )?);
+    }
+    Ok(escrowed_skins)
+}
 
-    #[msg("Collect key is not expected value")]
-    CollectionKeyInvalid,
+// escrow_ingredients of mints + amounts lists (Recipe or LargeRecipe), no input skins
+#[allow(clippy::too_many_arguments)] // recipe ingredient lists + accounts of the escrow transfers
+pub fn escrow_ingredient_list<'a, 'info>(
+    mints: &[Pubkey],
+    amounts: &[u64],
+    quantity: u64,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    user: &Signer<'info>,
+    escrow: &AccountInfo<'info>,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    for (ingredient_mint, amount) in mints.iter().zip(amounts.iter()) {
+        // one transfer per ingredient covers every craft of the batch
+        let ingredient_amount = &amount
+            .checked_mul(quantity)
+            .ok_or(ErrorCode::BatchQuantityInvalid)?;
+        // user ingredient token account
+        let user_token = next_account_info(iterator)?;
+        // user ingredient mint (should == ingredient_mint)
+        let user_mint = next_account_info(iterator)?;
+        // escrow ATA to receive user ingredient
+        let escrow_token = next_account_info(iterator)?;
 
-    #[msg("User token account mint != user mint account")]
-    TokenMintMismatch,
+        // verify user ingredient token == required ingredient mint/amount defined in Recipe
+        verify_user_ingredient(
+            user_token,              // ingredient token account
+            &user.to_account_info(), // owner of user_token account
+            ingredient_mint,         // expected mint inside user_token, defined in Recipe
+            ingredient_amount,       // expect amount inside user_token, defined in Recipe
+        )?;
 
-    #[msg("Escrow token account not initialized")]
-    EscrowNotInitialized,
+        create_escrow_account(
+            user,
+            escrow,
+            escrow_token,
+            user_mint,
+            rent_account,
+            token_program,
+            ata_program,
+            system_program,
+        )?;
+
+        // transfer token from user to escrow
+        transfer_ingredient_to_escrow(
+            user_token,
+            escrow_token,
+            user,
+            ingredient_amount,
+            token_program,
+        )?;
+        msg!("transfer_ingredient_to_escrow");
+    }
+    Ok(())
+}
+
+/*
+    consume each Recipe input skin, accounts follow the ingredient triplets
+      Burn   => [user skin token, skin mint, skin metadata]
+      Escrow => [user skin token, skin mint, skin metadata, escrow skin token]
+    returns the mints of escrowed skins, returned to user if the craft is refunded or canceled
+*/
+#[allow(clippy::too_many_arguments)] // input skin rules + accounts of the burn / escrow CPIs
+pub fn consume_input_skins<'a, 'info>(
+    recipe: &Recipe,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    user: &Signer<'info>,
+    escrow: &AccountInfo<'info>,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<Vec<Pubkey>> {
+    let mut escrowed_skins = vec![];
+    for input in recipe.input_skins.iter() {
+        let user_skin_token = next_account_info(iterator)?;
+        let skin_mint = next_account_info(iterator)?;
+        let skin_metadata = next_account_info(iterator)?;
+
+        // user holds a skin verified in the input recipe collection
+        verify_user_ingredient(user_skin_token, &user.to_account_info(), skin_mint.key, &1)?;
+        verify_skin_collection(skin_mint, skin_metadata, &input.collection)?;
+
+        match input.consume {
+            SkinConsume::Burn => {
+                let cpi_accounts = Burn {
+                    mint: skin_mint.to_account_info(),
+                    from: user_skin_token.to_account_info(),
+                    authority: user.to_account_info(),
+                };
+                let cpi_ctx = CpiContext::new(token_program.to_account_info(), cpi_accounts);
+                burn(cpi_ctx, 1)?;
+                msg!("burn_input_skin");
+            }
+            SkinConsume::Escrow => {
+                let escrow_skin_token = next_account_info(iterator)?;
+                create_escrow_account(
+                    user,
+                    escrow,
+                    escrow_skin_token,
+                    skin_mint,
+                    rent_account,
+                    token_program,
+                    ata_program,
+                    system_program,
+                )?;
+                transfer_ingredient_to_escrow(
+                    user_skin_token,
+                    escrow_skin_token,
+                    user,
+                    &1,
+                    token_program,
+                )?;
+                escrowed_skins.push(skin_mint.key());
+                msg!("escrow_input_skin");
+            }
+        }
+    }
+    Ok(escrowed_skins)
+}
+
+/*
+    user qualifies for a gated recipe, gate accounts are not consumed
+      TokenBalance => [user gate token]
+      Collection   => [user gate token, gate mint, gate metadata]
+*/
+pub fn verify_recipe_gate<'a, 'info>(
+    gate: &RecipeGate,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    user: &AccountInfo<'info>,
+) -> Result<()> {
+    let user_gate_token = next_account_info(iterator)?;
+    match gate {
+        RecipeGate::TokenBalance { mint, min_amount } => {
+            verify_token_balance(user_gate_token, user, mint, *min_amount)?;
+        }
+        RecipeGate::Collection { collection } => {
+            let gate_mint = next_account_info(iterator)?;
+            let gate_metadata = next_account_info(iterator)?;
+            verify_token_balance(user_gate_token, user, gate_mint.key, 1)?;
+            verify_skin_collection(gate_mint, gate_metadata, collection)?;
+        }
+    }
+    Ok(())
+}
+
+/*
+    validate + write the settings given to create_recipe / update_recipe, listing fields included
+    bump, revision, version and open_crafts are left to the caller
+*/
+pub fn write_recipe_settings(recipe: &mut Recipe, settings: RecipeSettings) -> Result<()> {
+    // royalty is a share of craft_fee, cannot exceed 100%
+    royalty_amount(settings.craft_fee, settings.royalty_bps)?;
+    // ingredients + outputs must fit in Recipe account
+    verify_recipe_lists(
+        &settings.ingredient_mints,
+        &settings.ingredient_amounts,
+        &settings.outputs,
+    )?;
+    // loot table must match recipe mode
+    verify_loot_table(settings.mode, &settings.loot_table)?;
+    verify_reveal_window(
+        settings.mode,
+        settings.randomness_program,
+        settings.reveal_delay,
+        settings.reveal_timeout,
+        settings.cancel_penalty_bps,
+    )?;
+    verify_craft_duration(
+        settings.mode,
+        settings.craft_duration,
+        settings.speed_up_mint,
+        settings.speed_up_amount,
+    )?;
+    if settings.cancel_penalty_bps > MAX_BASIS_POINTS || settings.salvage_bps > MAX_BASIS_POINTS {
+        return Err(ErrorCode::PenaltyInvalid.into());
+    }
+    if settings.input_skins.len() > Recipe::MAX_INPUT_SKINS {
+        return Err(ErrorCode::TooManyInputSkins.into());
+    }
+    write_recipe_metadata(
+        recipe,
+        settings.name,
+        settings.description,
+        settings.category,
+        settings.sort_order,
+    )?;
+
+    recipe.mints = settings.ingredient_mints;
+    recipe.amounts = settings.ingredient_amounts;
+    recipe.craft_fee = settings.craft_fee;
+    recipe.royalty_bps = settings.royalty_bps;
+    recipe.outputs = settings.outputs;
+    recipe.mode = settings.mode;
+    recipe.loot_table = settings.loot_table;
+    recipe.randomness_program = settings.randomness_program;
+    recipe.reveal_delay = settings.reveal_delay;
+    recipe.reveal_timeout = settings.reveal_timeout;
+    recipe.craft_duration = settings.craft_duration;
+    recipe.speed_up_mint = settings.speed_up_mint;
+    recipe.speed_up_amount = settings.speed_up_amount;
+    recipe.cancel_penalty_bps = settings.cancel_penalty_bps;
+    recipe.salvage_bps = settings.salvage_bps;
+    recipe.input_skins = settings.input_skins;
+    recipe.gate = settings.gate;
+    recipe.allowlist_root = settings.allowlist_root;
+    Ok(())
+}
+
+// validate + write listing fields given to create_recipe / update_recipe
+pub fn write_recipe_metadata(
+    recipe: &mut Recipe,
+    name: String,
+    description: String,
+    category: RecipeCategory,
+    sort_order: u16,
+) -> Result<()> {
+    check_recipe_metadata(&name, &description)?;
+    recipe.name = name;
+    recipe.description = description;
+    recipe.category = category;
+    recipe.sort_order = sort_order;
+    Ok(())
+}
+
+// validate ingredient + output lists given to create_recipe
+pub fn verify_recipe_lists(
+    ingredient_mints: &[Pubkey],
+    ingredient_amounts: &[u64],
+    outputs: &[RecipeOutput],
+) -> Result<()> {
+    if ingredient_mints.len() != ingredient_amounts.len() {
+        return Err(ErrorCode::IngredientLengthMismatch.into());
+    }
+    if ingredient_mints.len() > Recipe::MAX_INGREDIENTS {
+        return Err(ErrorCode::TooManyIngredients.into());
+    }
+    if outputs.len() > Recipe::MAX_OUTPUTS {
+        return Err(ErrorCode::TooManyOutputs.into());
+    }
+    verify_output_amounts(outputs)
+}
+
+pub fn verify_output_amounts(outputs: &[RecipeOutput]) -> Result<()> {
+    for output in outputs.iter() {
+        // skins are NFTs, delivered one at a time
+        let valid = match output.kind {
+            OutputKind::Skin => output.amount == 1,
+            OutputKind::Fungible => output.amount > 0,
+        };
+        if !valid {
+            return Err(ErrorCode::OutputAmountInvalid.into());
+        }
+    }
+    Ok(())
+}
+
+// validate loot table matches recipe mode
+pub fn verify_loot_table(mode: RecipeMode, loot_table: &[LootEntry]) -> Result<()> {
+    if loot_table.len() > Recipe::MAX_LOOT_ENTRIES {
+        return Err(ErrorCode::TooManyLootEntries.into());
+    }
+    let valid = match mode {
+        RecipeMode::Fixed => loot_table.is_empty(),
+        RecipeMode::Random => !loot_table.is_empty() && loot_table.iter().all(|l| l.weight > 0),
+    };
+    if !valid {
+        return Err(ErrorCode::LootTableInvalid.into());
+    }
+    let outputs: Vec<RecipeOutput> = loot_table.iter().map(|l| l.output).collect();
+    verify_recipe_lists(&[], &[], &outputs)
+}
+
+/*
+    read 32 bytes of randomness for a single transaction craft_random_skin
+    most recent hash in slot hashes sysvar, VRF recipes go through begin_craft
+*/
+pub fn read_randomness(randomness: &AccountInfo) -> Result<[u8; 32]> {
+    if randomness.key() != slot_hashes::ID {
+        return Err(ErrorCode::RandomnessAccountInvalid.into());
+    }
+
+    // slot hashes data => [len: u64][(slot: u64, hash: [u8; 32])...], most recent first
+    let data = randomness.try_borrow_data()?;
+    let bytes = data
+        .get(8 + 8..8 + 8 + 32)
+        .ok_or(ErrorCode::RandomnessUnavailable)?;
+    let mut result = [0u8; 32];
+    result.copy_from_slice(bytes);
+    Ok(result)
+}
+
+// randomness of a VRF result account, None until the VRF program fulfilled it
+fn vrf_result(randomness: &AccountInfo) -> Result<Option<[u8; 32]>> {
+    let data = randomness.try_borrow_data()?;
+    let bytes = match data.get(VRF_RESULT_OFFSET..VRF_RESULT_OFFSET + 32) {
+        Some(bytes) if bytes.iter().any(|b| *b != 0) => bytes,
+        _ => return Ok(None),
+    };
+    let mut result = [0u8; 32];
+    result.copy_from_slice(bytes);
+    Ok(Some(result))
+}
+
+// VRF result account of a begin_craft request, PDA of randomness_program
+pub fn vrf_result_address(
+    program: &Pubkey,
+    recipe: &Pubkey,
+    user: &Pubkey,
+    request: u64,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            b"randomness",
+            recipe.as_ref(),
+            user.as_ref(),
+            &request.to_le_bytes(),
+        ],
+        program,
+    )
+    .0
+}
+
+/*
+    validate the randomness account a begin_craft commits to
+      randomness_program == None => slot hashes sysvar
+      randomness_program == Some => VRF result account of ["randomness", recipe, user, request]
+        not yet fulfilled, so the roll is unknown when ingredients are escrowed
+*/
+pub fn verify_randomness_request(
+    randomness: &AccountInfo,
+    recipe: &Recipe,
+    recipe_key: &Pubkey,
+    user: &Pubkey,
+    request: u64,
+) -> Result<()> {
+    let program = match recipe.randomness_program {
+        None if randomness.key() == slot_hashes::ID => return Ok(()),
+        None => return Err(ErrorCode::RandomnessAccountInvalid.into()),
+        Some(program) => program,
+    };
+    if randomness.key() != vrf_result_address(&program, recipe_key, user, request) {
+        return Err(ErrorCode::RandomnessAccountInvalid.into());
+    }
+    if *randomness.owner == program && vrf_result(randomness)?.is_some() {
+        return Err(ErrorCode::RandomnessAlreadyFulfilled.into());
+    }
+    Ok(())
+}
+
+// mix randomness with user + recipe so every crafter in a slot rolls differently
+pub fn roll_loot(randomness: &[u8; 32], user: &Pubkey, recipe: &Pubkey) -> u64 {
+    let hash = keccak::hashv(&[randomness, user.as_ref(), recipe.as_ref()]);
+    let mut roll = [0u8; 8];
+    roll.copy_from_slice(&hash.0[..8]);
+    u64::from_le_bytes(roll)
+}
+
+// select loot table index, each entry has weight / total weight chance
+pub fn select_loot_entry(loot_table: &[LootEntry], roll: u64) -> Result<usize> {
+    let total: u64 = loot_table.iter().map(|l| l.weight as u64).sum();
+    if total == 0 {
+        return Err(ErrorCode::LootTableInvalid.into());
+    }
+    let mut target = roll % total;
+    for (i, loot) in loot_table.iter().enumerate() {
+        if target < loot.weight as u64 {
+            return Ok(i);
+        }
+        target -= loot.weight as u64;
+    }
+    Err(ErrorCode::LootTableInvalid.into())
+}
+
+// validate commit-reveal window of a recipe
+pub fn verify_reveal_window(
+    mode: RecipeMode,
+    randomness_program: Option<Pubkey>,
+    reveal_delay: u64,
+    reveal_timeout: u64,
+    cancel_penalty_bps: u16,
+) -> Result<()> {
+    if reveal_delay == 0 {
+        // VRF results are bound to a begin_craft request
+        if randomness_program.is_some() {
+            return Err(ErrorCode::RevealWindowInvalid.into());
+        }
+        return Ok(());
+    }
+    // commit-reveal only applies to rolled recipes
+    if mode != RecipeMode::Random {
+        return Err(ErrorCode::RevealWindowInvalid.into());
+    }
+    // refund must not open while the reveal slot hash can still be read
+    if randomness_program.is_none() && reveal_timeout < SLOT_HASHES_MAX_ENTRIES as u64 {
+        return Err(ErrorCode::RevealWindowInvalid.into());
+    }
+    // roll is public after reveal_slot, a free refund would let users skip bad rolls
+    if cancel_penalty_bps == 0 {
+        return Err(ErrorCode::RevealWindowInvalid.into());
+    }
+    Ok(())
+}
+
+/*
+    read 32 bytes of randomness for a commit-reveal craft
+    randomness must be the account recorded in PendingCraft by begin_craft
+      randomness_program == None => hash of first slot >= reveal_slot in slot hashes sysvar,
+        unavailable once reveal_slot leaves the sysvar, refund_craft is left
+      randomness_program == Some => fulfilled VRF result account owned by randomness_program
+*/
+pub fn read_reveal_randomness(
+    randomness: &AccountInfo,
+    recipe: &Recipe,
+    pending_craft: &PendingCraft,
+) -> Result<[u8; 32]> {
+    if randomness.key() != pending_craft.randomness {
+        return Err(ErrorCode::RandomnessAccountInvalid.into());
+    }
+    if let Some(program) = recipe.randomness_program {
+        if *randomness.owner != program {
+            return Err(ErrorCode::RandomnessAccountInvalid.into());
+        }
+        return vrf_result(randomness)?.ok_or_else(|| ErrorCode::RandomnessUnavailable.into());
+    }
+    let reveal_slot = pending_craft.reveal_slot;
+
+    /*
+        slot hashes data => [len: u64][(slot: u64, hash: [u8; 32])...], most recent first
+        the first slot >= reveal_slot is only known while an entry before reveal_slot is kept,
+        once it is evicted the oldest entry changes every slot and could be picked by waiting
+    */
+    let data = randomness.try_borrow_data()?;
+    let mut found: Option<&[u8]> = None;
+    let mut pinned = false;
+    for entry in data.get(8..).unwrap_or(&[]).chunks_exact(8 + 32) {
+        let slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if slot < reveal_slot {
+            pinned = true;
+            break;
+        }
+        found = Some(&entry[8..]);
+    }
+
+    let bytes = found
+        .filter(|_| pinned)
+        .ok_or(ErrorCode::RandomnessUnavailable)?;
+    let mut result = [0u8; 32];
+    result.copy_from_slice(bytes);
+    Ok(result)
+}
+
+/*
+    signer seeds of a craft account holding its own escrow
+      PendingCraft => ["pending", recipe, user]
+      CraftJob     => ["job", recipe, user]
+    ATAs of the craft account are out of reach of withdraw_vault until the craft settles
+*/
+pub fn craft_seeds<'a>(
+    prefix: &'static [u8],
+    recipe: &'a Pubkey,
+    user: &'a Pubkey,
+    bump: &'a [u8],
+) -> [&'a [u8]; 4] {
+    [prefix, recipe.as_ref(), user.as_ref(), bump]
+}
+
+// balance of a token account of mint held by owner
+fn owned_token_balance(token: &AccountInfo, owner: &Pubkey, mint: &Pubkey) -> Result<u64> {
+    assert_owned_by(token, &SPL_TOKEN_ID)?;
+    let data = token.try_borrow_data()?;
+    let account = TokenAccount::try_deserialize(&mut &**data)?;
+    if account.mint != *mint {
+        return Err(ErrorCode::TokenMintInvalid.into());
+    }
+    if account.owner != *owner {
+        return Err(ErrorCode::TokenOwnerInvalid.into());
+    }
+    Ok(account.amount)
+}
+
+/*
+    move the escrow of a delivered craft into the recipe vault (finish_craft, claim_craft)
+    accounts are triplets in remaining_accounts: [escrow token, mint, vault token]
+    input skins escrowed by the craft settle the same way with amount 1
+*/
+#[allow(clippy::too_many_arguments)] // ingredient lists + craft signer + accounts of the vault transfers
+pub fn settle_escrow<'a, 'info>(
+    mints: &[Pubkey],
+    amounts: &[u64],
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    escrow: &AccountInfo<'info>,
+    escrow_seeds: &[&[u8]],
+    payer: &Signer<'info>,
+    recipe_vault: &AccountInfo<'info>,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    for (mint, amount) in mints.iter().zip(amounts.iter()) {
+        let escrow_token = next_account_info(iterator)?;
+        let escrow_mint = next_account_info(iterator)?;
+        let vault_token = next_account_info(iterator)?;
+        if escrow_mint.key() != *mint {
+            return Err(ErrorCode::TokenMintInvalid.into());
+        }
+        if *amount > owned_token_balance(escrow_token, escrow.key, mint)? {
+            return Err(ErrorCode::NotEnoughToken.into());
+        }
+
+        create_owner_token_account(
+            payer,
+            recipe_vault,
+            vault_token,
+            escrow_mint,
+            rent_account,
+            token_program,
+            ata_program,
+            system_program,
+        )?;
+        transfer_from_program(
+            escrow_token,
+            vault_token,
+            escrow,
+            *amount,
+            token_program,
+            escrow_seeds,
+        )?;
+        msg!("settle_escrow");
+    }
+    Ok(())
+}
+
+/*
+    return the escrow of a refunded or canceled craft to user
+    penalty_bps share of each ingredient goes to the recipe vault, the rest to user
+    accounts are quadruplets in remaining_accounts: [escrow token, mint, user token, vault token]
+*/
+#[allow(clippy::too_many_arguments)] // ingredient lists + craft signer + accounts of the refund transfers
+pub fn return_escrow<'a, 'info>(
+    mints: &[Pubkey],
+    amounts: &[u64],
+    penalty_bps: u16,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    escrow: &AccountInfo<'info>,
+    escrow_seeds: &[&[u8]],
+    user: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    recipe_vault: &AccountInfo<'info>,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    for (mint, amount) in mints.iter().zip(amounts.iter()) {
+        let escrow_token = next_account_info(iterator)?;
+        let escrow_mint = next_account_info(iterator)?;
+        let user_token = next_account_info(iterator)?;
+        let vault_token = next_account_info(iterator)?;
+        if escrow_mint.key() != *mint {
+            return Err(ErrorCode::TokenMintInvalid.into());
+        }
+        if *amount > owned_token_balance(escrow_token, escrow.key, mint)? {
+            return Err(ErrorCode::NotEnoughToken.into());
+        }
+        owned_token_balance(user_token, user.key, mint)?;
+
+        let penalty = bps_share(*amount, penalty_bps);
+        if amount - penalty > 0 {
+            transfer_from_program(
+                escrow_token,
+                user_token,
+                escrow,
+                amount - penalty,
+                token_program,
+                escrow_seeds,
+            )?;
+            msg!("return_ingredient");
+        }
+        if penalty > 0 {
+            create_owner_token_account(
+                payer,
+                recipe_vault,
+                vault_token,
+                escrow_mint,
+                rent_account,
+                token_program,
+                ata_program,
+                system_program,
+            )?;
+            transfer_from_program(
+                escrow_token,
+                vault_token,
+                escrow,
+                penalty,
+                token_program,
+                escrow_seeds,
+            )?;
+            msg!("keep_penalty");
+        }
+    }
+    Ok(())
+}
+
+/*
+    return input skins escrowed by a pending craft to user, burned input skins are gone
+    accounts are pairs in remaining_accounts: [escrow skin token, user skin token]
+*/
+pub fn return_input_skins<'a, 'info>(
+    skin_mints: &[Pubkey],
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    escrow: &AccountInfo<'info>,
+    escrow_seeds: &[&[u8]],
+    user: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    for skin_mint in skin_mints.iter() {
+        let escrow_skin_token = next_account_info(iterator)?;
+        let user_skin_token = next_account_info(iterator)?;
+        if owned_token_balance(escrow_skin_token, escrow.key, skin_mint)? == 0 {
+            return Err(ErrorCode::NotEnoughToken.into());
+        }
+        owned_token_balance(user_skin_token, user.key, skin_mint)?;
+
+        transfer_from_program(
+            escrow_skin_token,
+            user_skin_token,
+            escrow,
+            1,
+            token_program,
+            escrow_seeds,
+        )?;
+        msg!("return_input_skin");
+    }
+    Ok(())
+}
+
+// salvage_bps share of each ingredient amount, recorded when a skin is crafted
+pub fn salvage_amounts(amounts: &[u64], salvage_bps: u16) -> Vec<u64> {
+    amounts
+        .iter()
+        .map(|amount| amount - bps_share(*amount, MAX_BASIS_POINTS - salvage_bps))
+        .collect()
+}
+
+/*
+    pay the salvage recorded for an uncrafted skin out of the recipe vault
+    accounts are pairs in remaining_accounts: [vault token, user token]
+*/
+pub fn pay_salvage<'a, 'info>(
+    record: &SalvageRecord,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    recipe_vault: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    user: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    for (mint, amount) in record.mints.iter().zip(record.amounts.iter()) {
+        let vault_token = next_account_info(iterator)?;
+        let user_token = next_account_info(iterator)?;
+        let vaulted = owned_token_balance(vault_token, recipe_vault.key, mint)?;
+        owned_token_balance(user_token, user.key, mint)?;
+
+        if *amount == 0 {
+            continue;
+        }
+        if *amount > vaulted {
+            return Err(ErrorCode::NotEnoughToken.into());
+        }
+        transfer_from_program(
+            vault_token,
+            user_token,
+            recipe_vault,
+            *amount,
+            token_program,
+            vault_seeds,
+        )?;
+        msg!("pay_salvage");
+    }
+    Ok(())
+}
+
+// validate timed crafting settings of a recipe
+pub fn verify_craft_duration(
+    mode: RecipeMode,
+    craft_duration: i64,
+    speed_up_mint: Option<Pubkey>,
+    speed_up_amount: u64,
+) -> Result<()> {
+    // completes_at is started_at + craft_duration, a negative duration would complete in the past
+    if craft_duration < 0 {
+        return Err(ErrorCode::CraftDurationInvalid.into());
+    }
+    // only craft_skin opens craft jobs
+    let valid = match (craft_duration, speed_up_mint) {
+        (0, None) => true,
+        (d, None) => d > 0 && mode == RecipeMode::Fixed,
+        (d, Some(_)) => d > 0 && mode == RecipeMode::Fixed && speed_up_amount > 0,
+    };
+    if !valid {
+        return Err(ErrorCode::CraftDurationInvalid.into());
+    }
+    Ok(())
+}
+
+// user holds at least amount of mint
+pub fn verify_token_balance(
+    user_token: &AccountInfo,
+    user: &AccountInfo,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    assert_owned_by(user_token, &SPL_TOKEN_ID)?;
+    let data = user_token.try_borrow_data()?;
+    let token_account = TokenAccount::try_deserialize(&mut &**data)?;
+    check_token_balance(&token_state(&token_account), user.key, mint, amount)?;
+    Ok(())
+}
+
+// escrow of a pending craft returned by cancel_craft
+pub struct CancelableCraft {
+    // seed prefix + bump of the craft account, it signs for its escrow
+    pub seed_prefix: &'static [u8],
+    pub bump: u8,
+    pub mints: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    // input skins escrowed by the craft account
+    pub input_skins: Vec<Pubkey>,
+    // skin mint + holder it was taken from of a CraftJob, skin held by program_signer
+    pub job_skin: Option<(Pubkey, Pubkey)>,
+}
+
+// read a pending craft (CraftJob or PendingCraft) for cancel_craft
+pub fn read_cancelable_craft(
+    pending: &AccountInfo,
+    user: &Pubkey,
+    recipe: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<CancelableCraft> {
+    assert_owned_by(pending, program_id)?;
+    let data = pending.try_borrow_data()?;
+
+    if let Ok(job) = CraftJob::try_deserialize(&mut &**data) {
+        if job.user != *user || job.recipe != *recipe {
+            return Err(ErrorCode::PendingCraftInvalid.into());
+        }
+        return Ok(CancelableCraft {
+            seed_prefix: b"job",
+            bump: job.bump,
+            mints: job.mints,
+            amounts: job.amounts,
+            input_skins: job.input_skins,
+            job_skin: Some((job.skin_mint, job.skin_owner)),
+        });
+    }
+
+    let pending_craft =
+        PendingCraft::try_deserialize(&mut &**data).map_err(|_| ErrorCode::PendingCraftInvalid)?;
+    if pending_craft.user != *user || pending_craft.recipe != *recipe {
+        return Err(ErrorCode::PendingCraftInvalid.into());
+    }
+    // roll is known once reveal_slot passes, only refund_craft after timeout
+    if Clock::get()?.slot >= pending_craft.reveal_slot {
+        return Err(ErrorCode::CraftAlreadyRevealed.into());
+    }
+    Ok(CancelableCraft {
+        seed_prefix: b"pending",
+        bump: pending_craft.bump,
+        mints: pending_craft.mints,
+        amounts: pending_craft.amounts,
+        input_skins: pending_craft.input_skins,
+        job_skin: None,
+    })
+}
+
+/*
+    return the skin of a canceled CraftJob to the holder craft_skin took it from
+    accounts in remaining_accounts: [skin mint, program skin token, skin owner, owner skin token]
+*/
+#[allow(clippy::too_many_arguments)] // program signer + accounts of the return transfer
+pub fn return_job_skin<'a, 'info>(
+    skin_mint: &Pubkey,
+    skin_owner: &Pubkey,
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    user: &Signer<'info>,
+    program_signer: &AccountInfo<'info>,
+    signer_bump: u8,
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let mint = next_account_info(iterator)?;
+    let program_token = next_account_info(iterator)?;
+    let owner = next_account_info(iterator)?;
+    let owner_token = next_account_info(iterator)?;
+    if mint.key() != *skin_mint {
+        return Err(ErrorCode::TokenMintInvalid.into());
+    }
+    if owner.key() != *skin_owner {
+        return Err(ErrorCode::TokenOwnerInvalid.into());
+    }
+    if verify_token_account(program_token, program_signer, mint)? {
+        return Err(ErrorCode::EscrowNotInitialized.into());
+    }
+
+    create_owner_token_account(
+        user,
+        owner,
+        owner_token,
+        mint,
+        rent_account,
+        token_program,
+        ata_program,
+        system_program,
+    )?;
+    transfer_from_program(
+        program_token,
+        owner_token,
+        program_signer,
+        1,
+        token_program,
+        &[b"signer", &[signer_bump]],
+    )
+}
+
+// close program owned account, rent lamports to destination
+pub fn close_program_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? += lamports;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+// create CraftJob PDA of ["job", recipe, user], rent paid by user
+pub fn create_craft_job<'info>(
+    craft_job: &AccountInfo<'info>,
+    user: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+    mut job: CraftJob,
+) -> Result<()> {
+    let bump = assert_pda_derivation(
+        craft_job,
+        program_id,
+        &[b"job", job.recipe.as_ref(), job.user.as_ref()],
+    )?;
+    job.bump = bump;
+
+    let cpi_accounts = system_program::CreateAccount {
+        from: user.to_account_info(),
+        to: craft_job.to_account_info(),
+    };
+    let seeds: &[&[u8]] = &[b"job", job.recipe.as_ref(), job.user.as_ref(), &[bump]];
+    let signer = &[seeds];
+    let cpi_ctx =
+        CpiContext::new_with_signer(system_program.to_account_info(), cpi_accounts, signer);
+    system_program::create_account(
+        cpi_ctx,
+        Rent::get()?.minimum_balance(CraftJob::LEN),
+        CraftJob::LEN as u64,
+        program_id,
+    )?;
+
+    let mut data = craft_job.try_borrow_mut_data()?;
+    job.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/*
+    SalvageRecord PDA of ["salvage", recipe, skin_mint], rent paid by payer
+    a skin crafted again overwrites the record, only its last craft is salvaged
+*/
+pub fn write_salvage_record<'info>(
+    salvage_record: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+    mut record: SalvageRecord,
+) -> Result<()> {
+    let bump = assert_pda_derivation(
+        salvage_record,
+        program_id,
+        &[
+            b"salvage",
+            record.recipe.as_ref(),
+            record.skin_mint.as_ref(),
+        ],
+    )?;
+    record.bump = bump;
+
+    if salvage_record.data_is_empty() {
+        let cpi_accounts = system_program::CreateAccount {
+            from: payer.to_account_info(),
+            to: salvage_record.to_account_info(),
+        };
+        let seeds: &[&[u8]] = &[
+            b"salvage",
+            record.recipe.as_ref(),
+            record.skin_mint.as_ref(),
+            &[bump],
+        ];
+        let signer = &[seeds];
+        let cpi_ctx =
+            CpiContext::new_with_signer(system_program.to_account_info(), cpi_accounts, signer);
+        system_program::create_account(
+            cpi_ctx,
+            Rent::get()?.minimum_balance(SalvageRecord::LEN),
+            SalvageRecord::LEN as u64,
+            program_id,
+        )?;
+    } else {
+        assert_owned_by(salvage_record, program_id)?;
+    }
+
+    let mut data = salvage_record.try_borrow_mut_data()?;
+    record.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+// PlayerProfile PDA of ["profile", user], None before the first craft
+pub fn load_player_profile(
+    player_profile: &AccountInfo,
+    user: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<Option<PlayerProfile>> {
+    if player_profile.data_is_empty() {
+        // PDA is checked when the profile is created
+        return Ok(None);
+    }
+    assert_owned_by(player_profile, program_id)?;
+    let data = player_profile.try_borrow_data()?;
+    let profile = PlayerProfile::try_deserialize(&mut &**data)?;
+    assert_stored_pda(
+        player_profile,
+        program_id,
+        &[b"profile", user.as_ref()],
+        profile.bump,
+    )?;
+    Ok(Some(profile))
+}
+
+// crafts of recipe recorded on PlayerProfile, 0 before the first craft
+pub fn recipe_craft_count(
+    player_profile: &AccountInfo,
+    user: &Pubkey,
+    recipe: &Pubkey,
+    program_id: &Pubkey,
+) -> Result<u32> {
+    let profile = load_player_profile(player_profile, user, program_id)?;
+    Ok(profile
+        .and_then(|profile| {
+            profile
+                .recipes
+                .into_iter()
+                .find(|count| count.recipe == *recipe)
+        })
+        .map_or(0, |count| count.crafts))
+}
+
+// user is on the recipe allowlist with quantity of allowance crafts left, see rules::check_allowlist
+pub fn verify_allowlist(
+    root: &[u8; 32],
+    user: &Pubkey,
+    allowance: u32,
+    proof: &[[u8; 32]],
+    crafted: u32,
+    quantity: u32,
+) -> Result<()> {
+    check_allowlist(root, user, allowance, proof, crafted, quantity)?;
+    Ok(())
+}
+
+/*
+    count crafts on PlayerProfile PDA of ["profile", user]
+    created on first craft, grows by one entry per newly crafted recipe, rent paid by user
+*/
+pub fn record_craft<'info>(
+    player_profile: &AccountInfo<'info>,
+    user: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    program_id: &Pubkey,
+    recipe: &Pubkey,
+    crafts: u32,
+    crafted_at: i64,
+) -> Result<()> {
+    let loaded = load_player_profile(player_profile, &user.key(), program_id)?;
+    let created = loaded.is_some();
+    let mut profile = match loaded {
+        Some(profile) => profile,
+        None => PlayerProfile {
+            user: user.key(),
+            total_crafts: 0,
+            first_craft_at: crafted_at,
+            last_craft_at: crafted_at,
+            recipes: Vec::new(),
+            bump: assert_pda_derivation(
+                player_profile,
+                program_id,
+                &[b"profile", user.key().as_ref()],
+            )?,
+        },
+    };
+
+    profile.total_crafts += crafts as u64;
+    profile.last_craft_at = crafted_at;
+    match profile
+        .recipes
+        .iter_mut()
+        .find(|count| count.recipe == *recipe)
+    {
+        Some(count) => count.crafts += crafts,
+        None => profile.recipes.push(RecipeCraftCount {
+            recipe: *recipe,
+            crafts,
+        }),
+    }
+
+    let space = PlayerProfile::space(profile.recipes.len());
+    if !created {
+        let cpi_accounts = system_program::CreateAccount {
+            from: user.to_account_info(),
+            to: player_profile.to_account_info(),
+        };
+        let seeds: &[&[u8]] = &[b"profile", profile.user.as_ref(), &[profile.bump]];
+        let signer = &[seeds];
+        let cpi_ctx =
+            CpiContext::new_with_signer(system_program.to_account_info(), cpi_accounts, signer);
+        system_program::create_account(
+            cpi_ctx,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        )?;
+    } else if space > player_profile.data_len() {
+        let lamports = Rent::get()?
+            .minimum_balance(space)
+            .saturating_sub(player_profile.lamports());
+        if lamports > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: user.to_account_info(),
+                to: player_profile.to_account_info(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            system_program::transfer(cpi_ctx, lamports)?;
+        }
+        player_profile.realloc(space, false)?;
+    }
+
+    let mut data = player_profile.try_borrow_mut_data()?;
+    profile.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+/*
+    rewrites account of T in the current layout, migrate bumps the version it was read with
+    older layouts are shorter or equal, data is zero padded to len before deserializing
+    (trailing fields of the current layout read as 0), account grows to len with payer
+    funding the extra rent
+*/
+pub fn migrate_account<'info, T: AccountSerialize + AccountDeserialize>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    len: usize,
+    migrate: impl FnOnce(&mut T) -> Result<()>,
+) -> Result<()> {
+    let mut data = account.try_borrow_data()?.to_vec();
+    if data.len() < len {
+        data.resize(len, 0);
+    }
+    // discriminator is checked
+    let mut state = T::try_deserialize(&mut data.as_slice())?;
+    migrate(&mut state)?;
+
+    if len > account.data_len() {
+        let lamports = Rent::get()?
+            .minimum_balance(len)
+            .saturating_sub(account.lamports());
+        if lamports > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            system_program::transfer(cpi_ctx, lamports)?;
+        }
+        account.realloc(len, true)?;
+    }
+
+    let mut data = account.try_borrow_mut_data()?;
+    state.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+// validate skin mint + metadata is verified in expected collection
+pub fn verify_skin_collection(
+    mint: &AccountInfo,
+    metadata: &AccountInfo,
+    collection_mint: &Pubkey,
+) -> Result<()> {
+    // check metadata PDA was derived correctly
+    assert_derivation(
+        &mpl_token_metadata::id(),
+        metadata,
+        &[
+            PREFIX.as_bytes(),
+            mpl_token_metadata::id().as_ref(),
+            mint.key.as_ref(),
+        ],
+    )?;
+    if metadata.data_is_empty() {
+        return Err(ErrorCode::NotInitialized.into());
+    };
+
+    let metadata_account = Metadata::from_account_info(metadata)?;
+    let collection = metadata_account.collection.map(|c| NftCollection {
+        key: c.key,
+        verified: c.verified,
+    });
+    check_collection(collection.as_ref(), collection_mint)?;
+    Ok(())
+}
+
+/*
+    record crafter, craft time and recipe revision in the skin metadata uri
+      {uri}?crafter={user}&crafted_at={unix timestamp}&recipe_version={revision}
+    any previous query is replaced, so a re-crafted skin shows its latest craft
+    skipped unless program_signer is the (mutable) metadata update authority,
+    or when the stamped uri would not fit MAX_URI_LENGTH (the craft still goes through)
+*/
+pub fn stamp_skin_provenance<'info>(
+    metadata: &AccountInfo<'info>,
+    program_signer: &AccountInfo<'info>,
+    token_metadata_program: &AccountInfo<'info>,
+    signer_bump: u8,
+    crafter: &Pubkey,
+    crafted_at: i64,
+    revision: u32,
+) -> Result<()> {
+    let metadata_account = Metadata::from_account_info(metadata)?;
+    if metadata_account.update_authority != program_signer.key() || !metadata_account.is_mutable {
+        msg!("Skip provenance, program_signer is not skin update authority");
+        return Ok(());
+    }
+
+    // metadata strings are padded with null bytes up to their max length
+    let data = metadata_account.data;
+    let uri = data.uri.trim_end_matches(char::from(0));
+    let base_uri = uri.split('?').next().unwrap_or_default();
+    let uri = format!(
+        "{}?crafter={}&crafted_at={}&recipe_version={}",
+        base_uri, crafter, crafted_at, revision
+    );
+    if uri.len() > MAX_URI_LENGTH {
+        msg!("Skip provenance, stamped uri exceeds max uri length");
+        return Ok(());
+    }
+
+    let ix = update_metadata_accounts_v2(
+        mpl_token_metadata::id(),
+        metadata.key(),
+        program_signer.key(),
+        None,
+        Some(DataV2 {
+            name: data.name.trim_end_matches(char::from(0)).to_string(),
+            symbol: data.symbol.trim_end_matches(char::from(0)).to_string(),
+            uri,
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            creators: data.creators,
+            collection: metadata_account.collection,
+            uses: metadata_account.uses,
+        }),
+        None,
+        None,
+    );
+    invoke_signed(
+        &ix,
+        &[
+            metadata.clone(),
+            program_signer.clone(),
+            token_metadata_program.clone(),
+        ],
+        &[&[b"signer", &[signer_bump]]],
+    )?;
+
+    Ok(())
+}
+
+/*
+    accounts for one Recipe output in remaining_accounts
+      Skin     => [skin mint, skin metadata, vault token account, user token account]
+      Fungible => [output mint, user token account]
+*/
+pub struct OutputAccounts<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    pub metadata: Option<&'a AccountInfo<'info>>,
+    pub vault_token: Option<&'a AccountInfo<'info>>,
+    pub user_token: &'a AccountInfo<'info>,
+}
+
+pub fn next_output_accounts<'a, 'info>(
+    iterator: &mut Iter<'a, AccountInfo<'info>>,
+    kind: OutputKind,
+) -> Result<OutputAccounts<'a, 'info>> {
+    let mint = next_account_info(iterator)?;
+    let (metadata, vault_token) = match kind {
+        OutputKind::Skin => (
+            Some(next_account_info(iterator)?),
+            Some(next_account_info(iterator)?),
+        ),
+        OutputKind::Fungible => (None, None),
+    };
+    let user_token = next_account_info(iterator)?;
+    Ok(OutputAccounts {
+        mint,
+        metadata,
+        vault_token,
+        user_token,
+    })
+}
+
+/*
+    recipe vault PDA of ["vault", recipe], one per Recipe / LargeRecipe
+    skin outputs are stocked in its ATAs, it is mint authority of fungible outputs
+*/
+pub fn vault_seeds<'a>(recipe: &'a Pubkey, bump: &'a [u8]) -> [&'a [u8]; 3] {
+    [b"vault", recipe.as_ref(), bump]
+}
+
+// deliver one Recipe output to user out of the recipe vault
+#[allow(clippy::too_many_arguments)] // accounts of the output transfer / mint CPI
+pub fn deliver_output<'info>(
+    output: &RecipeOutput,
+    accounts: &OutputAccounts<'_, 'info>,
+    payer: &Signer<'info>,
+    user: &AccountInfo<'info>,
+    recipe_vault: &AccountInfo<'info>,
+    vault_seeds: &[&[u8]],
+    rent_account: &Sysvar<'info, Rent>,
+    token_program: &Program<'info, Token>,
+    ata_program: &Program<'info, AssociatedToken>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    match (output.kind, accounts.metadata, accounts.vault_token) {
+        (OutputKind::Skin, Some(metadata), Some(vault_token)) => {
+            // skin stocked in recipe vault must be verified in output collection
+            verify_skin_collection(accounts.mint, metadata, &output.mint)?;
+            if verify_token_account(vault_token, recipe_vault, accounts.mint)? {
+                return Err(ErrorCode::EscrowNotInitialized.into());
+            }
+            create_owner_token_account(
+                payer,
+                user,
+                accounts.user_token,
+                accounts.mint,
+                rent_account,
+                token_program,
+                ata_program,
+                system_program,
+            )?;
+            transfer_from_program(
+                vault_token,
+                accounts.user_token,
+                recipe_vault,
+                output.amount,
+                token_program,
+                vault_seeds,
+            )
+        }
+        (OutputKind::Fungible, _, _) => {
+            if accounts.mint.key() != output.mint {
+                return Err(ErrorCode::TokenMintInvalid.into());
+            }
+            create_owner_token_account(
+                payer,
+                user,
+                accounts.user_token,
+                accounts.mint,
+                rent_account,
+                token_program,
+                ata_program,
+                system_program,
+            )?;
+            // recipe vault is mint authority of fungible outputs
+            mint_output_to_user(
+                accounts.mint,
+                accounts.user_token,
+                recipe_vault,
+                output.amount,
+                token_program,
+                vault_seeds,
+            )
+        }
+        _ => Err(ErrorCode::NotEnoughAccounts.into()),
+    }
+}
+
+// transfer tokens held by a program PDA (program_signer or recipe vault) of signer_seeds
+pub fn transfer_from_program<'info>(
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    token_program: &Program<'info, Token>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_accounts = Transfer {
+        from: from.to_account_info(),
+        to: to.to_account_info(),
+        authority: authority.to_account_info(),
+    };
+    let signer = &[signer_seeds];
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    transfer(cpi_ctx, amount)?;
+    Ok(())
+}
+
+// mint fungible output, recipe vault PDA of signer_seeds is mint authority
+pub fn mint_output_to_user<'info>(
+    mint: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    recipe_vault: &AccountInfo<'info>,
+    amount: u64,
+    token_program: &Program<'info, Token>,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let cpi_accounts = MintTo {
+        mint: mint.to_account_info(),
+        to: to.to_account_info(),
+        authority: recipe_vault.to_account_info(),
+    };
+    let signer = &[signer_seeds];
+    let cpi_ctx =
+        CpiContext::new_with_signer(token_program.to_account_info(), cpi_accounts, signer);
+    mint_to(cpi_ctx, amount)?;
+    Ok(())
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum OutputKind {
+    // NFT transferred from recipe vault stock, mint = collection of the skin
+    Skin,
+    // fungible token minted by recipe vault PDA
+    Fungible,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RecipeOutput {
+    pub kind: OutputKind,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+impl RecipeOutput {
+    pub const LEN: usize = 1 + 32 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum SkinConsume {
+    // input skin is burned
+    Burn,
+    // input skin escrowed by the craft account of an open craft, else the recipe vault
+    Escrow,
+}
+
+// skin from another recipe's collection required by an upgrade recipe
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct SkinInput {
+    // Recipe mint of the input skin's collection
+    pub collection: Pubkey,
+    pub consume: SkinConsume,
+}
+
+impl SkinInput {
+    pub const LEN: usize = 32 + 1;
+}
+
+// held by user to craft a gated recipe
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub enum RecipeGate {
+    // user holds at least min_amount of mint
+    TokenBalance { mint: Pubkey, min_amount: u64 },
+    // user holds an NFT verified in collection
+    Collection { collection: Pubkey },
+}
+
+impl RecipeGate {
+    pub const LEN: usize = 1 + 32 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum RecipeMode {
+    // craft_skin delivers the skin passed by the client
+    Fixed,
+    // craft_random_skin rolls the skin from the loot table
+    Random,
+}
+
+// shelf of a recipe in client listings, stored as one byte
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RecipeCategory {
+    Uncategorized,
+    Weapon,
+    Armor,
+    Character,
+    Vehicle,
+    Cosmetic,
+    Consumable,
+    Bundle,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LootEntry {
+    // chance of entry == weight / total weight of loot table
+    pub weight: u32,
+    pub output: RecipeOutput,
+}
+
+impl LootEntry {
+    pub const LEN: usize = 4 + RecipeOutput::LEN;
+}
+
+// args of create_recipe / update_recipe, every setting of a Recipe
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RecipeSettings {
+    pub ingredient_mints: Vec<Pubkey>,
+    pub ingredient_amounts: Vec<u64>,
+    // lamports paid by user on every craft
+    pub craft_fee: u64,
+    // share of craft_fee paid to Recipe NFT holder
+    pub royalty_bps: u16,
+    // delivered to user with the skin on every craft
+    pub outputs: Vec<RecipeOutput>,
+    // Random => skin is rolled from loot_table
+    pub mode: RecipeMode,
+    pub loot_table: Vec<LootEntry>,
+    // VRF program, None => slot hashes sysvar
+    pub randomness_program: Option<Pubkey>,
+    // slots until finish_craft, 0 => craft_random_skin
+    pub reveal_delay: u64,
+    // slots after reveal until refund_craft
+    pub reveal_timeout: u64,
+    // seconds until claim_craft, 0 => instant craft
+    pub craft_duration: i64,
+    // ingredient paid to claim before craft_duration
+    pub speed_up_mint: Option<Pubkey>,
+    pub speed_up_amount: u64,
+    // share of ingredients kept by cancel_craft / refund_craft
+    pub cancel_penalty_bps: u16,
+    // share of ingredients paid back by uncraft_skin
+    pub salvage_bps: u16,
+    // skins of other recipes consumed on craft
+    pub input_skins: Vec<SkinInput>,
+    // pass held by user to craft
+    pub gate: Option<RecipeGate>,
+    // merkle root of wallets allowed to craft
+    pub allowlist_root: Option<[u8; 32]>,
+    // listing fields read by clients from the Recipe account
+    pub name: String,
+    pub description: String,
+    pub category: RecipeCategory,
+    pub sort_order: u16,
+}
+
+// randomness is read at this offset of a VRF result account (after Anchor discriminator)
+pub const VRF_RESULT_OFFSET: usize = 8;
+
+#[account]
+pub struct Recipe {
+    pub mints: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    // lamports paid by user on every craft
+    pub craft_fee: u64,
+    // share of craft_fee paid to Recipe NFT holder
+    pub royalty_bps: u16,
+    // delivered to user along with the skin
+    pub outputs: Vec<RecipeOutput>,
+    pub mode: RecipeMode,
+    // weighted output pools rolled in Random mode
+    pub loot_table: Vec<LootEntry>,
+    // VRF program owning the randomness account, None => slot hashes sysvar
+    // VRF recipes are commit-reveal only, the result account is bound to the begin_craft request
+    pub randomness_program: Option<Pubkey>,
+    // slots between begin_craft and reveal, 0 => single transaction craft_random_skin
+    pub reveal_delay: u64,
+    // slots after reveal_slot before refund_craft is allowed
+    pub reveal_timeout: u64,
+    // seconds from craft_skin until claim_craft, 0 => skin delivered by craft_skin
+    pub craft_duration: i64,
+    // ingredient paid in claim_craft to skip remaining craft_duration
+    pub speed_up_mint: Option<Pubkey>,
+    pub speed_up_amount: u64,
+    // share of escrowed ingredients kept when a pending craft is canceled or refunded
+    pub cancel_penalty_bps: u16,
+    // share of each ingredient paid back by uncraft_skin, 0 => uncraft disabled
+    pub salvage_bps: u16,
+    // skins of other recipes consumed on craft (upgrade recipes)
+    pub input_skins: Vec<SkinInput>,
+    // recipe version stamped into crafted skin metadata
+    pub revision: u32,
+    // pass held by user to craft, None => open to everyone
+    pub gate: Option<RecipeGate>,
+    // merkle root of (user, allowance) leaves, None => no allowlist
+    pub allowlist_root: Option<[u8; 32]>,
+    // bump of ["recipe", recipe_mint], checked without find_program_address
+    pub bump: u8,
+    // bump of ["vault", recipe], the vault signs with it
+    pub vault_bump: u8,
+    // layout version, 0 => created before versioning, migrate_recipe rewrites older layouts
+    pub version: u8,
+    // shown in client listings without fetching the Recipe NFT's off-chain JSON
+    pub name: String,
+    pub description: String,
+    pub category: RecipeCategory,
+    // ascending position within category
+    pub sort_order: u16,
+    // PendingCraft + CraftJob accounts open against the recipe, close_recipe needs 0
+    pub open_crafts: u32,
+}
+
+impl Recipe {
+    pub const VERSION: u8 = 1;
+    // bytes of UTF-8
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_DESCRIPTION_LEN: usize = 128;
+    pub const MAX_INGREDIENTS: usize = 5;
+    pub const MAX_OUTPUTS: usize = 4;
+    pub const MAX_LOOT_ENTRIES: usize = 8;
+    pub const MAX_INPUT_SKINS: usize = 3;
+    pub const LEN: usize = 8 // discriminator
+        + 4 + 32 * Recipe::MAX_INGREDIENTS // mints
+        + 4 + 8 * Recipe::MAX_INGREDIENTS // amounts
+        + 8 // craft_fee
+        + 2 // royalty_bps
+        + 4 + RecipeOutput::LEN * Recipe::MAX_OUTPUTS // outputs
+        + 1 // mode
+        + 4 + LootEntry::LEN * Recipe::MAX_LOOT_ENTRIES // loot_table
+        + 1 + 32 // randomness_program
+        + 8 // reveal_delay
+        + 8 // reveal_timeout
+        + 8 // craft_duration
+        + 1 + 32 // speed_up_mint
+        + 8 // speed_up_amount
+        + 2 // cancel_penalty_bps
+        + 2 // salvage_bps
+        + 4 + SkinInput::LEN * Recipe::MAX_INPUT_SKINS // input_skins
+        + 4 // revision
+        + 1 + RecipeGate::LEN // gate
+        + 1 + 32 // allowlist_root
+        + 1 // bump
+        + 1 // vault_bump
+        + 1 // version
+        + 4 + Recipe::MAX_NAME_LEN // name
+        + 4 + Recipe::MAX_DESCRIPTION_LEN // description
+        + 1 // category
+        + 2 // sort_order
+        + 4; // open_crafts
+
+    // begin_craft / timed craft_skin opened a PendingCraft / CraftJob
+    pub fn open_craft(&mut self) {
+        self.open_crafts = self.open_crafts.saturating_add(1);
+    }
+
+    // crafts opened before migrate_recipe were not counted, closing them leaves 0
+    pub fn close_craft(&mut self) {
+        self.open_crafts = self.open_crafts.saturating_sub(1);
+    }
+}
+
+// RecipeOutput of a LargeRecipe
+#[zero_copy]
+pub struct LargeRecipeOutput {
+    pub mint: Pubkey,
+    pub amount: u64,
+    // OutputKind as u8, 0 => Skin, 1 => Fungible
+    pub kind: u8,
+    pub padding: [u8; 7],
+}
+
+impl LargeRecipeOutput {
+    pub fn output(&self) -> RecipeOutput {
+        RecipeOutput {
+            kind: match self.kind {
+                0 => OutputKind::Skin,
+                _ => OutputKind::Fungible,
+            },
+            mint: self.mint,
+            amount: self.amount,
+        }
+    }
+}
+
+/*
+    zero-copy recipe for many ingredients + outputs
+    PDA of ["large_recipe", recipe_mint], read in place through AccountLoader so crafts
+    skip the Borsh decode of Recipe's Vecs, lists are filled in chunks by append_large_recipe
+    Fixed mode + untimed only: no gate, allowlist, input skins, loot table or PlayerProfile
+    entries after ingredient_count / output_count are zeroed
+    sized to what one legacy craft_large_skin transaction carries, see MAX_CRAFT_ACCOUNTS
+*/
+#[account(zero_copy)]
+pub struct LargeRecipe {
+    pub mints: [Pubkey; 3],
+    pub amounts: [u64; 3],
+    pub outputs: [LargeRecipeOutput; 4],
+    // lamports paid by user on every craft
+    pub craft_fee: u64,
+    // recipe version stamped into crafted skin metadata
+    pub revision: u32,
+    // share of craft_fee paid to Recipe NFT holder
+    pub royalty_bps: u16,
+    pub ingredient_count: u8,
+    pub output_count: u8,
+    // bump of ["large_recipe", recipe_mint], checked without find_program_address
+    pub bump: u8,
+    // bump of ["vault", large_recipe]
+    pub vault_bump: u8,
+    pub version: u8,
+    pub padding: [u8; 5],
+}
+
+impl LargeRecipe {
+    pub const VERSION: u8 = 1;
+    pub const MAX_INGREDIENTS: usize = 3;
+    pub const MAX_OUTPUTS: usize = 4;
+    pub const LEN: usize = 8 + std::mem::size_of::<LargeRecipe>();
+    /*
+      remaining_accounts of a craft fitting one legacy transaction next to the
+      CraftLargeSkin accounts, Recipe NFT holder apart from the skin owner
+        3 per ingredient, 2 per Fungible output, 4 per Skin output
+    */
+    pub const MAX_CRAFT_ACCOUNTS: usize = 9;
+
+    // remaining_accounts read by craft_large_skin for ingredients + outputs
+    pub fn craft_accounts<'a>(
+        ingredients: usize,
+        outputs: impl Iterator<Item = &'a RecipeOutput>,
+    ) -> usize {
+        3 * ingredients
+            + outputs
+                .map(|output| match output.kind {
+                    OutputKind::Skin => 4,
+                    OutputKind::Fungible => 2,
+                })
+                .sum::<usize>()
+    }
+
+    pub fn mints(&self) -> &[Pubkey] {
+        &self.mints[..self.ingredient_count as usize]
+    }
+
+    pub fn amounts(&self) -> &[u64] {
+        &self.amounts[..self.ingredient_count as usize]
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = RecipeOutput> + '_ {
+        self.outputs[..self.output_count as usize]
+            .iter()
+            .map(LargeRecipeOutput::output)
+    }
+
+    // ingredients + outputs are added after the current ones
+    pub fn append(
+        &mut self,
+        ingredient_mints: &[Pubkey],
+        ingredient_amounts: &[u64],
+        outputs: &[RecipeOutput],
+    ) -> Result<()> {
+        if ingredient_mints.len() != ingredient_amounts.len() {
+            return Err(ErrorCode::IngredientLengthMismatch.into());
+        }
+        let ingredients = self.ingredient_count as usize;
+        if ingredients + ingredient_mints.len() > LargeRecipe::MAX_INGREDIENTS {
+            return Err(ErrorCode::TooManyIngredients.into());
+        }
+        let output_count = self.output_count as usize;
+        if output_count + outputs.len() > LargeRecipe::MAX_OUTPUTS {
+            return Err(ErrorCode::TooManyOutputs.into());
+        }
+        let current: Vec<RecipeOutput> = self.outputs().collect();
+        let craft_accounts = LargeRecipe::craft_accounts(
+            ingredients + ingredient_mints.len(),
+            current.iter().chain(outputs),
+        );
+        if craft_accounts > LargeRecipe::MAX_CRAFT_ACCOUNTS {
+            return Err(ErrorCode::CraftAccountsExceeded.into());
+        }
+        verify_output_amounts(outputs)?;
+
+        let end = ingredients + ingredient_mints.len();
+        self.mints[ingredients..end].copy_from_slice(ingredient_mints);
+        self.amounts[ingredients..end].copy_from_slice(ingredient_amounts);
+        self.ingredient_count = end as u8;
+        for (slot, output) in self.outputs[output_count..].iter_mut().zip(outputs) {
+            *slot = LargeRecipeOutput {
+                mint: output.mint,
+                amount: output.amount,
+                kind: output.kind as u8,
+                padding: [0; 7],
+            };
+        }
+        self.output_count = (output_count + outputs.len()) as u8;
+        Ok(())
+    }
+
+    pub fn clear(&mut self) {
+        self.mints = [Pubkey::default(); LargeRecipe::MAX_INGREDIENTS];
+        self.amounts = [0; LargeRecipe::MAX_INGREDIENTS];
+        self.outputs = [LargeRecipeOutput {
+            mint: Pubkey::default(),
+            amount: 0,
+            kind: 0,
+            padding: [0; 7],
+        }; LargeRecipe::MAX_OUTPUTS];
+        self.ingredient_count = 0;
+        self.output_count = 0;
+    }
+}
+
+/*
+    commit-reveal craft of a Random mode recipe
+    PDA of ["pending", recipe, user], opened by begin_craft
+    closed by finish_craft (deliver) or refund_craft (timeout)
+*/
+#[account]
+pub struct PendingCraft {
+    pub user: Pubkey,
+    pub recipe: Pubkey,
+    // slot of begin_craft
+    pub commit_slot: u64,
+    // roll uses hash of first slot >= reveal_slot
+    pub reveal_slot: u64,
+    // ingredients escrowed in ATAs of this account by begin_craft, returned on refund
+    pub mints: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub bump: u8,
+    // slot hashes sysvar or VRF result account of the request, read by finish_craft
+    pub randomness: Pubkey,
+    // input skins escrowed in ATAs of this account by begin_craft, returned on refund
+    pub input_skins: Vec<Pubkey>,
+}
+
+impl PendingCraft {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // user
+        + 32 // recipe
+        + 8 // commit_slot
+        + 8 // reveal_slot
+        + 4 + 32 * Recipe::MAX_INGREDIENTS // mints
+        + 4 + 8 * Recipe::MAX_INGREDIENTS // amounts
+        + 1 // bump
+        + 32 // randomness
+        + 4 + 32 * Recipe::MAX_INPUT_SKINS; // input_skins
+}
+
+/*
+    timed craft of a recipe with craft_duration
+    PDA of ["job", recipe, user], opened by craft_skin
+    closed by claim_craft once completes_at has passed
+*/
+#[account]
+pub struct CraftJob {
+    pub user: Pubkey,
+    pub recipe: Pubkey,
+    // skin held by program_signer until claim
+    pub skin_mint: Pubkey,
+    // holder the skin was taken from by craft_skin, gets it back on cancel
+    pub skin_owner: Pubkey,
+    pub started_at: i64,
+    pub completes_at: i64,
+    // ingredients escrowed in ATAs of this account by craft_skin, returned on cancel
+    pub mints: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub bump: u8,
+    // input skins escrowed in ATAs of this account by craft_skin, returned on cancel
+    pub input_skins: Vec<Pubkey>,
+}
+
+impl CraftJob {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // user
+        + 32 // recipe
+        + 32 // skin_mint
+        + 32 // skin_owner
+        + 8 // started_at
+        + 8 // completes_at
+        + 4 + 32 * Recipe::MAX_INGREDIENTS // mints
+        + 4 + 8 * Recipe::MAX_INGREDIENTS // amounts
+        + 1 // bump
+        + 4 + 32 * Recipe::MAX_INPUT_SKINS; // input_skins
+}
+
+/*
+    salvage owed to the holder of a crafted skin
+    PDA of ["salvage", recipe, skin_mint], written when the skin is crafted
+    closed by uncraft_skin, Random mode skins have none
+*/
+#[account]
+pub struct SalvageRecord {
+    pub recipe: Pubkey,
+    pub skin_mint: Pubkey,
+    // salvage_bps share of each ingredient the craft paid into the recipe vault
+    pub mints: Vec<Pubkey>,
+    pub amounts: Vec<u64>,
+    pub bump: u8,
+}
+
+impl SalvageRecord {
+    pub const LEN: usize = 8 // discriminator
+        + 32 // recipe
+        + 32 // skin_mint
+        + 4 + 32 * Recipe::MAX_INGREDIENTS // mints
+        + 4 + 8 * Recipe::MAX_INGREDIENTS // amounts
+        + 1; // bump
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct RecipeCraftCount {
+    pub recipe: Pubkey,
+    pub crafts: u32,
+}
+
+impl RecipeCraftCount {
+    pub const LEN: usize = 32 + 4;
+}
+
+/*
+    crafting statistics of a user
+    PDA of ["profile", user], created by the user's first craft_skin
+*/
+#[account]
+pub struct PlayerProfile {
+    pub user: Pubkey,
+    pub total_crafts: u64,
+    pub first_craft_at: i64,
+    pub last_craft_at: i64,
+    // craft count of every recipe the user has crafted
+    pub recipes: Vec<RecipeCraftCount>,
+    pub bump: u8,
+}
+
+impl PlayerProfile {
+    pub fn space(recipes: usize) -> usize {
+        8 // discriminator
+        + 32 // user
+        + 8 // total_crafts
+        + 8 // first_craft_at
+        + 8 // last_craft_at
+        + 4 + RecipeCraftCount::LEN * recipes // recipes
+        + 1 // bump
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct LootRoll {
+    pub roll: u64,
+    // index into Recipe loot_table
+    pub entry: u8,
+}
+
+// emitted on every successful craft
+#[event]
+pub struct CraftEvent {
+    pub user: Pubkey,
+    pub recipe: Pubkey,
+    // fixed skin, or mint of rolled loot entry
+    pub skin_mint: Pubkey,
+    // set for Random mode recipes
+    pub roll: Option<LootRoll>,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("Token account requires balance of 1")]
+    TokenAmountInvalid,
+
+    #[msg("Token account owner is invalid")]
+    TokenOwnerInvalid,
+
+    #[msg("Token account mint != expected Recipe mint")]
+    TokenMintInvalid,
+
+    #[msg("Metadata account not initialized")]
+    NotInitialized,
+
+    #[msg("Wrong creators or already signed for program signer")]
+    WrongCreators,
+
+    #[msg("Not enough tokens")]
+    NotEnoughToken,
+
+    #[msg("Derived key is invalid PDA")]
+    DerivedKeyInvalid,
+
+    #[msg("Collection is unverified")]
+    CollectionUnverified,
+
+    #[msg("Collect key is not expected value")]
+    CollectionKeyInvalid,
+
+    #[msg("User token account mint != user mint account")]
+    TokenMintMismatch,
+
+    #[msg("Escrow token account not initialized")]
+    EscrowNotInitialized,
+
+    #[msg("Royalty basis points must be <= 10000")]
+    RoyaltyInvalid,
+
+    #[msg("Recipe holder != owner of Recipe token account")]
+    RecipeHolderInvalid,
+
+    #[msg("Admin != program manager admin")]
+    AdminInvalid,
+
+    #[msg("Ingredient mints and amounts have different lengths")]
+    IngredientLengthMismatch,
+
+    #[msg("Too many ingredients for Recipe account")]
+    TooManyIngredients,
+
+    #[msg("Too many outputs for Recipe account")]
+    TooManyOutputs,
+
+    #[msg("Output amount is invalid for output kind")]
+    OutputAmountInvalid,
+
+    #[msg("Too many loot table entries for Recipe account")]
+    TooManyLootEntries,
+
+    #[msg("Loot table does not match recipe mode")]
+    LootTableInvalid,
+
+    #[msg("Instruction does not support this recipe mode")]
+    RecipeModeInvalid,
+
+    #[msg("Randomness account is not slot hashes or owned by randomness program")]
+    RandomnessAccountInvalid,
+
+    #[msg("Randomness account has no randomness")]
+    RandomnessUnavailable,
+
+    #[msg("Not enough accounts for recipe")]
+    NotEnoughAccounts,
+
+    #[msg("Reveal window is invalid for recipe mode or randomness source")]
+    RevealWindowInvalid,
+
+    #[msg("Recipe requires begin_craft / finish_craft")]
+    CommitRevealRequired,
+
+    #[msg("Reveal slot has not passed")]
+    CraftNotReady,
+
+    #[msg("Pending craft has not timed out")]
+    CraftNotExpired,
+
+    #[msg("Expected loot entry != rolled loot entry")]
+    LootEntryMismatch,
+
+    #[msg("Craft duration or speed up is invalid for recipe")]
+    CraftDurationInvalid,
+
+    #[msg("Account is not a pending craft of user for recipe")]
+    PendingCraftInvalid,
+
+    #[msg("Pending craft is revealed and can no longer be canceled")]
+    CraftAlreadyRevealed,
+
+    #[msg("Penalty basis points must be <= 10000")]
+    PenaltyInvalid,
+
+    #[msg("Recipe does not allow uncrafting skins")]
+    UncraftDisabled,
+
+    #[msg("Too many input skins for Recipe account")]
+    TooManyInputSkins,
+
+    #[msg("User and allowance are not in recipe allowlist")]
+    NotAllowlisted,
+
+    #[msg("User has crafted recipe allowance times")]
+    AllowanceExceeded,
+
+    #[msg("Batch quantity must be 1..=MAX_BATCH_QUANTITY for an untimed recipe")]
+    BatchQuantityInvalid,
+
+    #[msg("Account has an older layout, run migrate_recipe / migrate_manager")]
+    AccountNotMigrated,
+
+    #[msg("Account version is newer than this program")]
+    AccountVersionUnknown,
+
+    #[msg("Recipe name exceeds Recipe::MAX_NAME_LEN bytes")]
+    RecipeNameTooLong,
+
+    #[msg("Recipe description exceeds Recipe::MAX_DESCRIPTION_LEN bytes")]
+    RecipeDescriptionTooLong,
+
+    #[msg("VRF result of the request is already fulfilled")]
+    RandomnessAlreadyFulfilled,
+
+    #[msg("Recipe has open pending crafts or craft jobs")]
+    RecipeHasOpenCrafts,
+
+    #[msg("LargeRecipe craft needs more accounts than one transaction carries")]
+    CraftAccountsExceeded,
 }
@@ -0,0 +1,144 @@
+// cargo test-bpf --test add_skin
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use craft_skins::utils::ErrorCode;
+use mpl_token_metadata::state::Collection;
+use solana_program_test::{tokio, BanksClientError, ProgramTest};
+use solana_sdk::signature::{Keypair, Signer};
+
+/*
+    add_skin of the skin returned by add_skin_nft to owner's recipe
+    recipe_created == false => Recipe account never created
+*/
+async fn add_skin(
+    recipe_created: bool,
+    add_skin_nft: impl FnOnce(&mut ProgramTest, &Keypair, &Nft) -> Nft,
+) -> Result<(), BanksClientError> {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let skin_nft = add_skin_nft(&mut program_test, &owner, &recipe_nft);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    if recipe_created {
+        let instruction = create_recipe_ix(&owner.pubkey(), &recipe_nft, vec![], vec![]);
+        process(&mut banks_client, &owner, &[instruction], &[])
+            .await
+            .unwrap();
+    }
+    let instruction = add_skin_ix(&owner.pubkey(), &recipe_nft, &skin_nft);
+    process(&mut banks_client, &owner, &[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn add_skin_of_recipe_collection() {
+    add_skin(true, |program_test, owner, recipe_nft| {
+        add_nft(
+            program_test,
+            &owner.pubkey(),
+            &owner.pubkey(),
+            Some(recipe_nft.mint),
+        )
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn recipe_not_created() {
+    let result = add_skin(false, |program_test, owner, recipe_nft| {
+        add_nft(
+            program_test,
+            &owner.pubkey(),
+            &owner.pubkey(),
+            Some(recipe_nft.mint),
+        )
+    })
+    .await;
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+}
+
+#[tokio::test]
+async fn skin_collection_unverified() {
+    let result = add_skin(true, |program_test, owner, recipe_nft| {
+        add_nft_with(program_test, &owner.pubkey(), |metadata| {
+            metadata.collection = Some(Collection {
+                verified: false,
+                key: recipe_nft.mint,
+            });
+        })
+    })
+    .await;
+    assert_error(result, ErrorCode::CollectionUnverified);
+}
+
+#[tokio::test]
+async fn skin_collection_key_invalid() {
+    let result = add_skin(true, |program_test, owner, _| {
+        // verified member of another owner's collection
+        let other_recipe = add_nft(program_test, &owner.pubkey(), &owner.pubkey(), None);
+        add_nft(
+            program_test,
+            &owner.pubkey(),
+            &owner.pubkey(),
+            Some(other_recipe.mint),
+        )
+    })
+    .await;
+    assert_error(result, ErrorCode::CollectionKeyInvalid);
+}
+
+#[tokio::test]
+async fn skin_token_amount_invalid() {
+    let result = add_skin(true, |program_test, owner, _| {
+        // owner's skin token account is empty
+        let mint = add_mint(program_test, &owner.pubkey(), 0);
+        let token = add_token_account(program_test, &mint, &owner.pubkey(), 0);
+        Nft {
+            mint,
+            token,
+            metadata: metadata_pda(&mint),
+            edition: edition_pda(&mint),
+        }
+    })
+    .await;
+    assert_error(result, ErrorCode::TokenAmountInvalid);
+}
+
+#[tokio::test]
+async fn skin_token_mint_invalid() {
+    let result = add_skin(true, |program_test, owner, recipe_nft| {
+        let skin_nft = add_nft(
+            program_test,
+            &owner.pubkey(),
+            &owner.pubkey(),
+            Some(recipe_nft.mint),
+        );
+        // token account holds the Recipe NFT instead of the skin
+        Nft {
+            token: recipe_nft.token,
+            ..skin_nft
+        }
+    })
+    .await;
+    assert_error(result, ErrorCode::TokenMintInvalid);
+}
+
+#[tokio::test]
+async fn skin_metadata_not_initialized() {
+    let result = add_skin(true, |program_test, owner, _| {
+        let mint = add_mint(program_test, &owner.pubkey(), 1);
+        let token = add_token_account(program_test, &mint, &owner.pubkey(), 1);
+        Nft {
+            mint,
+            token,
+            metadata: metadata_pda(&mint),
+            edition: edition_pda(&mint),
+        }
+    })
+    .await;
+    assert_error(result, ErrorCode::NotInitialized);
+}
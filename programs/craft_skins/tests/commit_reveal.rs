@@ -0,0 +1,462 @@
+// cargo test-bpf --test commit_reveal
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use craft_skins::utils::{
+    vrf_result_address, ErrorCode, LootEntry, OutputKind, PendingCraft, PlayerProfile, Recipe,
+    RecipeMode, RecipeOutput, RecipeSettings,
+};
+use solana_program::keccak;
+use solana_program_test::{tokio, BanksClientError, ProgramTestContext};
+use solana_sdk::{
+    account::{Account, AccountSharedData},
+    hash::Hash,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    slot_hashes::{SlotHashes, MAX_ENTRIES},
+    sysvar::slot_hashes,
+};
+
+const INGREDIENT_AMOUNT: u64 = 10;
+const REVEAL_TIMEOUT: u64 = 512;
+const CANCEL_PENALTY_BPS: u16 = 1_000;
+
+// stand-in VRF program, its result accounts are written straight into the bank
+fn vrf_program() -> Pubkey {
+    Pubkey::new_from_array([7; 32])
+}
+
+// owner's Random mode recipe rolling a single fungible loot entry, user holds the ingredient
+struct Reveal {
+    context: ProgramTestContext,
+    admin: Keypair,
+    owner: Keypair,
+    user: Keypair,
+    recipe_nft: Nft,
+    ingredient: Pubkey,
+    loot_mint: Pubkey,
+}
+
+// reveal 2 slots after begin_craft, args edited by edit(args, user) before create_recipe
+async fn reveal(edit: impl FnOnce(&mut RecipeSettings, &Pubkey)) -> Reveal {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let owner = add_wallet(&mut program_test);
+    let user = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let ingredient = add_mint(&mut program_test, &admin.pubkey(), INGREDIENT_AMOUNT);
+    add_token_account(
+        &mut program_test,
+        &ingredient,
+        &user.pubkey(),
+        INGREDIENT_AMOUNT,
+    );
+    let recipe_vault = recipe_vault_pda(&recipe_pda(&recipe_nft.mint));
+    let loot_mint = add_mint(&mut program_test, &recipe_vault, 0);
+
+    let mut args = recipe_args(vec![ingredient], vec![INGREDIENT_AMOUNT]);
+    args.mode = RecipeMode::Random;
+    args.loot_table = vec![LootEntry {
+        weight: 1,
+        output: RecipeOutput {
+            kind: OutputKind::Fungible,
+            mint: loot_mint,
+            amount: 1,
+        },
+    }];
+    args.reveal_delay = 2;
+    args.reveal_timeout = REVEAL_TIMEOUT;
+    args.cancel_penalty_bps = CANCEL_PENALTY_BPS;
+    edit(&mut args, &user.pubkey());
+
+    let mut context = program_test.start_with_context().await;
+    process(
+        &mut context.banks_client,
+        &admin,
+        &[initialize_ix(&admin.pubkey())],
+        &[],
+    )
+    .await
+    .unwrap();
+    process(
+        &mut context.banks_client,
+        &owner,
+        &[create_recipe_ix_with(&owner.pubkey(), &recipe_nft, args)],
+        &[],
+    )
+    .await
+    .unwrap();
+    Reveal {
+        context,
+        admin,
+        owner,
+        user,
+        recipe_nft,
+        ingredient,
+        loot_mint,
+    }
+}
+
+fn vrf_recipe(args: &mut RecipeSettings, _user: &Pubkey) {
+    args.randomness_program = Some(vrf_program());
+}
+
+impl Reveal {
+    fn recipe(&self) -> Pubkey {
+        recipe_pda(&self.recipe_nft.mint)
+    }
+
+    fn vrf_result(&self, request: u64) -> Pubkey {
+        vrf_result_address(&vrf_program(), &self.recipe(), &self.user.pubkey(), request)
+    }
+
+    async fn begin(&mut self, randomness: Pubkey, request: u64) -> Result<(), BanksClientError> {
+        self.begin_with_allowance(randomness, request, 0).await
+    }
+
+    async fn begin_with_allowance(
+        &mut self,
+        randomness: Pubkey,
+        request: u64,
+        allowance: u32,
+    ) -> Result<(), BanksClientError> {
+        let instruction = begin_craft_ix(
+            &self.owner.pubkey(),
+            &self.user.pubkey(),
+            &self.admin.pubkey(),
+            &self.recipe_nft,
+            &randomness,
+            request,
+            allowance,
+            &[self.ingredient],
+        );
+        process(
+            &mut self.context.banks_client,
+            &self.user,
+            &[instruction],
+            &[],
+        )
+        .await
+    }
+
+    // anyone finishes, owner pays
+    async fn finish(&mut self, randomness: Pubkey) -> Result<(), BanksClientError> {
+        let instruction = finish_craft_ix(
+            &self.owner.pubkey(),
+            &self.user.pubkey(),
+            &self.recipe_nft.mint,
+            &randomness,
+            0,
+            &self.loot_mint,
+            &[self.ingredient],
+        );
+        process(
+            &mut self.context.banks_client,
+            &self.owner,
+            &[instruction],
+            &[],
+        )
+        .await
+    }
+
+    // anyone refunds, owner pays the fee
+    async fn refund(&mut self) -> Result<(), BanksClientError> {
+        let instruction = refund_craft_ix(
+            &self.owner.pubkey(),
+            &self.user.pubkey(),
+            &self.recipe_nft.mint,
+            &[self.ingredient],
+        );
+        process(
+            &mut self.context.banks_client,
+            &self.owner,
+            &[instruction],
+            &[],
+        )
+        .await
+    }
+
+    async fn pending(&mut self) -> PendingCraft {
+        let pending = pending_pda(&self.recipe(), &self.user.pubkey());
+        get_account(&mut self.context.banks_client, &pending).await
+    }
+
+    // VRF result account, randomness after the Anchor discriminator (zero => unfulfilled)
+    fn set_vrf_result(&mut self, key: &Pubkey, randomness: [u8; 32]) {
+        let mut data = vec![0; 8];
+        data.extend_from_slice(&randomness);
+        let account = Account {
+            lamports: Rent::default().minimum_balance(data.len()),
+            data,
+            owner: vrf_program(),
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context
+            .set_account(key, &AccountSharedData::from(account));
+    }
+
+    // one slot past reveal_slot, slot hashes then hold the hash of reveal_slot
+    async fn warp_past_reveal(&mut self) {
+        let reveal_slot = self.pending().await.reveal_slot;
+        self.context.warp_to_slot(reveal_slot).unwrap();
+        self.context.warp_to_slot(reveal_slot + 1).unwrap();
+    }
+
+    async fn loot_amount(&mut self) -> u64 {
+        let user_loot = get_associated_token_address(&self.user.pubkey(), &self.loot_mint);
+        token_amount(&mut self.context.banks_client, &user_loot).await
+    }
+}
+
+#[tokio::test]
+async fn slot_hashes_craft_reveals_recorded_sysvar() {
+    let mut reveal = reveal(|_, _| {}).await;
+    reveal.begin(slot_hashes::id(), 0).await.unwrap();
+    assert_eq!(reveal.pending().await.randomness, slot_hashes::id());
+
+    reveal.warp_past_reveal().await;
+    let other = reveal.vrf_result(0);
+    reveal.set_vrf_result(&other, [1; 32]);
+    assert_error(
+        reveal.finish(other).await,
+        ErrorCode::RandomnessAccountInvalid,
+    );
+
+    reveal.finish(slot_hashes::id()).await.unwrap();
+    assert_eq!(reveal.loot_amount().await, 1);
+}
+
+#[tokio::test]
+async fn slot_hashes_craft_expires_once_reveal_slot_is_evicted() {
+    let mut reveal = reveal(|_, _| {}).await;
+    reveal.begin(slot_hashes::id(), 0).await.unwrap();
+    let reveal_slot = reveal.pending().await.reveal_slot;
+
+    // MAX_ENTRIES slots on, the oldest kept hash moves every slot and would be picked by waiting
+    let slot = reveal_slot + MAX_ENTRIES as u64 + 1;
+    reveal.context.warp_to_slot(slot).unwrap();
+    let entries: Vec<_> = (1..=MAX_ENTRIES as u64)
+        .map(|age| (slot - age, Hash::new_unique()))
+        .collect();
+    reveal.context.set_sysvar(&SlotHashes::new(&entries));
+    assert_error(
+        reveal.finish(slot_hashes::id()).await,
+        ErrorCode::RandomnessUnavailable,
+    );
+
+    // refund_craft is left, keeping cancel_penalty_bps
+    reveal.refund().await.unwrap();
+    let pending = pending_pda(&reveal.recipe(), &reveal.user.pubkey());
+    let banks_client = &mut reveal.context.banks_client;
+    assert!(banks_client.get_account(pending).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn begin_craft_rejects_vrf_result_of_other_request() {
+    let mut reveal = reveal(vrf_recipe).await;
+
+    let other_request = reveal.vrf_result(1);
+    assert_error(
+        reveal.begin(other_request, 0).await,
+        ErrorCode::RandomnessAccountInvalid,
+    );
+    assert_error(
+        reveal.begin(slot_hashes::id(), 0).await,
+        ErrorCode::RandomnessAccountInvalid,
+    );
+}
+
+#[tokio::test]
+async fn begin_craft_rejects_fulfilled_vrf_result() {
+    let mut reveal = reveal(vrf_recipe).await;
+
+    // result already known, user would only commit to a good roll
+    let result = reveal.vrf_result(0);
+    reveal.set_vrf_result(&result, [1; 32]);
+    assert_error(
+        reveal.begin(result, 0).await,
+        ErrorCode::RandomnessAlreadyFulfilled,
+    );
+
+    // requested but unfulfilled result accounts are accepted
+    reveal.set_vrf_result(&result, [0; 32]);
+    reveal.begin(result, 0).await.unwrap();
+    assert_eq!(reveal.pending().await.randomness, result);
+}
+
+#[tokio::test]
+async fn finish_craft_reads_vrf_result_of_request() {
+    let mut reveal = reveal(vrf_recipe).await;
+    let result = reveal.vrf_result(0);
+    reveal.begin(result, 0).await.unwrap();
+    reveal.warp_past_reveal().await;
+
+    // unfulfilled result
+    reveal.set_vrf_result(&result, [0; 32]);
+    assert_error(
+        reveal.finish(result).await,
+        ErrorCode::RandomnessUnavailable,
+    );
+
+    // fulfilled result of another request
+    let other_request = reveal.vrf_result(1);
+    reveal.set_vrf_result(&other_request, [1; 32]);
+    assert_error(
+        reveal.finish(other_request).await,
+        ErrorCode::RandomnessAccountInvalid,
+    );
+
+    reveal.set_vrf_result(&result, [2; 32]);
+    reveal.finish(result).await.unwrap();
+    assert_eq!(reveal.loot_amount().await, 1);
+}
+
+#[tokio::test]
+async fn refund_after_timeout_keeps_cancel_penalty() {
+    let mut reveal = reveal(|_, _| {}).await;
+    reveal.begin(slot_hashes::id(), 0).await.unwrap();
+    let reveal_slot = reveal.pending().await.reveal_slot;
+
+    // roll is public once reveal_slot passes, refund stays closed until reveal_timeout
+    reveal.warp_past_reveal().await;
+    assert_error(reveal.refund().await, ErrorCode::CraftNotExpired);
+
+    // waiting out a bad roll costs cancel_penalty_bps of the ingredients
+    reveal
+        .context
+        .warp_to_slot(reveal_slot + REVEAL_TIMEOUT + 1)
+        .unwrap();
+    reveal.refund().await.unwrap();
+
+    let penalty = INGREDIENT_AMOUNT * CANCEL_PENALTY_BPS as u64 / 10_000;
+    let user_token = get_associated_token_address(&reveal.user.pubkey(), &reveal.ingredient);
+    let recipe_vault = recipe_vault_pda(&reveal.recipe());
+    let vault_token = get_associated_token_address(&recipe_vault, &reveal.ingredient);
+    let pending = pending_pda(&reveal.recipe(), &reveal.user.pubkey());
+    let escrow_token = get_associated_token_address(&pending, &reveal.ingredient);
+    let banks_client = &mut reveal.context.banks_client;
+    assert_eq!(
+        token_amount(banks_client, &user_token).await,
+        INGREDIENT_AMOUNT - penalty
+    );
+    assert_eq!(token_amount(banks_client, &vault_token).await, penalty);
+    assert_eq!(token_amount(banks_client, &escrow_token).await, 0);
+    assert!(banks_client.get_account(pending).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn pending_escrow_out_of_reach_of_withdraw_vault() {
+    let mut reveal = reveal(|_, _| {}).await;
+    reveal.begin(slot_hashes::id(), 0).await.unwrap();
+
+    // PendingCraft holds the escrow, the recipe vault has nothing to withdraw yet
+    let pending = pending_pda(&reveal.recipe(), &reveal.user.pubkey());
+    let escrow_token = get_associated_token_address(&pending, &reveal.ingredient);
+    let recipe_vault = recipe_vault_pda(&reveal.recipe());
+    let vault_token = get_associated_token_address(&recipe_vault, &reveal.ingredient);
+    let user_token = get_associated_token_address(&reveal.user.pubkey(), &reveal.ingredient);
+    assert_eq!(
+        token_amount(&mut reveal.context.banks_client, &escrow_token).await,
+        INGREDIENT_AMOUNT
+    );
+    let withdraw = withdraw_vault_ix(
+        &reveal.owner.pubkey(),
+        &reveal.recipe(),
+        &reveal.recipe_nft,
+        &reveal.ingredient,
+        &user_token,
+        INGREDIENT_AMOUNT,
+    );
+    let result = process(
+        &mut reveal.context.banks_client,
+        &reveal.owner,
+        &[withdraw],
+        &[],
+    )
+    .await;
+    assert_error(result, anchor_lang::error::ErrorCode::AccountNotInitialized);
+
+    // finish_craft settles the escrow into the vault
+    reveal.warp_past_reveal().await;
+    reveal.finish(slot_hashes::id()).await.unwrap();
+    let banks_client = &mut reveal.context.banks_client;
+    assert_eq!(token_amount(banks_client, &escrow_token).await, 0);
+    assert_eq!(
+        token_amount(banks_client, &vault_token).await,
+        INGREDIENT_AMOUNT
+    );
+}
+
+#[tokio::test]
+async fn close_recipe_waits_for_pending_craft() {
+    let mut reveal = reveal(|_, _| {}).await;
+    reveal.begin(slot_hashes::id(), 0).await.unwrap();
+    let recipe = reveal.recipe();
+    let opened: Recipe = get_account(&mut reveal.context.banks_client, &recipe).await;
+    assert_eq!(opened.open_crafts, 1);
+
+    // refund_craft reads the Recipe, it stays until the PendingCraft is closed
+    let close = close_recipe_ix(&reveal.owner.pubkey(), &reveal.recipe_nft);
+    let result = process(
+        &mut reveal.context.banks_client,
+        &reveal.owner,
+        std::slice::from_ref(&close),
+        &[],
+    )
+    .await;
+    assert_error(result, ErrorCode::RecipeHasOpenCrafts);
+
+    let reveal_slot = reveal.pending().await.reveal_slot;
+    reveal
+        .context
+        .warp_to_slot(reveal_slot + REVEAL_TIMEOUT + 1)
+        .unwrap();
+    reveal.refund().await.unwrap();
+    let refunded: Recipe = get_account(&mut reveal.context.banks_client, &recipe).await;
+    assert_eq!(refunded.open_crafts, 0);
+
+    process(
+        &mut reveal.context.banks_client,
+        &reveal.owner,
+        &[close],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert!(reveal
+        .context
+        .banks_client
+        .get_account(recipe)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+#[tokio::test]
+async fn begin_craft_spends_allowlist_allowance() {
+    // single leaf allowlist of (user, 1 craft)
+    let mut reveal = reveal(|args, user| {
+        let leaf = keccak::hashv(&[&[0x00], user.as_ref(), &1u32.to_le_bytes()]).0;
+        args.allowlist_root = Some(leaf);
+    })
+    .await;
+
+    assert_error(
+        reveal.begin_with_allowance(slot_hashes::id(), 0, 2).await,
+        ErrorCode::NotAllowlisted,
+    );
+    reveal
+        .begin_with_allowance(slot_hashes::id(), 0, 1)
+        .await
+        .unwrap();
+
+    let profile = profile_pda(&reveal.user.pubkey());
+    let profile: PlayerProfile = get_account(&mut reveal.context.banks_client, &profile).await;
+    assert_eq!(profile.recipes[0].recipe, reveal.recipe());
+    assert_eq!(profile.recipes[0].crafts, 1);
+}
@@ -0,0 +1,1106 @@
+/*
+    shared fixtures for the solana-program-test suites
+
+    craft_skins runs as BPF (target/deploy/craft_skins.so, built by `cargo test-bpf`)
+      cargo build-bpf --manifest-path programs/craft_skins/Cargo.toml
+      cargo test-bpf --manifest-path programs/craft_skins/Cargo.toml
+    suites are gated on feature test-bpf, plain `cargo test` compiles them to 0 tests,
+    program_test() panics when craft_skins.so is not built
+    token metadata runs natively (processor! of the mpl-token-metadata crate), no .so to fetch
+    NFT metadata + master edition accounts are written straight into the test bank,
+    Recipe / Manager / escrow accounts are created by the program itself
+*/
+#![allow(dead_code)]
+
+use anchor_lang::{
+    AccountDeserialize, AnchorDeserialize, AnchorSerialize, InstructionData, ToAccountMetas,
+};
+use anchor_spl::associated_token::get_associated_token_address;
+use craft_skins::utils::{LargeRecipe, RecipeCategory, RecipeMode, RecipeOutput, RecipeSettings};
+use mpl_token_metadata::state::{
+    Collection, Creator, Data, Key, MasterEditionV2, Metadata, TokenStandard, EDITION,
+    MAX_MASTER_EDITION_LEN, MAX_METADATA_LEN, PREFIX,
+};
+use solana_program_test::{processor, BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+pub const LAMPORTS: u64 = 100_000_000_000;
+
+pub fn program_test() -> ProgramTest {
+    // no native processor, account creation inside craft_skins needs the BPF loader
+    if solana_program_test::find_file("craft_skins.so").is_none() {
+        panic!("craft_skins.so not found, build it with `cargo build-bpf` or run `cargo test-bpf`");
+    }
+    let mut program_test = ProgramTest::new("craft_skins", craft_skins::id(), None);
+    program_test.prefer_bpf(true);
+    // update_metadata_accounts_v2 CPI of craft_skin, builtin so prefer_bpf does not look for a .so
+    program_test.add_builtin_program(
+        "mpl_token_metadata",
+        mpl_token_metadata::id(),
+        processor!(process_token_metadata).unwrap(),
+    );
+    program_test
+}
+
+// mpl process_instruction ties the slice borrow to the account lifetime, processor! wants both free
+fn process_token_metadata(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    // leaked copy lives as long as the accounts, a few AccountInfo per CPI in tests
+    let accounts = Box::leak(accounts.to_vec().into_boxed_slice());
+    // registered at mpl_token_metadata::id() only
+    mpl_token_metadata::processor::process_instruction(&mpl_token_metadata::ID, accounts, input)
+}
+
+pub fn manager_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"manager"], &craft_skins::id()).0
+}
+
+pub fn signer_pda() -> Pubkey {
+    Pubkey::find_program_address(&[b"signer"], &craft_skins::id()).0
+}
+
+pub fn recipe_pda(recipe_mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"recipe", recipe_mint.as_ref()], &craft_skins::id()).0
+}
+
+pub fn large_recipe_pda(recipe_mint: &Pubkey) -> Pubkey {
+    let seeds = &[b"large_recipe".as_ref(), recipe_mint.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+// recipe is the Recipe or LargeRecipe PDA
+pub fn recipe_vault_pda(recipe: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"vault", recipe.as_ref()], &craft_skins::id()).0
+}
+
+pub fn pending_pda(recipe: &Pubkey, user: &Pubkey) -> Pubkey {
+    let seeds = &[b"pending".as_ref(), recipe.as_ref(), user.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+pub fn job_pda(recipe: &Pubkey, user: &Pubkey) -> Pubkey {
+    let seeds = &[b"job".as_ref(), recipe.as_ref(), user.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+pub fn salvage_pda(recipe: &Pubkey, skin_mint: &Pubkey) -> Pubkey {
+    let seeds = &[b"salvage".as_ref(), recipe.as_ref(), skin_mint.as_ref()];
+    Pubkey::find_program_address(seeds, &craft_skins::id()).0
+}
+
+pub fn profile_pda(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"profile", user.as_ref()], &craft_skins::id()).0
+}
+
+pub fn metadata_pda(mint: &Pubkey) -> Pubkey {
+    let program_id = mpl_token_metadata::id();
+    let seeds = &[PREFIX.as_bytes(), program_id.as_ref(), mint.as_ref()];
+    Pubkey::find_program_address(seeds, &program_id).0
+}
+
+pub fn edition_pda(mint: &Pubkey) -> Pubkey {
+    let program_id = mpl_token_metadata::id();
+    let seeds = &[
+        PREFIX.as_bytes(),
+        program_id.as_ref(),
+        mint.as_ref(),
+        EDITION.as_bytes(),
+    ];
+    Pubkey::find_program_address(seeds, &program_id).0
+}
+
+// NFT accounts, token account is the ATA of the NFT holder
+pub struct Nft {
+    pub mint: Pubkey,
+    pub token: Pubkey,
+    pub metadata: Pubkey,
+    pub edition: Pubkey,
+}
+
+fn rent_exempt(owner: Pubkey, data: Vec<u8>) -> Account {
+    Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+// account of craft_skins written straight into the bank (layouts of older program versions)
+pub fn add_program_account(program_test: &mut ProgramTest, key: Pubkey, data: Vec<u8>) {
+    program_test.add_account(key, rent_exempt(craft_skins::id(), data));
+}
+
+pub fn add_wallet(program_test: &mut ProgramTest) -> Keypair {
+    let wallet = Keypair::new();
+    program_test.add_account(
+        wallet.pubkey(),
+        Account {
+            lamports: LAMPORTS,
+            owner: system_program::id(),
+            ..Account::default()
+        },
+    );
+    wallet
+}
+
+pub fn add_mint(program_test: &mut ProgramTest, authority: &Pubkey, supply: u64) -> Pubkey {
+    let mint = Pubkey::new_unique();
+    let mut data = vec![0; Mint::LEN];
+    Mint::pack(
+        Mint {
+            mint_authority: Some(*authority).into(),
+            supply,
+            decimals: 0,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        },
+        &mut data,
+    )
+    .unwrap();
+    program_test.add_account(mint, rent_exempt(spl_token::id(), data));
+    mint
+}
+
+pub fn add_token_account(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Pubkey {
+    let token = get_associated_token_address(owner, mint);
+    let mut data = vec![0; TokenAccount::LEN];
+    TokenAccount::pack(
+        TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: AccountState::Initialized,
+            ..TokenAccount::default()
+        },
+        &mut data,
+    )
+    .unwrap();
+    program_test.add_account(token, rent_exempt(spl_token::id(), data));
+    token
+}
+
+/*
+    NFT held by owner with a verified creator
+      collection => verified member of collection (skins)
+      None       => unique master edition (Recipe NFTs)
+*/
+pub fn add_nft(
+    program_test: &mut ProgramTest,
+    owner: &Pubkey,
+    update_authority: &Pubkey,
+    collection: Option<Pubkey>,
+) -> Nft {
+    add_nft_with(program_test, owner, |metadata| {
+        metadata.update_authority = *update_authority;
+        metadata.collection = collection.map(|key| Collection {
+            verified: true,
+            key,
+        });
+    })
+}
+
+// NFT held by owner, metadata edited before it is written (invalid skins, long uris)
+pub fn add_nft_with(
+    program_test: &mut ProgramTest,
+    owner: &Pubkey,
+    edit: impl FnOnce(&mut Metadata),
+) -> Nft {
+    let mint = add_mint(program_test, owner, 1);
+    let token = add_token_account(program_test, &mint, owner, 1);
+
+    let metadata = metadata_pda(&mint);
+    let mut metadata_account = Metadata {
+        key: Key::MetadataV1,
+        update_authority: *owner,
+        mint,
+        data: Data {
+            name: "skin".to_string(),
+            symbol: "SKIN".to_string(),
+            uri: "https://example.com/skin.json".to_string(),
+            seller_fee_basis_points: 0,
+            creators: Some(vec![Creator {
+                address: *owner,
+                verified: true,
+                share: 100,
+            }]),
+        },
+        primary_sale_happened: false,
+        is_mutable: true,
+        edition_nonce: None,
+        token_standard: Some(TokenStandard::NonFungible),
+        collection: None,
+        uses: None,
+    };
+    edit(&mut metadata_account);
+    let mut data = metadata_account.try_to_vec().unwrap();
+    data.resize(MAX_METADATA_LEN, 0);
+    program_test.add_account(metadata, rent_exempt(mpl_token_metadata::id(), data));
+
+    let edition = edition_pda(&mint);
+    let mut data = MasterEditionV2 {
+        key: Key::MasterEditionV2,
+        supply: 0,
+        max_supply: Some(0),
+    }
+    .try_to_vec()
+    .unwrap();
+    data.resize(MAX_MASTER_EDITION_LEN, 0);
+    program_test.add_account(edition, rent_exempt(mpl_token_metadata::id(), data));
+
+    Nft {
+        mint,
+        token,
+        metadata,
+        edition,
+    }
+}
+
+pub fn initialize_ix(manager: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::Initialize {
+            manager: *manager,
+            program_manager: manager_pda(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::Initialize {}.data(),
+    }
+}
+
+// Fixed mode recipe, no fee, outputs, timing or gates
+pub fn create_recipe_ix(
+    owner: &Pubkey,
+    recipe_nft: &Nft,
+    ingredient_mints: Vec<Pubkey>,
+    ingredient_amounts: Vec<u64>,
+) -> Instruction {
+    create_recipe_ix_with(
+        owner,
+        recipe_nft,
+        recipe_args(ingredient_mints, ingredient_amounts),
+    )
+}
+
+// settings of a Fixed mode recipe, edited by tests before create_recipe_ix_with
+pub fn recipe_args(ingredient_mints: Vec<Pubkey>, ingredient_amounts: Vec<u64>) -> RecipeSettings {
+    RecipeSettings {
+        ingredient_mints,
+        ingredient_amounts,
+        craft_fee: 0,
+        royalty_bps: 0,
+        outputs: vec![],
+        mode: RecipeMode::Fixed,
+        loot_table: vec![],
+        randomness_program: None,
+        reveal_delay: 0,
+        reveal_timeout: 0,
+        craft_duration: 0,
+        speed_up_mint: None,
+        speed_up_amount: 0,
+        cancel_penalty_bps: 0,
+        salvage_bps: 0,
+        input_skins: vec![],
+        gate: None,
+        allowlist_root: None,
+        name: String::new(),
+        description: String::new(),
+        category: RecipeCategory::Uncategorized,
+        sort_order: 0,
+    }
+}
+
+pub fn create_recipe_ix_with(
+    owner: &Pubkey,
+    recipe_nft: &Nft,
+    args: RecipeSettings,
+) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::CreateRecipe {
+            owner: *owner,
+            recipe: recipe_pda(&recipe_nft.mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            rent_account: sysvar::rent::id(),
+            token_metadata_program: mpl_token_metadata::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::CreateRecipe { settings: args }.data(),
+    }
+}
+
+// update_recipe replacing every setting with args
+pub fn update_recipe_ix(owner: &Pubkey, recipe_nft: &Nft, args: RecipeSettings) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::UpdateRecipe {
+            owner: *owner,
+            recipe: recipe_pda(&recipe_nft.mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::UpdateRecipe { settings: args }.data(),
+    }
+}
+
+pub fn close_recipe_ix(owner: &Pubkey, recipe_nft: &Nft) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::CloseRecipe {
+            owner: *owner,
+            recipe: recipe_pda(&recipe_nft.mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::CloseRecipe {}.data(),
+    }
+}
+
+pub fn withdraw_escrow_ix(
+    admin: &Pubkey,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::WithdrawEscrow {
+            admin: *admin,
+            program_manager: manager_pda(),
+            program_signer: signer_pda(),
+            mint: *mint,
+            escrow_token_account: get_associated_token_address(&signer_pda(), mint),
+            destination: *destination,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::WithdrawEscrow { amount }.data(),
+    }
+}
+
+// recipe is the Recipe or LargeRecipe PDA of recipe_nft owning the vault
+pub fn withdraw_vault_ix(
+    owner: &Pubkey,
+    recipe: &Pubkey,
+    recipe_nft: &Nft,
+    mint: &Pubkey,
+    destination: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let recipe_vault = recipe_vault_pda(recipe);
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::WithdrawVault {
+            owner: *owner,
+            recipe: *recipe,
+            recipe_vault,
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            mint: *mint,
+            vault_token_account: get_associated_token_address(&recipe_vault, mint),
+            destination: *destination,
+            token_program: spl_token::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::WithdrawVault { amount }.data(),
+    }
+}
+
+pub fn migrate_recipe_ix(payer: &Pubkey, recipe_mint: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::MigrateRecipe {
+            payer: *payer,
+            recipe: recipe_pda(recipe_mint),
+            recipe_mint: *recipe_mint,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::MigrateRecipe {}.data(),
+    }
+}
+
+pub fn migrate_manager_ix(payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::MigrateManager {
+            payer: *payer,
+            program_manager: manager_pda(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::MigrateManager {}.data(),
+    }
+}
+
+pub fn create_large_recipe_ix(owner: &Pubkey, recipe_nft: &Nft, craft_fee: u64) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::CreateLargeRecipe {
+            owner: *owner,
+            large_recipe: large_recipe_pda(&recipe_nft.mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::CreateLargeRecipe {
+            craft_fee,
+            royalty_bps: 0,
+        }
+        .data(),
+    }
+}
+
+pub fn append_large_recipe_ix(
+    owner: &Pubkey,
+    recipe_nft: &Nft,
+    ingredient_mints: Vec<Pubkey>,
+    ingredient_amounts: Vec<u64>,
+    outputs: Vec<RecipeOutput>,
+) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: update_large_recipe_accounts(owner, recipe_nft),
+        data: craft_skins::instruction::AppendLargeRecipe {
+            ingredient_mints,
+            ingredient_amounts,
+            outputs,
+        }
+        .data(),
+    }
+}
+
+pub fn reset_large_recipe_ix(owner: &Pubkey, recipe_nft: &Nft, craft_fee: u64) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: update_large_recipe_accounts(owner, recipe_nft),
+        data: craft_skins::instruction::ResetLargeRecipe {
+            craft_fee,
+            royalty_bps: 0,
+        }
+        .data(),
+    }
+}
+
+fn update_large_recipe_accounts(owner: &Pubkey, recipe_nft: &Nft) -> Vec<AccountMeta> {
+    craft_skins::accounts::UpdateLargeRecipe {
+        owner: *owner,
+        large_recipe: large_recipe_pda(&recipe_nft.mint),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+    }
+    .to_account_metas(None)
+}
+
+// ingredient triplets of user ATAs follow the CraftLargeSkin accounts
+pub fn craft_large_skin_ix(
+    owner: &Pubkey,
+    user: &Pubkey,
+    admin: &Pubkey,
+    recipe_nft: &Nft,
+    skin_nft: &Nft,
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    let mut accounts = craft_skins::accounts::CraftLargeSkin {
+        owner: *owner,
+        user: *user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: *admin,
+        recipe_holder: *owner,
+        large_recipe: large_recipe_pda(&recipe_nft.mint),
+        recipe_vault: recipe_vault_pda(&large_recipe_pda(&recipe_nft.mint)),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        user_skin_token_account: get_associated_token_address(user, &skin_nft.mint),
+        skin_token_account: skin_nft.token,
+        skin_mint: skin_nft.mint,
+        skin_metadata: skin_nft.metadata,
+        rent_account: sysvar::rent::id(),
+        token_metadata_program: mpl_token_metadata::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    let recipe_vault = recipe_vault_pda(&large_recipe_pda(&recipe_nft.mint));
+    for mint in ingredient_mints {
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(user, mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(*mint, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(&recipe_vault, mint),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CraftLargeSkin {}.data(),
+    }
+}
+
+pub fn add_skin_ix(owner: &Pubkey, recipe_nft: &Nft, skin_nft: &Nft) -> Instruction {
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts: craft_skins::accounts::AddSkin {
+            owner: *owner,
+            recipe: recipe_pda(&recipe_nft.mint),
+            recipe_token_account: recipe_nft.token,
+            recipe_mint: recipe_nft.mint,
+            recipe_metadata: recipe_nft.metadata,
+            recipe_master_edition: recipe_nft.edition,
+            skin_token_account: skin_nft.token,
+            skin_mint: skin_nft.mint,
+            skin_metadata: skin_nft.metadata,
+            rent_account: sysvar::rent::id(),
+            token_metadata_program: mpl_token_metadata::id(),
+            token_program: spl_token::id(),
+            system_program: system_program::id(),
+        }
+        .to_account_metas(None),
+        data: craft_skins::instruction::AddSkin { _recipe_bump: 0 }.data(),
+    }
+}
+
+/*
+    craft_skin of an untimed recipe without outputs or gates
+    remaining_accounts: [user token, mint, vault token] per ingredient
+*/
+pub fn craft_skin_ix(
+    owner: &Pubkey,
+    user: &Pubkey,
+    admin: &Pubkey,
+    recipe_nft: &Nft,
+    skin_nft: &Nft,
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    craft_skin_ix_with(
+        craft_skin_accounts(owner, user, admin, recipe_nft, skin_nft),
+        ingredient_mints,
+        vec![],
+        0,
+        vec![],
+    )
+}
+
+// CraftSkin accounts with recipe_holder = owner, edited by tests before craft_skin_ix_with
+pub fn craft_skin_accounts(
+    owner: &Pubkey,
+    user: &Pubkey,
+    admin: &Pubkey,
+    recipe_nft: &Nft,
+    skin_nft: &Nft,
+) -> craft_skins::accounts::CraftSkin {
+    craft_skins::accounts::CraftSkin {
+        owner: *owner,
+        user: *user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: *admin,
+        recipe_holder: *owner,
+        player_profile: profile_pda(user),
+        recipe: recipe_pda(&recipe_nft.mint),
+        recipe_vault: recipe_vault_pda(&recipe_pda(&recipe_nft.mint)),
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        user_skin_token_account: get_associated_token_address(user, &skin_nft.mint),
+        skin_token_account: skin_nft.token,
+        skin_mint: skin_nft.mint,
+        skin_metadata: skin_nft.metadata,
+        rent_account: sysvar::rent::id(),
+        token_metadata_program: mpl_token_metadata::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+}
+
+// [user token, mint, escrow token] per ingredient, escrow is the recipe vault or a craft account
+pub fn ingredient_accounts(user: &Pubkey, escrow: &Pubkey, mints: &[Pubkey]) -> Vec<AccountMeta> {
+    mints
+        .iter()
+        .flat_map(|mint| {
+            [
+                AccountMeta::new(get_associated_token_address(user, mint), false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(get_associated_token_address(escrow, mint), false),
+            ]
+        })
+        .collect()
+}
+
+// gate accounts lead remaining_accounts, ingredient triplets of user ATAs follow
+pub fn craft_skin_ix_with(
+    accounts: craft_skins::accounts::CraftSkin,
+    ingredient_mints: &[Pubkey],
+    gate_accounts: Vec<AccountMeta>,
+    allowance: u32,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let user = accounts.user;
+    let recipe_vault = accounts.recipe_vault;
+    let mut accounts = accounts.to_account_metas(None);
+    accounts.extend(gate_accounts);
+    accounts.extend(ingredient_accounts(&user, &recipe_vault, ingredient_mints));
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CraftSkin { allowance, proof }.data(),
+    }
+}
+
+/*
+    craft_skin_batch of one skin per craft, skins held by owner
+    remaining_accounts: ingredient triplets, then [owner skin token, skin mint, skin metadata, user skin token] per skin
+*/
+pub fn craft_skin_batch_ix(
+    owner: &Pubkey,
+    user: &Pubkey,
+    admin: &Pubkey,
+    recipe_nft: &Nft,
+    skin_mints: &[Pubkey],
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    let recipe = recipe_pda(&recipe_nft.mint);
+    let recipe_vault = recipe_vault_pda(&recipe);
+    let mut accounts = craft_skins::accounts::CraftSkinBatch {
+        owner: *owner,
+        user: *user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        admin: *admin,
+        recipe_holder: *owner,
+        player_profile: profile_pda(user),
+        recipe,
+        recipe_vault,
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        rent_account: sysvar::rent::id(),
+        token_metadata_program: mpl_token_metadata::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(ingredient_accounts(user, &recipe_vault, ingredient_mints));
+    for skin_mint in skin_mints {
+        accounts.extend([
+            AccountMeta::new(get_associated_token_address(owner, skin_mint), false),
+            AccountMeta::new_readonly(*skin_mint, false),
+            AccountMeta::new(metadata_pda(skin_mint), false),
+            AccountMeta::new(get_associated_token_address(user, skin_mint), false),
+        ]);
+    }
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CraftSkinBatch {
+            quantity: skin_mints.len() as u8,
+            allowance: 0,
+            proof: vec![],
+        }
+        .data(),
+    }
+}
+
+/*
+    begin_craft of a Random mode recipe held by owner
+    remaining_accounts: [user token, mint, pending escrow token] per ingredient
+*/
+#[allow(clippy::too_many_arguments)] // mirrors the begin_craft accounts + args
+pub fn begin_craft_ix(
+    owner: &Pubkey,
+    user: &Pubkey,
+    admin: &Pubkey,
+    recipe_nft: &Nft,
+    randomness: &Pubkey,
+    request: u64,
+    allowance: u32,
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    let recipe = recipe_pda(&recipe_nft.mint);
+    let pending_craft = pending_pda(&recipe, user);
+    let mut accounts = craft_skins::accounts::BeginCraft {
+        user: *user,
+        program_manager: manager_pda(),
+        admin: *admin,
+        recipe_holder: *owner,
+        player_profile: profile_pda(user),
+        recipe,
+        recipe_token_account: recipe_nft.token,
+        recipe_mint: recipe_nft.mint,
+        pending_craft,
+        randomness: *randomness,
+        rent_account: sysvar::rent::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(ingredient_accounts(user, &pending_craft, ingredient_mints));
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        // single leaf allowlists only, root == leaf and the proof is empty
+        data: craft_skins::instruction::BeginCraft {
+            request,
+            allowance,
+            proof: vec![],
+        }
+        .data(),
+    }
+}
+
+/*
+    finish_craft delivering a fungible loot entry
+    remaining_accounts: [pending escrow token, mint, vault token] per ingredient, then [mint, user token]
+*/
+pub fn finish_craft_ix(
+    payer: &Pubkey,
+    user: &Pubkey,
+    recipe_mint: &Pubkey,
+    randomness: &Pubkey,
+    entry: u8,
+    loot_mint: &Pubkey,
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    let recipe = recipe_pda(recipe_mint);
+    let recipe_vault = recipe_vault_pda(&recipe);
+    let pending_craft = pending_pda(&recipe, user);
+    let mut accounts = craft_skins::accounts::FinishCraft {
+        payer: *payer,
+        user: *user,
+        recipe,
+        recipe_vault,
+        pending_craft,
+        randomness: *randomness,
+        rent_account: sysvar::rent::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    for mint in ingredient_mints {
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(&pending_craft, mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(*mint, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(&recipe_vault, mint),
+            false,
+        ));
+    }
+    accounts.push(AccountMeta::new(*loot_mint, false));
+    accounts.push(AccountMeta::new(
+        get_associated_token_address(user, loot_mint),
+        false,
+    ));
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::FinishCraft { entry }.data(),
+    }
+}
+
+// [escrow token, mint, user token, vault token] per ingredient returned by refund / cancel
+pub fn return_escrow_accounts(
+    user: &Pubkey,
+    escrow: &Pubkey,
+    recipe_vault: &Pubkey,
+    mints: &[Pubkey],
+) -> Vec<AccountMeta> {
+    mints
+        .iter()
+        .flat_map(|mint| {
+            [
+                AccountMeta::new(get_associated_token_address(escrow, mint), false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(get_associated_token_address(user, mint), false),
+                AccountMeta::new(get_associated_token_address(recipe_vault, mint), false),
+            ]
+        })
+        .collect()
+}
+
+// refund_craft after reveal_timeout, ingredients return out of the PendingCraft ATAs
+pub fn refund_craft_ix(
+    payer: &Pubkey,
+    user: &Pubkey,
+    recipe_mint: &Pubkey,
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    let recipe = recipe_pda(recipe_mint);
+    let recipe_vault = recipe_vault_pda(&recipe);
+    let pending_craft = pending_pda(&recipe, user);
+    let mut accounts = craft_skins::accounts::RefundCraft {
+        payer: *payer,
+        user: *user,
+        recipe,
+        recipe_vault,
+        pending_craft,
+        rent_account: sysvar::rent::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(return_escrow_accounts(
+        user,
+        &pending_craft,
+        &recipe_vault,
+        ingredient_mints,
+    ));
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::RefundCraft {}.data(),
+    }
+}
+
+/*
+    cancel_craft of a CraftJob or PendingCraft, the escrow is held by pending_craft
+    remaining_accounts: [escrow token, mint, user token, vault token] per ingredient,
+    [escrow skin token, user skin token] per escrowed input skin,
+    then [skin mint, program skin token, skin owner, owner skin token] of a CraftJob skin
+*/
+pub fn cancel_craft_ix(
+    user: &Pubkey,
+    recipe: &Pubkey,
+    pending_craft: &Pubkey,
+    ingredient_mints: &[Pubkey],
+    input_skins: &[Pubkey],
+    job_skin: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let recipe_vault = recipe_vault_pda(recipe);
+    let mut accounts = craft_skins::accounts::CancelCraft {
+        user: *user,
+        program_signer: signer_pda(),
+        program_manager: manager_pda(),
+        recipe: *recipe,
+        recipe_vault,
+        pending_craft: *pending_craft,
+        rent_account: sysvar::rent::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    accounts.extend(return_escrow_accounts(
+        user,
+        pending_craft,
+        &recipe_vault,
+        ingredient_mints,
+    ));
+    for mint in input_skins {
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(pending_craft, mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(user, mint),
+            false,
+        ));
+    }
+    if let Some((skin_mint, skin_owner)) = job_skin {
+        accounts.push(AccountMeta::new_readonly(*skin_mint, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(&signer_pda(), skin_mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(*skin_owner, false));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(skin_owner, skin_mint),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::CancelCraft {}.data(),
+    }
+}
+
+/*
+    uncraft_skin of a skin held by user in its ATA
+    remaining_accounts: [vault token, user token] per salvage record ingredient
+*/
+pub fn uncraft_skin_ix(
+    user: &Pubkey,
+    recipe_nft: &Nft,
+    skin_mint: &Pubkey,
+    ingredient_mints: &[Pubkey],
+) -> Instruction {
+    let recipe = recipe_pda(&recipe_nft.mint);
+    let recipe_vault = recipe_vault_pda(&recipe);
+    let mut accounts = craft_skins::accounts::UncraftSkin {
+        user: *user,
+        recipe,
+        recipe_vault,
+        recipe_mint: recipe_nft.mint,
+        recipe_metadata: recipe_nft.metadata,
+        recipe_master_edition: recipe_nft.edition,
+        user_skin_token_account: get_associated_token_address(user, skin_mint),
+        skin_mint: *skin_mint,
+        skin_metadata: metadata_pda(skin_mint),
+        vault_skin_token_account: get_associated_token_address(&recipe_vault, skin_mint),
+        salvage_record: salvage_pda(&recipe, skin_mint),
+        rent_account: sysvar::rent::id(),
+        token_program: spl_token::id(),
+        ata_program: anchor_spl::associated_token::ID,
+        system_program: system_program::id(),
+    }
+    .to_account_metas(None);
+    for mint in ingredient_mints {
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(&recipe_vault, mint),
+            false,
+        ));
+        accounts.push(AccountMeta::new(
+            get_associated_token_address(user, mint),
+            false,
+        ));
+    }
+    Instruction {
+        program_id: craft_skins::id(),
+        accounts,
+        data: craft_skins::instruction::UncraftSkin {}.data(),
+    }
+}
+
+pub async fn process(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) -> Result<(), BanksClientError> {
+    let blockhash = banks_client.get_latest_blockhash().await.unwrap();
+    let mut all_signers = vec![payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    banks_client
+        .process_transaction_with_preflight(transaction)
+        .await
+}
+
+/*
+    compute units consumed by craft_skins in instruction
+    banks only report simulation details of failing transactions,
+    so a transfer that always fails is appended after the measured instruction
+*/
+pub async fn compute_units(
+    banks_client: &mut BanksClient,
+    payer: &Keypair,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> u64 {
+    let always_fails = system_instruction::transfer(&payer.pubkey(), &payer.pubkey(), u64::MAX);
+    let err = process(banks_client, payer, &[instruction, always_fails], signers)
+        .await
+        .unwrap_err();
+    let logs = match err {
+        BanksClientError::SimulationError { logs, .. } => logs,
+        err => panic!("expected simulation error, got {}", err),
+    };
+
+    let prefix = format!("Program {} consumed ", craft_skins::id());
+    logs.iter()
+        .find_map(|log| log.strip_prefix(&prefix))
+        .and_then(|rest| rest.split(' ').next())
+        .and_then(|units| units.parse().ok())
+        .unwrap_or_else(|| panic!("no compute units logged for craft_skins: {:?}", logs))
+}
+
+// instruction 0 of a failed transaction returned error (ErrorCode of craft_skins or anchor)
+pub fn assert_error(result: Result<(), BanksClientError>, error: impl Into<u32>) {
+    assert_instruction_error(result, InstructionError::Custom(error.into()));
+}
+
+pub fn assert_instruction_error(result: Result<(), BanksClientError>, error: InstructionError) {
+    let err = result.expect_err("transaction should fail").unwrap();
+    assert_eq!(err, TransactionError::InstructionError(0, error));
+}
+
+pub async fn get_account<T: AccountDeserialize>(banks_client: &mut BanksClient, key: &Pubkey) -> T {
+    let account = banks_client
+        .get_account(*key)
+        .await
+        .unwrap()
+        .unwrap_or_else(|| panic!("account {} not found", key));
+    T::try_deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+pub async fn token_amount(banks_client: &mut BanksClient, token: &Pubkey) -> u64 {
+    let account = banks_client.get_account(*token).await.unwrap().unwrap();
+    TokenAccount::unpack(&account.data).unwrap().amount
+}
+
+pub async fn lamports(banks_client: &mut BanksClient, key: &Pubkey) -> u64 {
+    banks_client.get_balance(*key).await.unwrap()
+}
+
+pub async fn get_metadata(banks_client: &mut BanksClient, metadata: &Pubkey) -> Metadata {
+    let account = banks_client.get_account(*metadata).await.unwrap().unwrap();
+    Metadata::deserialize(&mut account.data.as_slice()).unwrap()
+}
+
+// zero-copy account, read as is after the discriminator
+pub async fn get_large_recipe(banks_client: &mut BanksClient, recipe_mint: &Pubkey) -> LargeRecipe {
+    let key = large_recipe_pda(recipe_mint);
+    let account = banks_client.get_account(key).await.unwrap().unwrap();
+    bytemuck::pod_read_unaligned(&account.data[8..LargeRecipe::LEN])
+}
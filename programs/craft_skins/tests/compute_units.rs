@@ -0,0 +1,374 @@
+/*
+    compute unit benchmark of create_recipe, add_skin and craft_skin for 1..=N ingredients
+
+      cargo test-bpf --test compute_units -- --nocapture
+
+    compute units are measured on craft_skins.so (feature test-bpf, set by cargo test-bpf)
+    and checked against
+      MAX_INSTRUCTION_UNITS      => default compute budget of one instruction
+      compute_units.baseline     => recorded units + BASELINE_TOLERANCE_PERCENT
+    record a new baseline with UPDATE_CU_BASELINE=1, a missing baseline file
+    or benchmark entry fails the check otherwise
+
+    craft_skin is bounded by transaction size before compute units:
+    MAX_CRAFT_INGREDIENTS is the most ingredients whose craft_skin transaction
+    fits in one packet, checked by craft_skin_transaction_size without BPF,
+    craft_large_skin at LargeRecipe::MAX_CRAFT_ACCOUNTS likewise
+
+    units saved by stored PDA bumps are estimated without BPF by
+    craft_skin_stored_bumps_save_units, find_program_address pays
+    CREATE_PROGRAM_ADDRESS_UNITS for every bump it tries
+*/
+mod common;
+
+use common::*;
+use craft_skins::utils::{LargeRecipe, Recipe};
+use solana_sdk::{
+    instruction::AccountMeta, message::Message, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+};
+
+// most ingredients of a craft_skin transaction (owner == recipe holder, no outputs)
+const MAX_CRAFT_INGREDIENTS: usize = 3;
+
+fn dummy_nft() -> Nft {
+    Nft {
+        mint: Pubkey::new_unique(),
+        token: Pubkey::new_unique(),
+        metadata: Pubkey::new_unique(),
+        edition: Pubkey::new_unique(),
+    }
+}
+
+// serialized size of a transaction signed by owner + user (fee payer)
+fn craft_skin_transaction_size(ingredients: usize) -> usize {
+    let owner = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let mints: Vec<Pubkey> = (0..ingredients).map(|_| Pubkey::new_unique()).collect();
+    let instruction = craft_skin_ix(&owner, &user, &admin, &dummy_nft(), &dummy_nft(), &mints);
+
+    let message = Message::new(&[instruction], Some(&user));
+    let signatures = message.header.num_required_signatures as usize;
+    // compact signature count + signatures + message
+    1 + 64 * signatures + message.serialize().len()
+}
+
+#[test]
+fn craft_skin_max_ingredients_fit_in_transaction() {
+    for ingredients in 1..=Recipe::MAX_INGREDIENTS {
+        println!(
+            "craft_skin {} ingredients: {} bytes",
+            ingredients,
+            craft_skin_transaction_size(ingredients)
+        );
+    }
+    assert!(craft_skin_transaction_size(MAX_CRAFT_INGREDIENTS) <= PACKET_DATA_SIZE);
+    if MAX_CRAFT_INGREDIENTS < Recipe::MAX_INGREDIENTS {
+        assert!(craft_skin_transaction_size(MAX_CRAFT_INGREDIENTS + 1) > PACKET_DATA_SIZE);
+    }
+}
+
+// serialized size of a craft_large_skin transaction with remaining_accounts distinct accounts,
+// the Recipe NFT held apart from the skin owner
+fn craft_large_skin_transaction_size(remaining_accounts: usize) -> usize {
+    let owner = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let admin = Pubkey::new_unique();
+    let mut instruction =
+        craft_large_skin_ix(&owner, &user, &admin, &dummy_nft(), &dummy_nft(), &[]);
+    let holder = instruction
+        .accounts
+        .iter()
+        .position(|account| account.pubkey == owner && !account.is_signer)
+        .unwrap();
+    instruction.accounts[holder].pubkey = Pubkey::new_unique();
+    instruction
+        .accounts
+        .extend((0..remaining_accounts).map(|_| AccountMeta::new(Pubkey::new_unique(), false)));
+
+    let message = Message::new(&[instruction], Some(&user));
+    let signatures = message.header.num_required_signatures as usize;
+    1 + 64 * signatures + message.serialize().len()
+}
+
+#[test]
+fn craft_large_skin_max_accounts_fit_in_transaction() {
+    // LargeRecipe::append keeps every craft within MAX_CRAFT_ACCOUNTS, max ingredients use them all
+    let mints = vec![Pubkey::new_unique(); LargeRecipe::MAX_INGREDIENTS];
+    let mut large_recipe: LargeRecipe = bytemuck::Zeroable::zeroed();
+    large_recipe
+        .append(&mints, &[1; LargeRecipe::MAX_INGREDIENTS], &[])
+        .unwrap();
+    assert_eq!(
+        LargeRecipe::craft_accounts(LargeRecipe::MAX_INGREDIENTS, [].iter()),
+        LargeRecipe::MAX_CRAFT_ACCOUNTS
+    );
+    let size = craft_large_skin_transaction_size(LargeRecipe::MAX_CRAFT_ACCOUNTS);
+    println!(
+        "craft_large_skin {} remaining accounts: {} bytes",
+        LargeRecipe::MAX_CRAFT_ACCOUNTS,
+        size
+    );
+    assert!(size <= PACKET_DATA_SIZE);
+}
+
+// units charged by one create_program_address syscall
+const CREATE_PROGRAM_ADDRESS_UNITS: u64 = 1_500;
+
+// units to check a PDA of seeds by bump search, then with its stored bump
+fn derivation_units(seeds: &[&[u8]]) -> (u64, u64) {
+    let (_, bump) = Pubkey::find_program_address(seeds, &craft_skins::id());
+    let attempts = (u8::MAX - bump) as u64 + 1;
+    (
+        attempts * CREATE_PROGRAM_ADDRESS_UNITS,
+        CREATE_PROGRAM_ADDRESS_UNITS,
+    )
+}
+
+#[test]
+fn craft_skin_stored_bumps_save_units() {
+    // fixed keys, the estimate is the same on every run
+    let recipe_mint = Pubkey::new_from_array([1; 32]);
+    let user = Pubkey::new_from_array([2; 32]);
+    /*
+      PDAs craft_skin checks of a returning user
+        recipe   => searched by its seeds constraint + assert_recipe_derivation before stored bumps
+        manager, signer, profile => searched once each
+    */
+    let recipe_seeds: &[&[u8]] = &[b"recipe", recipe_mint.as_ref()];
+    let checks: [&[&[u8]]; 5] = [
+        recipe_seeds,
+        recipe_seeds,
+        &[b"manager"],
+        &[b"signer"],
+        &[b"profile", user.as_ref()],
+    ];
+    let before: u64 = checks.iter().map(|seeds| derivation_units(seeds).0).sum();
+    // the stored recipe bump is checked once
+    let after: u64 = checks[1..]
+        .iter()
+        .map(|seeds| derivation_units(seeds).1)
+        .sum();
+    println!(
+        "craft_skin PDA checks: {} CU by bump search, {} CU by stored bump",
+        before, after
+    );
+    assert!(after < before);
+}
+
+#[cfg(feature = "test-bpf")]
+mod bpf {
+    use super::*;
+    use solana_program_test::{tokio, ProgramTest};
+    use solana_sdk::signature::{Keypair, Signer};
+
+    // default compute budget of one instruction
+    const MAX_INSTRUCTION_UNITS: u64 = 200_000;
+
+    // measured units may exceed the recorded baseline by this much
+    const BASELINE_TOLERANCE_PERCENT: u64 = 5;
+
+    const BASELINE_FILE: &str = "tests/compute_units.baseline";
+
+    // recorded units of each "instruction/ingredients" benchmark, none when recording a new one
+    fn read_baseline(update: bool) -> Vec<(String, u64)> {
+        let baseline = match std::fs::read_to_string(BASELINE_FILE) {
+            Ok(baseline) => baseline,
+            Err(_) if update => String::new(),
+            Err(err) => panic!(
+                "{} not readable ({}), record it with UPDATE_CU_BASELINE=1",
+                BASELINE_FILE, err
+            ),
+        };
+        baseline
+            .lines()
+            .filter_map(|line| {
+                let (name, units) = line.split_once(' ')?;
+                Some((name.to_string(), units.trim().parse().ok()?))
+            })
+            .collect()
+    }
+
+    fn check_units(measured: &[(String, u64)]) {
+        let update = std::env::var("UPDATE_CU_BASELINE").is_ok();
+        let baseline = read_baseline(update);
+        let mut regressions = vec![];
+        for (name, units) in measured {
+            println!("{:<20} {:>7} CU", name, units);
+            assert!(
+                *units <= MAX_INSTRUCTION_UNITS,
+                "{} exceeds instruction compute budget",
+                name
+            );
+            match baseline.iter().find(|(recorded, _)| recorded == name) {
+                Some((_, recorded)) => {
+                    if *units > recorded + recorded * BASELINE_TOLERANCE_PERCENT / 100 {
+                        regressions
+                            .push(format!("{}: {} CU, baseline {} CU", name, units, recorded));
+                    }
+                }
+                // benchmark added since the baseline was recorded
+                None => regressions.push(format!("{}: {} CU, no baseline", name, units)),
+            }
+        }
+
+        if update {
+            let lines: Vec<String> = measured
+                .iter()
+                .map(|(name, units)| format!("{} {}", name, units))
+                .collect();
+            std::fs::write(BASELINE_FILE, lines.join("\n") + "\n").unwrap();
+            return;
+        }
+        assert!(
+            regressions.is_empty(),
+            "compute unit regressions (UPDATE_CU_BASELINE=1 records a new baseline):\n{}",
+            regressions.join("\n")
+        );
+    }
+
+    // accounts of one recipe with ingredients, each held once by user
+    struct Bench {
+        user: Keypair,
+        recipe_nft: Nft,
+        skin_nft: Nft,
+        mints: Vec<Pubkey>,
+    }
+
+    impl Bench {
+        fn add(
+            program_test: &mut ProgramTest,
+            admin: &Pubkey,
+            owner: &Pubkey,
+            ingredients: usize,
+        ) -> Bench {
+            let user = add_wallet(program_test);
+            let recipe_nft = add_nft(program_test, owner, owner, None);
+            let skin_nft = add_nft(program_test, owner, owner, Some(recipe_nft.mint));
+            let mints = (0..ingredients)
+                .map(|_| {
+                    let mint = add_mint(program_test, admin, 1);
+                    add_token_account(program_test, &mint, &user.pubkey(), 1);
+                    mint
+                })
+                .collect();
+            Bench {
+                user,
+                recipe_nft,
+                skin_nft,
+                mints,
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn compute_units_by_ingredient_count() {
+        let mut program_test = program_test();
+        let admin = add_wallet(&mut program_test);
+        let owner = add_wallet(&mut program_test);
+
+        let benches: Vec<Bench> = (1..=Recipe::MAX_INGREDIENTS)
+            .map(|ingredients| {
+                Bench::add(
+                    &mut program_test,
+                    &admin.pubkey(),
+                    &owner.pubkey(),
+                    ingredients,
+                )
+            })
+            .collect();
+        // LargeRecipe at MAX_INGREDIENTS, the most a craft_large_skin transaction carries
+        let large = Bench::add(
+            &mut program_test,
+            &admin.pubkey(),
+            &owner.pubkey(),
+            LargeRecipe::MAX_INGREDIENTS,
+        );
+
+        let (mut banks_client, _payer, _) = program_test.start().await;
+        process(
+            &mut banks_client,
+            &admin,
+            &[initialize_ix(&admin.pubkey())],
+            &[],
+        )
+        .await
+        .unwrap();
+
+        let mut measured = vec![];
+        for bench in benches.iter() {
+            let ingredients = bench.mints.len();
+            let steps = [
+                (
+                    "create_recipe",
+                    create_recipe_ix(
+                        &owner.pubkey(),
+                        &bench.recipe_nft,
+                        bench.mints.clone(),
+                        vec![1; ingredients],
+                    ),
+                    vec![&owner],
+                ),
+                (
+                    "add_skin",
+                    add_skin_ix(&owner.pubkey(), &bench.recipe_nft, &bench.skin_nft),
+                    vec![&owner],
+                ),
+                (
+                    "craft_skin",
+                    craft_skin_ix(
+                        &owner.pubkey(),
+                        &bench.user.pubkey(),
+                        &admin.pubkey(),
+                        &bench.recipe_nft,
+                        &bench.skin_nft,
+                        &bench.mints,
+                    ),
+                    vec![&owner, &bench.user],
+                ),
+            ];
+            for (name, instruction, signers) in steps {
+                let units =
+                    compute_units(&mut banks_client, &admin, instruction.clone(), &signers).await;
+                measured.push((format!("{}/{}", name, ingredients), units));
+                process(&mut banks_client, &admin, &[instruction], &signers)
+                    .await
+                    .unwrap();
+            }
+        }
+
+        process(
+            &mut banks_client,
+            &owner,
+            &[
+                create_large_recipe_ix(&owner.pubkey(), &large.recipe_nft, 0),
+                append_large_recipe_ix(
+                    &owner.pubkey(),
+                    &large.recipe_nft,
+                    large.mints.clone(),
+                    vec![1; large.mints.len()],
+                    vec![],
+                ),
+                add_skin_ix(&owner.pubkey(), &large.recipe_nft, &large.skin_nft),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+        let craft = craft_large_skin_ix(
+            &owner.pubkey(),
+            &large.user.pubkey(),
+            &admin.pubkey(),
+            &large.recipe_nft,
+            &large.skin_nft,
+            &large.mints,
+        );
+        let units = compute_units(&mut banks_client, &admin, craft, &[&owner, &large.user]).await;
+        measured.push((
+            format!("craft_large_skin/{}", LargeRecipe::MAX_INGREDIENTS),
+            units,
+        ));
+
+        check_units(&measured);
+    }
+}
@@ -0,0 +1,1054 @@
+// cargo test-bpf --test craft_skin
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use craft_skins::utils::{
+    CraftJob, ErrorCode, LootEntry, OutputKind, PlayerProfile, RecipeGate, RecipeMode,
+    RecipeOutput, RecipeSettings, SalvageRecord, SkinConsume, SkinInput,
+};
+use mpl_token_metadata::state::{Collection, Creator};
+use solana_program::keccak;
+use solana_program_test::{tokio, BanksClient, BanksClientError, ProgramTest};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction, InstructionError},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+// owner's recipe of ingredients held once by user, skin added by owner
+struct Fixture {
+    program_test: ProgramTest,
+    admin: Keypair,
+    owner: Keypair,
+    user: Keypair,
+    recipe_nft: Nft,
+    skin_nft: Nft,
+    mints: Vec<Pubkey>,
+}
+
+struct Craft {
+    banks_client: BanksClient,
+    admin: Keypair,
+    owner: Keypair,
+    user: Keypair,
+    recipe_nft: Nft,
+    skin_nft: Nft,
+    mints: Vec<Pubkey>,
+}
+
+fn fixture(ingredients: usize) -> Fixture {
+    fixture_with(ingredients, |_| {})
+}
+
+// skin metadata edited by edit_skin (update authority, uri)
+fn fixture_with(
+    ingredients: usize,
+    edit_skin: impl FnOnce(&mut mpl_token_metadata::state::Metadata),
+) -> Fixture {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let owner = add_wallet(&mut program_test);
+    let user = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let skin_nft = add_nft_with(&mut program_test, &owner.pubkey(), |metadata| {
+        metadata.collection = Some(Collection {
+            verified: true,
+            key: recipe_nft.mint,
+        });
+        edit_skin(metadata);
+    });
+    let mints = (0..ingredients)
+        .map(|_| {
+            let mint = add_mint(&mut program_test, &admin.pubkey(), 1);
+            add_token_account(&mut program_test, &mint, &user.pubkey(), 1);
+            mint
+        })
+        .collect();
+    Fixture {
+        program_test,
+        admin,
+        owner,
+        user,
+        recipe_nft,
+        skin_nft,
+        mints,
+    }
+}
+
+impl Fixture {
+    fn args(&self) -> RecipeSettings {
+        recipe_args(self.mints.clone(), vec![1; self.mints.len()])
+    }
+
+    // initialize, create_recipe of args, add_skin
+    async fn start(self, args: RecipeSettings) -> Craft {
+        let (mut banks_client, _payer, _) = self.program_test.start().await;
+        let owner = &self.owner;
+        process(
+            &mut banks_client,
+            &self.admin,
+            &[initialize_ix(&self.admin.pubkey())],
+            &[],
+        )
+        .await
+        .unwrap();
+        process(
+            &mut banks_client,
+            owner,
+            &[
+                create_recipe_ix_with(&owner.pubkey(), &self.recipe_nft, args),
+                add_skin_ix(&owner.pubkey(), &self.recipe_nft, &self.skin_nft),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+        Craft {
+            banks_client,
+            admin: self.admin,
+            owner: self.owner,
+            user: self.user,
+            recipe_nft: self.recipe_nft,
+            skin_nft: self.skin_nft,
+            mints: self.mints,
+        }
+    }
+}
+
+impl Craft {
+    fn accounts(&self) -> craft_skins::accounts::CraftSkin {
+        craft_skin_accounts(
+            &self.owner.pubkey(),
+            &self.user.pubkey(),
+            &self.admin.pubkey(),
+            &self.recipe_nft,
+            &self.skin_nft,
+        )
+    }
+
+    fn ix(&self) -> Instruction {
+        craft_skin_ix_with(self.accounts(), &self.mints, vec![], 0, vec![])
+    }
+
+    // craft of a skin held by owner that was never added to the recipe
+    fn skin_ix(&self, skin_nft: &Nft) -> Instruction {
+        let accounts = craft_skin_accounts(
+            &self.owner.pubkey(),
+            &self.user.pubkey(),
+            &self.admin.pubkey(),
+            &self.recipe_nft,
+            skin_nft,
+        );
+        craft_skin_ix_with(accounts, &self.mints, vec![], 0, vec![])
+    }
+
+    // timed recipe: [craft job PDA, program skin token] lead, ingredient triplets escrow into the job
+    fn job_ix(&self) -> Instruction {
+        let mut instruction = craft_skin_ix_with(self.accounts(), &[], vec![], 0, vec![]);
+        let job = job_pda(&recipe_pda(&self.recipe_nft.mint), &self.user.pubkey());
+        instruction.accounts.extend([
+            AccountMeta::new(job, false),
+            AccountMeta::new(
+                get_associated_token_address(&signer_pda(), &self.skin_nft.mint),
+                false,
+            ),
+        ]);
+        instruction
+            .accounts
+            .extend(ingredient_accounts(&self.user.pubkey(), &job, &self.mints));
+        instruction
+    }
+
+    // salvage recipe: [salvage record] follows the ingredient triplets of an instant craft
+    fn salvage_ix(&self) -> Instruction {
+        let mut instruction = self.ix();
+        let recipe = recipe_pda(&self.recipe_nft.mint);
+        instruction.accounts.push(AccountMeta::new(
+            salvage_pda(&recipe, &self.skin_nft.mint),
+            false,
+        ));
+        instruction
+    }
+
+    // user pays, owner signs for the skin transfer
+    async fn craft(&mut self, instruction: Instruction) -> Result<(), BanksClientError> {
+        process(
+            &mut self.banks_client,
+            &self.user,
+            &[instruction],
+            &[&self.owner],
+        )
+        .await
+    }
+}
+
+// allowlist of a single (user, allowance) leaf, root == leaf and proof is empty
+fn allowlist_root(user: &Pubkey, allowance: u32) -> [u8; 32] {
+    keccak::hashv(&[&[0x00], user.as_ref(), &allowance.to_le_bytes()]).0
+}
+
+#[tokio::test]
+async fn craft_skin_delivers_skin_and_escrows_ingredients() {
+    let fixture = fixture(2);
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.ix();
+    craft.craft(instruction).await.unwrap();
+
+    let user = craft.user.pubkey();
+    let user_skin = get_associated_token_address(&user, &craft.skin_nft.mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_skin).await, 1);
+    let owner_skin = craft.skin_nft.token;
+    assert_eq!(token_amount(&mut craft.banks_client, &owner_skin).await, 0);
+    for mint in craft.mints.clone() {
+        let user_token = get_associated_token_address(&user, &mint);
+        let recipe_vault = recipe_vault_pda(&recipe_pda(&craft.recipe_nft.mint));
+        let escrow_token = get_associated_token_address(&recipe_vault, &mint);
+        assert_eq!(token_amount(&mut craft.banks_client, &user_token).await, 0);
+        assert_eq!(
+            token_amount(&mut craft.banks_client, &escrow_token).await,
+            1
+        );
+    }
+
+    let profile: PlayerProfile = get_account(&mut craft.banks_client, &profile_pda(&user)).await;
+    assert_eq!(profile.user, user);
+    assert_eq!(profile.total_crafts, 1);
+    assert_eq!(profile.recipes.len(), 1);
+    assert_eq!(
+        profile.recipes[0].recipe,
+        recipe_pda(&craft.recipe_nft.mint)
+    );
+    assert_eq!(profile.recipes[0].crafts, 1);
+}
+
+#[tokio::test]
+async fn craft_skin_pays_fee_and_royalty() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.craft_fee = 1_000_000;
+    args.royalty_bps = 2_500;
+    let mut craft = fixture.start(args).await;
+
+    let owner = craft.owner.pubkey();
+    let admin = craft.admin.pubkey();
+    let owner_before = lamports(&mut craft.banks_client, &owner).await;
+    let admin_before = lamports(&mut craft.banks_client, &admin).await;
+    let instruction = craft.ix();
+    craft.craft(instruction).await.unwrap();
+
+    // owner holds the Recipe NFT, royalty share goes to owner
+    let owner_after = lamports(&mut craft.banks_client, &owner).await;
+    let admin_after = lamports(&mut craft.banks_client, &admin).await;
+    assert_eq!(owner_after - owner_before, 250_000);
+    assert_eq!(admin_after - admin_before, 750_000);
+}
+
+#[tokio::test]
+async fn craft_skin_pays_royalty_to_recipe_buyer() {
+    let mut fixture = fixture(1);
+    let buyer = add_wallet(&mut fixture.program_test);
+    let buyer_token = add_token_account(
+        &mut fixture.program_test,
+        &fixture.recipe_nft.mint,
+        &buyer.pubkey(),
+        0,
+    );
+    let mut args = fixture.args();
+    args.craft_fee = 1_000_000;
+    args.royalty_bps = 2_500;
+    let mut craft = fixture.start(args).await;
+
+    // Recipe NFT sold, owner keeps the skins and signs for them
+    let sale = spl_token::instruction::transfer(
+        &spl_token::id(),
+        &craft.recipe_nft.token,
+        &buyer_token,
+        &craft.owner.pubkey(),
+        &[],
+        1,
+    )
+    .unwrap();
+    process(&mut craft.banks_client, &craft.owner, &[sale], &[])
+        .await
+        .unwrap();
+
+    let mut accounts = craft.accounts();
+    accounts.recipe_holder = buyer.pubkey();
+    accounts.recipe_token_account = buyer_token;
+    let instruction = craft_skin_ix_with(accounts, &craft.mints, vec![], 0, vec![]);
+    let buyer_before = lamports(&mut craft.banks_client, &buyer.pubkey()).await;
+    craft.craft(instruction).await.unwrap();
+
+    let buyer_after = lamports(&mut craft.banks_client, &buyer.pubkey()).await;
+    assert_eq!(buyer_after - buyer_before, 250_000);
+    let user_skin = get_associated_token_address(&craft.user.pubkey(), &craft.skin_nft.mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_skin).await, 1);
+}
+
+#[tokio::test]
+async fn craft_skin_stamps_provenance() {
+    // program_signer is update authority of the skin, uri is rewritten by token metadata
+    let fixture = fixture_with(1, |metadata| metadata.update_authority = signer_pda());
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.ix();
+    craft.craft(instruction).await.unwrap();
+
+    let metadata = get_metadata(&mut craft.banks_client, &craft.skin_nft.metadata).await;
+    let uri = metadata.data.uri.trim_end_matches(char::from(0));
+    let prefix = format!(
+        "https://example.com/skin.json?crafter={}&crafted_at=",
+        craft.user.pubkey()
+    );
+    assert!(uri.starts_with(&prefix), "{}", uri);
+    assert!(uri.ends_with("&recipe_version=1"), "{}", uri);
+}
+
+#[tokio::test]
+async fn uri_too_long_skips_provenance() {
+    // stamped uri would not fit, skin is delivered with its uri untouched
+    let long_uri = format!("https://example.com/{}.json", "a".repeat(150));
+    let fixture = fixture_with(1, |metadata| {
+        metadata.update_authority = signer_pda();
+        metadata.data.uri = long_uri.clone();
+    });
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.ix();
+    craft.craft(instruction).await.unwrap();
+
+    let user_skin = get_associated_token_address(&craft.user.pubkey(), &craft.skin_nft.mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_skin).await, 1);
+    let metadata = get_metadata(&mut craft.banks_client, &craft.skin_nft.metadata).await;
+    assert_eq!(metadata.data.uri.trim_end_matches(char::from(0)), long_uri);
+}
+
+#[tokio::test]
+async fn admin_invalid() {
+    let fixture = fixture(1);
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let mut accounts = craft.accounts();
+    accounts.admin = craft.user.pubkey();
+    let instruction = craft_skin_ix_with(accounts, &craft.mints, vec![], 0, vec![]);
+    assert_error(craft.craft(instruction).await, ErrorCode::AdminInvalid);
+}
+
+#[tokio::test]
+async fn recipe_holder_invalid() {
+    let fixture = fixture(1);
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let mut accounts = craft.accounts();
+    accounts.recipe_holder = craft.user.pubkey();
+    let instruction = craft_skin_ix_with(accounts, &craft.mints, vec![], 0, vec![]);
+    assert_error(
+        craft.craft(instruction).await,
+        ErrorCode::RecipeHolderInvalid,
+    );
+}
+
+#[tokio::test]
+async fn not_enough_ingredient() {
+    // recipe needs 2, user holds 1
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.ingredient_amounts = vec![2];
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.ix();
+    assert_error(craft.craft(instruction).await, ErrorCode::NotEnoughToken);
+}
+
+#[tokio::test]
+async fn craft_skin_escrows_only_recipe_amount() {
+    // recipe needs 2, user holds 3 and keeps the surplus
+    let mut fixture = fixture(0);
+    let mint = add_mint(&mut fixture.program_test, &fixture.admin.pubkey(), 3);
+    add_token_account(&mut fixture.program_test, &mint, &fixture.user.pubkey(), 3);
+    fixture.mints = vec![mint];
+    let mut args = fixture.args();
+    args.ingredient_amounts = vec![2];
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.ix();
+    craft.craft(instruction).await.unwrap();
+
+    let user_token = get_associated_token_address(&craft.user.pubkey(), &mint);
+    let recipe_vault = recipe_vault_pda(&recipe_pda(&craft.recipe_nft.mint));
+    let vault_token = get_associated_token_address(&recipe_vault, &mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_token).await, 1);
+    assert_eq!(token_amount(&mut craft.banks_client, &vault_token).await, 2);
+}
+
+#[tokio::test]
+async fn ingredient_mint_invalid() {
+    let fixture = fixture(2);
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    // ingredient triplets out of recipe order
+    let mints: Vec<Pubkey> = craft.mints.iter().rev().cloned().collect();
+    let instruction = craft_skin_ix_with(craft.accounts(), &mints, vec![], 0, vec![]);
+    assert_error(craft.craft(instruction).await, ErrorCode::TokenMintInvalid);
+}
+
+#[tokio::test]
+async fn ingredient_accounts_missing() {
+    let fixture = fixture(1);
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let instruction = craft_skin_ix_with(craft.accounts(), &[], vec![], 0, vec![]);
+    assert_instruction_error(
+        craft.craft(instruction).await,
+        InstructionError::NotEnoughAccountKeys,
+    );
+}
+
+// recipe of one Fungible output of mint, amount 5
+fn fungible_output(fixture: &Fixture, mint: Pubkey) -> RecipeSettings {
+    let mut args = fixture.args();
+    args.outputs = vec![RecipeOutput {
+        kind: OutputKind::Fungible,
+        mint,
+        amount: 5,
+    }];
+    args
+}
+
+// recipe of one Skin output of collection, skin accounts appended to craft.ix()
+fn skin_output_ix(craft: &Craft, skin: &Nft, vault: &Pubkey) -> Instruction {
+    let mut instruction = craft.ix();
+    instruction.accounts.extend([
+        AccountMeta::new(skin.mint, false),
+        AccountMeta::new_readonly(skin.metadata, false),
+        AccountMeta::new(get_associated_token_address(vault, &skin.mint), false),
+        AccountMeta::new(
+            get_associated_token_address(&craft.user.pubkey(), &skin.mint),
+            false,
+        ),
+    ]);
+    instruction
+}
+
+#[tokio::test]
+async fn fungible_output_minted_by_recipe_vault() {
+    let mut fixture = fixture(1);
+    let vault = recipe_vault_pda(&recipe_pda(&fixture.recipe_nft.mint));
+    let output_mint = add_mint(&mut fixture.program_test, &vault, 0);
+    let args = fungible_output(&fixture, output_mint);
+    let mut craft = fixture.start(args).await;
+
+    let user_output = get_associated_token_address(&craft.user.pubkey(), &output_mint);
+    let mut instruction = craft.ix();
+    instruction
+        .accounts
+        .push(AccountMeta::new(output_mint, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new(user_output, false));
+    craft.craft(instruction).await.unwrap();
+    assert_eq!(token_amount(&mut craft.banks_client, &user_output).await, 5);
+}
+
+#[tokio::test]
+async fn fungible_output_of_other_recipe_vault() {
+    // mint authority is the vault of a recipe this creator does not own
+    let mut fixture = fixture(1);
+    let other_recipe = Pubkey::new_unique();
+    let other_vault = recipe_vault_pda(&recipe_pda(&other_recipe));
+    let output_mint = add_mint(&mut fixture.program_test, &other_vault, 0);
+    let args = fungible_output(&fixture, output_mint);
+    let mut craft = fixture.start(args).await;
+
+    let user_output = get_associated_token_address(&craft.user.pubkey(), &output_mint);
+    let mut instruction = craft.ix();
+    instruction
+        .accounts
+        .push(AccountMeta::new(output_mint, false));
+    instruction
+        .accounts
+        .push(AccountMeta::new(user_output, false));
+    assert_error(
+        craft.craft(instruction).await,
+        spl_token::error::TokenError::OwnerMismatch as u32,
+    );
+}
+
+#[tokio::test]
+async fn skin_output_delivered_from_recipe_vault() {
+    let mut fixture = fixture(1);
+    let vault = recipe_vault_pda(&recipe_pda(&fixture.recipe_nft.mint));
+    let collection = Pubkey::new_unique();
+    let skin = add_nft(&mut fixture.program_test, &vault, &vault, Some(collection));
+    let mut args = fixture.args();
+    args.outputs = vec![RecipeOutput {
+        kind: OutputKind::Skin,
+        mint: collection,
+        amount: 1,
+    }];
+    let mut craft = fixture.start(args).await;
+
+    let instruction = skin_output_ix(&craft, &skin, &vault);
+    craft.craft(instruction).await.unwrap();
+    let user_skin = get_associated_token_address(&craft.user.pubkey(), &skin.mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_skin).await, 1);
+    assert_eq!(token_amount(&mut craft.banks_client, &skin.token).await, 0);
+}
+
+#[tokio::test]
+async fn skin_output_of_other_recipe_vault() {
+    // skin of the same collection stocked by another recipe stays there
+    let mut fixture = fixture(1);
+    let other_vault = recipe_vault_pda(&recipe_pda(&Pubkey::new_unique()));
+    let collection = Pubkey::new_unique();
+    let skin = add_nft(
+        &mut fixture.program_test,
+        &other_vault,
+        &other_vault,
+        Some(collection),
+    );
+    let mut args = fixture.args();
+    args.outputs = vec![RecipeOutput {
+        kind: OutputKind::Skin,
+        mint: collection,
+        amount: 1,
+    }];
+    let mut craft = fixture.start(args).await;
+
+    let instruction = skin_output_ix(&craft, &skin, &other_vault);
+    assert_error(craft.craft(instruction).await, ErrorCode::DerivedKeyInvalid);
+    assert_eq!(token_amount(&mut craft.banks_client, &skin.token).await, 1);
+}
+
+#[tokio::test]
+async fn random_recipe_mode_invalid() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.mode = RecipeMode::Random;
+    args.loot_table = vec![LootEntry {
+        weight: 1,
+        output: RecipeOutput {
+            kind: OutputKind::Skin,
+            mint: fixture.recipe_nft.mint,
+            amount: 1,
+        },
+    }];
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.ix();
+    assert_error(craft.craft(instruction).await, ErrorCode::RecipeModeInvalid);
+}
+
+#[tokio::test]
+async fn skin_collection_key_invalid() {
+    // skin verified in another recipe's collection
+    let mut fixture = fixture(1);
+    let owner = fixture.owner.pubkey();
+    let other_recipe = add_nft(&mut fixture.program_test, &owner, &owner, None);
+    let other_skin = add_nft(
+        &mut fixture.program_test,
+        &owner,
+        &owner,
+        Some(other_recipe.mint),
+    );
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.skin_ix(&other_skin);
+    assert_error(
+        craft.craft(instruction).await,
+        ErrorCode::CollectionKeyInvalid,
+    );
+}
+
+#[tokio::test]
+async fn skin_collection_unverified() {
+    let mut fixture = fixture(1);
+    let recipe_mint = fixture.recipe_nft.mint;
+    let unverified_skin = add_nft_with(
+        &mut fixture.program_test,
+        &fixture.owner.pubkey(),
+        |metadata| {
+            metadata.collection = Some(Collection {
+                verified: false,
+                key: recipe_mint,
+            });
+        },
+    );
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.skin_ix(&unverified_skin);
+    assert_error(
+        craft.craft(instruction).await,
+        ErrorCode::CollectionUnverified,
+    );
+}
+
+#[tokio::test]
+async fn batch_skin_creator_invalid() {
+    // skin of the collection held by owner, minted by someone else
+    let mut fixture = fixture(1);
+    let recipe_mint = fixture.recipe_nft.mint;
+    let foreign_skin = add_nft_with(
+        &mut fixture.program_test,
+        &fixture.owner.pubkey(),
+        |metadata| {
+            metadata.collection = Some(Collection {
+                verified: true,
+                key: recipe_mint,
+            });
+            metadata.data.creators = Some(vec![Creator {
+                address: Pubkey::new_unique(),
+                verified: true,
+                share: 100,
+            }]);
+        },
+    );
+    let args = fixture.args();
+    let mut craft = fixture.start(args).await;
+    let batch = |skin: &Nft| {
+        craft_skin_batch_ix(
+            &craft.owner.pubkey(),
+            &craft.user.pubkey(),
+            &craft.admin.pubkey(),
+            &craft.recipe_nft,
+            &[skin.mint],
+            &craft.mints,
+        )
+    };
+    let (foreign_ix, skin_ix) = (batch(&foreign_skin), batch(&craft.skin_nft));
+    assert_error(craft.craft(foreign_ix).await, ErrorCode::WrongCreators);
+
+    craft.craft(skin_ix).await.unwrap();
+    let user_skin = get_associated_token_address(&craft.user.pubkey(), &craft.skin_nft.mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_skin).await, 1);
+}
+
+#[tokio::test]
+async fn allowlisted_user_crafts() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.allowlist_root = Some(allowlist_root(&fixture.user.pubkey(), 1));
+    let mut craft = fixture.start(args).await;
+    let instruction = craft_skin_ix_with(craft.accounts(), &craft.mints, vec![], 1, vec![]);
+    craft.craft(instruction).await.unwrap();
+}
+
+#[tokio::test]
+async fn not_allowlisted() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.allowlist_root = Some(allowlist_root(&fixture.owner.pubkey(), 1));
+    let mut craft = fixture.start(args).await;
+    let instruction = craft_skin_ix_with(craft.accounts(), &craft.mints, vec![], 1, vec![]);
+    assert_error(craft.craft(instruction).await, ErrorCode::NotAllowlisted);
+}
+
+#[tokio::test]
+async fn allowance_exceeded() {
+    // user is allowlisted for 0 crafts
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.allowlist_root = Some(allowlist_root(&fixture.user.pubkey(), 0));
+    let mut craft = fixture.start(args).await;
+    let instruction = craft_skin_ix_with(craft.accounts(), &craft.mints, vec![], 0, vec![]);
+    assert_error(craft.craft(instruction).await, ErrorCode::AllowanceExceeded);
+}
+
+#[tokio::test]
+async fn gate_not_enough_token() {
+    let mut fixture = fixture(1);
+    let gate_mint = add_mint(&mut fixture.program_test, &fixture.admin.pubkey(), 1);
+    let gate_token = add_token_account(
+        &mut fixture.program_test,
+        &gate_mint,
+        &fixture.user.pubkey(),
+        1,
+    );
+    let mut args = fixture.args();
+    args.gate = Some(RecipeGate::TokenBalance {
+        mint: gate_mint,
+        min_amount: 2,
+    });
+    let mut craft = fixture.start(args).await;
+    let gate_accounts = vec![AccountMeta::new_readonly(gate_token, false)];
+    let instruction = craft_skin_ix_with(craft.accounts(), &craft.mints, gate_accounts, 0, vec![]);
+    assert_error(craft.craft(instruction).await, ErrorCode::NotEnoughToken);
+}
+
+#[tokio::test]
+async fn gate_token_owner_invalid() {
+    // gate token held by owner, not user
+    let mut fixture = fixture(1);
+    let gate_mint = add_mint(&mut fixture.program_test, &fixture.admin.pubkey(), 1);
+    let gate_token = add_token_account(
+        &mut fixture.program_test,
+        &gate_mint,
+        &fixture.owner.pubkey(),
+        1,
+    );
+    let mut args = fixture.args();
+    args.gate = Some(RecipeGate::TokenBalance {
+        mint: gate_mint,
+        min_amount: 1,
+    });
+    let mut craft = fixture.start(args).await;
+    let gate_accounts = vec![AccountMeta::new_readonly(gate_token, false)];
+    let instruction = craft_skin_ix_with(craft.accounts(), &craft.mints, gate_accounts, 0, vec![]);
+    assert_error(craft.craft(instruction).await, ErrorCode::TokenOwnerInvalid);
+}
+
+#[tokio::test]
+async fn timed_craft_opens_craft_job() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.craft_duration = 60;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.job_ix();
+    craft.craft(instruction).await.unwrap();
+
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let job: CraftJob = get_account(
+        &mut craft.banks_client,
+        &job_pda(&recipe, &craft.user.pubkey()),
+    )
+    .await;
+    assert_eq!(job.skin_mint, craft.skin_nft.mint);
+    assert_eq!(job.completes_at, job.started_at + 60);
+    let program_skin = get_associated_token_address(&signer_pda(), &craft.skin_nft.mint);
+    assert_eq!(
+        token_amount(&mut craft.banks_client, &program_skin).await,
+        1
+    );
+    // ingredients stay with the job until claimed or canceled
+    let job_token =
+        get_associated_token_address(&job_pda(&recipe, &craft.user.pubkey()), &craft.mints[0]);
+    assert_eq!(token_amount(&mut craft.banks_client, &job_token).await, 1);
+}
+
+#[tokio::test]
+async fn withdraw_vault_cannot_take_job_escrow() {
+    let mut fixture = fixture(1);
+    let mint = fixture.mints[0];
+    let owner_token =
+        add_token_account(&mut fixture.program_test, &mint, &fixture.owner.pubkey(), 0);
+    let mut args = fixture.args();
+    args.craft_duration = 60;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.job_ix();
+    craft.craft(instruction).await.unwrap();
+
+    // the recipe vault holds none of the job's escrow
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let withdraw = withdraw_vault_ix(
+        &craft.owner.pubkey(),
+        &recipe,
+        &craft.recipe_nft,
+        &mint,
+        &owner_token,
+        1,
+    );
+    assert_error(
+        process(&mut craft.banks_client, &craft.owner, &[withdraw], &[]).await,
+        anchor_lang::error::ErrorCode::AccountNotInitialized,
+    );
+
+    let user = craft.user.pubkey();
+    let job = job_pda(&recipe, &user);
+    let skin = craft.skin_nft.mint;
+    let instruction = cancel_craft_ix(
+        &user,
+        &recipe,
+        &job,
+        &craft.mints,
+        &[],
+        Some((&skin, &craft.owner.pubkey())),
+    );
+    process(&mut craft.banks_client, &craft.user, &[instruction], &[])
+        .await
+        .unwrap();
+    let user_token = get_associated_token_address(&user, &mint);
+    assert_eq!(token_amount(&mut craft.banks_client, &user_token).await, 1);
+}
+
+#[tokio::test]
+async fn craft_duration_overflow() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.craft_duration = i64::MAX;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.job_ix();
+    assert_error(
+        craft.craft(instruction).await,
+        ErrorCode::CraftDurationInvalid,
+    );
+}
+
+#[tokio::test]
+async fn cancel_timed_craft_returns_skin_to_its_owner() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.craft_duration = 60;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.job_ix();
+    craft.craft(instruction).await.unwrap();
+
+    let user = craft.user.pubkey();
+    let owner = craft.owner.pubkey();
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let job = job_pda(&recipe, &user);
+    let craft_job: CraftJob = get_account(&mut craft.banks_client, &job).await;
+    assert_eq!(craft_job.skin_owner, owner);
+    let skin = craft.skin_nft.mint;
+
+    // skin owner is recorded on the CraftJob, the user cannot name another wallet
+    let instruction = cancel_craft_ix(
+        &user,
+        &recipe,
+        &job,
+        &craft.mints,
+        &[],
+        Some((&skin, &user)),
+    );
+    assert_error(
+        process(&mut craft.banks_client, &craft.user, &[instruction], &[]).await,
+        ErrorCode::TokenOwnerInvalid,
+    );
+
+    let instruction = cancel_craft_ix(
+        &user,
+        &recipe,
+        &job,
+        &craft.mints,
+        &[],
+        Some((&skin, &owner)),
+    );
+    process(&mut craft.banks_client, &craft.user, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let vault_skin = get_associated_token_address(&recipe_vault_pda(&recipe), &skin);
+    let program_skin = get_associated_token_address(&signer_pda(), &skin);
+    let user_token = get_associated_token_address(&user, &craft.mints[0]);
+    let banks_client = &mut craft.banks_client;
+    assert_eq!(token_amount(banks_client, &craft.skin_nft.token).await, 1);
+    assert!(banks_client
+        .get_account(vault_skin)
+        .await
+        .unwrap()
+        .is_none());
+    assert_eq!(token_amount(banks_client, &program_skin).await, 0);
+    assert_eq!(token_amount(banks_client, &user_token).await, 1);
+    assert!(banks_client.get_account(job).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn cancel_timed_craft_with_other_skin() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.craft_duration = 60;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.job_ix();
+    craft.craft(instruction).await.unwrap();
+
+    let user = craft.user.pubkey();
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let job = job_pda(&recipe, &user);
+    let other_skin = Pubkey::new_unique();
+    let owner = craft.owner.pubkey();
+    let instruction = cancel_craft_ix(
+        &user,
+        &recipe,
+        &job,
+        &craft.mints,
+        &[],
+        Some((&other_skin, &owner)),
+    );
+    let result = process(&mut craft.banks_client, &craft.user, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::TokenMintInvalid);
+}
+
+#[tokio::test]
+async fn cancel_timed_craft_returns_escrowed_input_skin() {
+    let mut fixture = fixture(1);
+    let collection = Pubkey::new_unique();
+    let user = fixture.user.pubkey();
+    let input_skin = add_nft(&mut fixture.program_test, &user, &user, Some(collection));
+    let mut args = fixture.args();
+    args.craft_duration = 60;
+    args.input_skins = vec![SkinInput {
+        collection,
+        consume: SkinConsume::Escrow,
+    }];
+    let mut craft = fixture.start(args).await;
+
+    // input skin accounts follow the ingredient triplets, the job escrows the skin
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let job = job_pda(&recipe, &user);
+    let job_skin = get_associated_token_address(&job, &input_skin.mint);
+    let mut instruction = craft.job_ix();
+    instruction.accounts.extend([
+        AccountMeta::new(input_skin.token, false),
+        AccountMeta::new(input_skin.mint, false),
+        AccountMeta::new_readonly(input_skin.metadata, false),
+        AccountMeta::new(job_skin, false),
+    ]);
+    craft.craft(instruction).await.unwrap();
+
+    let craft_job: CraftJob = get_account(&mut craft.banks_client, &job).await;
+    assert_eq!(craft_job.input_skins, vec![input_skin.mint]);
+    assert_eq!(token_amount(&mut craft.banks_client, &job_skin).await, 1);
+
+    let skin = craft.skin_nft.mint;
+    let instruction = cancel_craft_ix(
+        &user,
+        &recipe,
+        &job,
+        &craft.mints,
+        &[input_skin.mint],
+        Some((&skin, &craft.owner.pubkey())),
+    );
+    process(&mut craft.banks_client, &craft.user, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let banks_client = &mut craft.banks_client;
+    assert_eq!(token_amount(banks_client, &input_skin.token).await, 1);
+    assert_eq!(token_amount(banks_client, &job_skin).await, 0);
+}
+
+#[tokio::test]
+async fn uncraft_skin_pays_salvage_from_recipe_vault() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.salvage_bps = 10_000;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.salvage_ix();
+    craft.craft(instruction).await.unwrap();
+
+    let user = craft.user.pubkey();
+    let skin = craft.skin_nft.mint;
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let record: SalvageRecord =
+        get_account(&mut craft.banks_client, &salvage_pda(&recipe, &skin)).await;
+    assert_eq!(record.mints, craft.mints);
+    assert_eq!(record.amounts, vec![1]);
+    let instruction = uncraft_skin_ix(&user, &craft.recipe_nft, &skin, &craft.mints);
+    process(&mut craft.banks_client, &craft.user, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let recipe_vault = recipe_vault_pda(&recipe_pda(&craft.recipe_nft.mint));
+    let vault_token = get_associated_token_address(&recipe_vault, &craft.mints[0]);
+    let user_token = get_associated_token_address(&user, &craft.mints[0]);
+    let vault_skin = get_associated_token_address(&recipe_vault, &skin);
+    let banks_client = &mut craft.banks_client;
+    assert_eq!(token_amount(banks_client, &user_token).await, 1);
+    assert_eq!(token_amount(banks_client, &vault_token).await, 0);
+    assert_eq!(token_amount(banks_client, &vault_skin).await, 1);
+    let record = salvage_pda(&recipe, &skin);
+    assert!(banks_client.get_account(record).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn uncraft_pays_salvage_recorded_at_craft() {
+    let mut fixture = fixture(1);
+    let mint = fixture.mints[0];
+    // vault stock beyond this craft, e.g. ingredients of earlier crafts
+    let recipe_vault = recipe_vault_pda(&recipe_pda(&fixture.recipe_nft.mint));
+    add_token_account(&mut fixture.program_test, &mint, &recipe_vault, 4);
+    let mut args = fixture.args();
+    args.salvage_bps = 5_000;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.salvage_ix();
+    craft.craft(instruction).await.unwrap();
+
+    // amounts + salvage raised after the craft do not change what the skin salvages
+    let mut args = recipe_args(vec![mint], vec![5]);
+    args.salvage_bps = 10_000;
+    let update = update_recipe_ix(&craft.owner.pubkey(), &craft.recipe_nft, args);
+    process(&mut craft.banks_client, &craft.owner, &[update], &[])
+        .await
+        .unwrap();
+
+    let user = craft.user.pubkey();
+    let skin = craft.skin_nft.mint;
+    let instruction = uncraft_skin_ix(&user, &craft.recipe_nft, &skin, &craft.mints);
+    process(&mut craft.banks_client, &craft.user, &[instruction], &[])
+        .await
+        .unwrap();
+    // kept share rounds down, the salvage of 1 at 5000 bps is 1
+    let user_token = get_associated_token_address(&user, &mint);
+    let vault_token = get_associated_token_address(&recipe_vault, &mint);
+    let banks_client = &mut craft.banks_client;
+    assert_eq!(token_amount(banks_client, &user_token).await, 1);
+    assert_eq!(token_amount(banks_client, &vault_token).await, 4);
+}
+
+#[tokio::test]
+async fn uncraft_requires_salvage_record() {
+    let fixture = fixture(1);
+    let mut args = fixture.args();
+    args.salvage_bps = 10_000;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.salvage_ix();
+    craft.craft(instruction).await.unwrap();
+
+    // skin of the collection never crafted by the recipe, held by user
+    let user = craft.user.pubkey();
+    let skin = craft.skin_nft.mint;
+    let mut instruction = uncraft_skin_ix(&user, &craft.recipe_nft, &skin, &craft.mints);
+    let other_skin = Pubkey::new_unique();
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let record_index = instruction
+        .accounts
+        .iter()
+        .position(|account| account.pubkey == salvage_pda(&recipe, &skin))
+        .unwrap();
+    instruction.accounts[record_index].pubkey = salvage_pda(&recipe, &other_skin);
+    assert_error(
+        process(&mut craft.banks_client, &craft.user, &[instruction], &[]).await,
+        anchor_lang::error::ErrorCode::AccountNotInitialized,
+    );
+}
+
+#[tokio::test]
+async fn uncraft_salvage_capped_at_recipe_vault() {
+    let mut fixture = fixture(1);
+    let mint = fixture.mints[0];
+    // escrow of other recipes, not paid out by this one
+    let legacy_escrow = add_token_account(&mut fixture.program_test, &mint, &signer_pda(), 5);
+    let owner_token =
+        add_token_account(&mut fixture.program_test, &mint, &fixture.owner.pubkey(), 0);
+    let mut args = fixture.args();
+    args.salvage_bps = 10_000;
+    let mut craft = fixture.start(args).await;
+    let instruction = craft.salvage_ix();
+    craft.craft(instruction).await.unwrap();
+
+    // holder empties the vault before the skin is salvaged
+    let recipe = recipe_pda(&craft.recipe_nft.mint);
+    let withdraw = withdraw_vault_ix(
+        &craft.owner.pubkey(),
+        &recipe,
+        &craft.recipe_nft,
+        &mint,
+        &owner_token,
+        1,
+    );
+    process(&mut craft.banks_client, &craft.owner, &[withdraw], &[])
+        .await
+        .unwrap();
+
+    let user = craft.user.pubkey();
+    let skin = craft.skin_nft.mint;
+    let instruction = uncraft_skin_ix(&user, &craft.recipe_nft, &skin, &craft.mints);
+    assert_error(
+        process(&mut craft.banks_client, &craft.user, &[instruction], &[]).await,
+        ErrorCode::NotEnoughToken,
+    );
+    assert_eq!(
+        token_amount(&mut craft.banks_client, &legacy_escrow).await,
+        5
+    );
+}
@@ -0,0 +1,322 @@
+// cargo test-bpf --test create_recipe
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use craft_skins::utils::{
+    ErrorCode, LootEntry, OutputKind, Recipe, RecipeCategory, RecipeMode, RecipeOutput,
+    RecipeSettings, SkinConsume, SkinInput,
+};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+fn output(kind: OutputKind, amount: u64) -> RecipeOutput {
+    RecipeOutput {
+        kind,
+        mint: Pubkey::new_unique(),
+        amount,
+    }
+}
+
+fn loot_entry() -> LootEntry {
+    LootEntry {
+        weight: 1,
+        output: output(OutputKind::Skin, 1),
+    }
+}
+
+// create_recipe of owner's Recipe NFT with args edited by edit_args
+async fn create_recipe(
+    edit_args: impl FnOnce(&mut RecipeSettings),
+) -> Result<(), BanksClientError> {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let mint = add_mint(&mut program_test, &owner.pubkey(), 1);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let mut args = recipe_args(vec![mint], vec![1]);
+    edit_args(&mut args);
+    let instruction = create_recipe_ix_with(&owner.pubkey(), &recipe_nft, args);
+    process(&mut banks_client, &owner, &[instruction], &[]).await
+}
+
+// create_recipe of a Recipe NFT whose accounts were replaced by edit_nft
+async fn create_recipe_of(
+    edit_nft: impl FnOnce(&mut solana_program_test::ProgramTest, &Keypair, Nft) -> Nft,
+) -> Result<(), BanksClientError> {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let recipe_nft = edit_nft(&mut program_test, &owner, recipe_nft);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let instruction = create_recipe_ix(&owner.pubkey(), &recipe_nft, vec![], vec![]);
+    process(&mut banks_client, &owner, &[instruction], &[]).await
+}
+
+#[tokio::test]
+async fn create_recipe_stores_ingredients() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let mints = vec![
+        add_mint(&mut program_test, &owner.pubkey(), 1),
+        add_mint(&mut program_test, &owner.pubkey(), 1),
+    ];
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let mut args = recipe_args(mints.clone(), vec![1, 3]);
+    args.craft_fee = 1_000;
+    args.royalty_bps = 500;
+    args.name = "Scrap Blade".to_string();
+    args.category = RecipeCategory::Weapon;
+    args.sort_order = 3;
+    let instruction = create_recipe_ix_with(&owner.pubkey(), &recipe_nft, args);
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let recipe_key = recipe_pda(&recipe_nft.mint);
+    let recipe: Recipe = get_account(&mut banks_client, &recipe_key).await;
+    assert_eq!(recipe.mints, mints);
+    assert_eq!(recipe.amounts, vec![1, 3]);
+    assert_eq!(recipe.craft_fee, 1_000);
+    assert_eq!(recipe.royalty_bps, 500);
+    assert!(recipe.mode == RecipeMode::Fixed);
+    assert_eq!(recipe.revision, 1);
+    assert_eq!(recipe.name, "Scrap Blade");
+    assert!(recipe.category == RecipeCategory::Weapon);
+    assert_eq!(recipe.sort_order, 3);
+    assert_eq!(recipe.version, Recipe::VERSION);
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"recipe", recipe_nft.mint.as_ref()], &craft_skins::id());
+    assert_eq!(recipe.bump, bump);
+}
+
+#[tokio::test]
+async fn create_recipe_twice_fails() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let instruction = create_recipe_ix(&owner.pubkey(), &recipe_nft, vec![], vec![]);
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+    // Recipe PDA already exists, other args keep the transaction distinct
+    let mut args = recipe_args(vec![], vec![]);
+    args.craft_fee = 1;
+    let instruction = create_recipe_ix_with(&owner.pubkey(), &recipe_nft, args);
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn ingredient_length_mismatch() {
+    let result = create_recipe(|args| args.ingredient_amounts = vec![1, 1]).await;
+    assert_error(result, ErrorCode::IngredientLengthMismatch);
+}
+
+#[tokio::test]
+async fn too_many_ingredients() {
+    let result = create_recipe(|args| {
+        let ingredients = Recipe::MAX_INGREDIENTS + 1;
+        args.ingredient_mints = (0..ingredients).map(|_| Pubkey::new_unique()).collect();
+        args.ingredient_amounts = vec![1; ingredients];
+    })
+    .await;
+    assert_error(result, ErrorCode::TooManyIngredients);
+}
+
+#[tokio::test]
+async fn too_many_outputs() {
+    let result = create_recipe(|args| {
+        args.outputs = (0..=Recipe::MAX_OUTPUTS)
+            .map(|_| output(OutputKind::Fungible, 1))
+            .collect();
+    })
+    .await;
+    assert_error(result, ErrorCode::TooManyOutputs);
+}
+
+#[tokio::test]
+async fn output_amount_invalid() {
+    // skins are NFTs, delivered one at a time
+    let result = create_recipe(|args| args.outputs = vec![output(OutputKind::Skin, 2)]).await;
+    assert_error(result, ErrorCode::OutputAmountInvalid);
+
+    let result = create_recipe(|args| args.outputs = vec![output(OutputKind::Fungible, 0)]).await;
+    assert_error(result, ErrorCode::OutputAmountInvalid);
+}
+
+#[tokio::test]
+async fn listing_fields_too_long() {
+    let result = create_recipe(|args| args.name = "x".repeat(Recipe::MAX_NAME_LEN + 1)).await;
+    assert_error(result, ErrorCode::RecipeNameTooLong);
+
+    let result = create_recipe(|args| {
+        args.description = "x".repeat(Recipe::MAX_DESCRIPTION_LEN + 1);
+    })
+    .await;
+    assert_error(result, ErrorCode::RecipeDescriptionTooLong);
+
+    // longest values fit Recipe::LEN, multi-byte characters count as bytes
+    let result = create_recipe(|args| {
+        args.name = "é".repeat(Recipe::MAX_NAME_LEN / 2);
+        args.description = "x".repeat(Recipe::MAX_DESCRIPTION_LEN);
+    })
+    .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+async fn royalty_invalid() {
+    let result = create_recipe(|args| args.royalty_bps = 10_001).await;
+    assert_error(result, ErrorCode::RoyaltyInvalid);
+}
+
+#[tokio::test]
+async fn too_many_loot_entries() {
+    let result = create_recipe(|args| {
+        args.mode = RecipeMode::Random;
+        args.loot_table = (0..=Recipe::MAX_LOOT_ENTRIES)
+            .map(|_| loot_entry())
+            .collect();
+    })
+    .await;
+    assert_error(result, ErrorCode::TooManyLootEntries);
+}
+
+#[tokio::test]
+async fn loot_table_invalid() {
+    // Fixed recipes deliver the skin passed by the client
+    let result = create_recipe(|args| args.loot_table = vec![loot_entry()]).await;
+    assert_error(result, ErrorCode::LootTableInvalid);
+
+    // Random recipes roll from a non-empty table of weighted entries
+    let result = create_recipe(|args| args.mode = RecipeMode::Random).await;
+    assert_error(result, ErrorCode::LootTableInvalid);
+}
+
+#[tokio::test]
+async fn reveal_window_invalid() {
+    let result = create_recipe(|args| args.reveal_delay = 10).await;
+    assert_error(result, ErrorCode::RevealWindowInvalid);
+
+    // slot hashes randomness must still be readable when refund opens
+    let result = create_recipe(|args| {
+        args.mode = RecipeMode::Random;
+        args.loot_table = vec![loot_entry()];
+        args.reveal_delay = 10;
+        args.reveal_timeout = 1;
+    })
+    .await;
+    assert_error(result, ErrorCode::RevealWindowInvalid);
+    // refunds of a public roll must cost something
+    let result = create_recipe(|args| {
+        args.mode = RecipeMode::Random;
+        args.loot_table = vec![loot_entry()];
+        args.reveal_delay = 10;
+        args.reveal_timeout = 512;
+    })
+    .await;
+    assert_error(result, ErrorCode::RevealWindowInvalid);
+
+    // VRF results are bound to a begin_craft request, no single transaction rolls
+    let result = create_recipe(|args| {
+        args.mode = RecipeMode::Random;
+        args.loot_table = vec![loot_entry()];
+        args.randomness_program = Some(Pubkey::new_unique());
+    })
+    .await;
+    assert_error(result, ErrorCode::RevealWindowInvalid);
+}
+
+#[tokio::test]
+async fn craft_duration_invalid() {
+    let result = create_recipe(|args| args.craft_duration = -1).await;
+    assert_error(result, ErrorCode::CraftDurationInvalid);
+
+    let result = create_recipe(|args| {
+        args.craft_duration = 60;
+        args.speed_up_mint = Some(Pubkey::new_unique());
+    })
+    .await;
+    assert_error(result, ErrorCode::CraftDurationInvalid);
+}
+
+#[tokio::test]
+async fn penalty_invalid() {
+    let result = create_recipe(|args| args.cancel_penalty_bps = 10_001).await;
+    assert_error(result, ErrorCode::PenaltyInvalid);
+
+    let result = create_recipe(|args| args.salvage_bps = 10_001).await;
+    assert_error(result, ErrorCode::PenaltyInvalid);
+}
+
+#[tokio::test]
+async fn too_many_input_skins() {
+    let result = create_recipe(|args| {
+        args.input_skins = (0..=Recipe::MAX_INPUT_SKINS)
+            .map(|_| SkinInput {
+                collection: Pubkey::new_unique(),
+                consume: SkinConsume::Burn,
+            })
+            .collect();
+    })
+    .await;
+    assert_error(result, ErrorCode::TooManyInputSkins);
+}
+
+#[tokio::test]
+async fn recipe_token_amount_invalid() {
+    let result = create_recipe_of(|program_test, owner, recipe_nft| {
+        // owner holds 2 of a mint, not a single NFT
+        let mint = add_mint(program_test, &owner.pubkey(), 2);
+        let token = add_token_account(program_test, &mint, &owner.pubkey(), 2);
+        Nft {
+            mint,
+            token,
+            ..recipe_nft
+        }
+    })
+    .await;
+    assert_error(result, ErrorCode::TokenAmountInvalid);
+}
+
+#[tokio::test]
+async fn recipe_token_mint_invalid() {
+    let result = create_recipe_of(|program_test, owner, recipe_nft| {
+        // token account of another NFT held by owner
+        let other = add_nft(program_test, &owner.pubkey(), &owner.pubkey(), None);
+        Nft {
+            token: other.token,
+            ..recipe_nft
+        }
+    })
+    .await;
+    assert_error(result, ErrorCode::TokenMintInvalid);
+}
+
+#[tokio::test]
+async fn recipe_metadata_not_initialized() {
+    let result = create_recipe_of(|program_test, owner, _| {
+        // NFT mint without metadata or master edition accounts
+        let mint = add_mint(program_test, &owner.pubkey(), 1);
+        let token = add_token_account(program_test, &mint, &owner.pubkey(), 1);
+        Nft {
+            mint,
+            token,
+            metadata: metadata_pda(&mint),
+            edition: edition_pda(&mint),
+        }
+    })
+    .await;
+    assert_error(result, ErrorCode::NotInitialized);
+}
@@ -0,0 +1,61 @@
+// cargo test-bpf --test initialize
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use common::*;
+use craft_skins::Manager;
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn initialize_stores_admin_and_bumps() {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    process(
+        &mut banks_client,
+        &admin,
+        &[initialize_ix(&admin.pubkey())],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let manager: Manager = get_account(&mut banks_client, &manager_pda()).await;
+    assert_eq!(manager.admin, admin.pubkey());
+    let (_, bump) = Pubkey::find_program_address(&[b"manager"], &craft_skins::id());
+    assert_eq!(manager.bump, bump);
+    let (_, signer_bump) = Pubkey::find_program_address(&[b"signer"], &craft_skins::id());
+    assert_eq!(manager.signer_bump, signer_bump);
+}
+
+#[tokio::test]
+async fn initialize_twice_fails() {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let other = add_wallet(&mut program_test);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    process(
+        &mut banks_client,
+        &admin,
+        &[initialize_ix(&admin.pubkey())],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // Manager PDA already exists, admin cannot be taken over
+    let result = process(
+        &mut banks_client,
+        &other,
+        &[initialize_ix(&other.pubkey())],
+        &[],
+    )
+    .await;
+    assert!(result.is_err());
+    let manager: Manager = get_account(&mut banks_client, &manager_pda()).await;
+    assert_eq!(manager.admin, admin.pubkey());
+}
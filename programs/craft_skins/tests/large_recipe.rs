@@ -0,0 +1,271 @@
+// cargo test-bpf --test large_recipe
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use craft_skins::utils::{ErrorCode, LargeRecipe, OutputKind, RecipeOutput};
+use mpl_token_metadata::state::Collection;
+use solana_program_test::{tokio, BanksClient};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+struct Fixture {
+    banks_client: BanksClient,
+    admin: Keypair,
+    owner: Keypair,
+    user: Keypair,
+    recipe_nft: Nft,
+    skin_nft: Nft,
+    mints: Vec<Pubkey>,
+}
+
+// initialize + empty LargeRecipe of owner, user holds 1 of each of ingredients mints
+async fn fixture(ingredients: usize) -> Fixture {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let owner = add_wallet(&mut program_test);
+    let user = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let skin_nft = add_nft_with(&mut program_test, &owner.pubkey(), |metadata| {
+        metadata.collection = Some(Collection {
+            verified: true,
+            key: recipe_nft.mint,
+        });
+    });
+    let mints = (0..ingredients)
+        .map(|_| {
+            let mint = add_mint(&mut program_test, &admin.pubkey(), 1);
+            add_token_account(&mut program_test, &mint, &user.pubkey(), 1);
+            mint
+        })
+        .collect();
+
+    let (mut banks_client, _payer, _) = program_test.start().await;
+    process(
+        &mut banks_client,
+        &admin,
+        &[initialize_ix(&admin.pubkey())],
+        &[],
+    )
+    .await
+    .unwrap();
+    process(
+        &mut banks_client,
+        &owner,
+        &[create_large_recipe_ix(&owner.pubkey(), &recipe_nft, 0)],
+        &[],
+    )
+    .await
+    .unwrap();
+    Fixture {
+        banks_client,
+        admin,
+        owner,
+        user,
+        recipe_nft,
+        skin_nft,
+        mints,
+    }
+}
+
+#[tokio::test]
+async fn appends_up_to_craft_accounts() {
+    let mut fixture = fixture(0).await;
+    let owner = &fixture.owner;
+    let large_recipe = get_large_recipe(&mut fixture.banks_client, &fixture.recipe_nft.mint).await;
+    assert_eq!(large_recipe.version, LargeRecipe::VERSION);
+    assert_eq!(large_recipe.revision, 1);
+    assert!(large_recipe.mints().is_empty());
+    let large_recipe_key = large_recipe_pda(&fixture.recipe_nft.mint);
+    let (_, vault_bump) =
+        Pubkey::find_program_address(&[b"vault", large_recipe_key.as_ref()], &craft_skins::id());
+    assert_eq!(large_recipe.vault_bump, vault_bump);
+
+    // chunks of 2 + 1 => LargeRecipe::MAX_INGREDIENTS
+    let mints: Vec<Pubkey> = (0..LargeRecipe::MAX_INGREDIENTS)
+        .map(|_| Pubkey::new_unique())
+        .collect();
+    for chunk in mints.chunks(2) {
+        process(
+            &mut fixture.banks_client,
+            owner,
+            &[append_large_recipe_ix(
+                &owner.pubkey(),
+                &fixture.recipe_nft,
+                chunk.to_vec(),
+                vec![2; chunk.len()],
+                vec![],
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+    let large_recipe = get_large_recipe(&mut fixture.banks_client, &fixture.recipe_nft.mint).await;
+    assert_eq!(large_recipe.mints(), &mints[..]);
+    assert_eq!(large_recipe.amounts(), &[2; LargeRecipe::MAX_INGREDIENTS]);
+    assert_eq!(large_recipe.revision, 3);
+
+    // MAX_INGREDIENTS + 1
+    let result = process(
+        &mut fixture.banks_client,
+        owner,
+        &[append_large_recipe_ix(
+            &owner.pubkey(),
+            &fixture.recipe_nft,
+            vec![Pubkey::new_unique()],
+            vec![1],
+            vec![],
+        )],
+        &[],
+    )
+    .await;
+    assert_error(result, ErrorCode::TooManyIngredients);
+
+    process(
+        &mut fixture.banks_client,
+        owner,
+        &[reset_large_recipe_ix(
+            &owner.pubkey(),
+            &fixture.recipe_nft,
+            500,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let large_recipe = get_large_recipe(&mut fixture.banks_client, &fixture.recipe_nft.mint).await;
+    assert!(large_recipe.mints().is_empty());
+    assert_eq!(large_recipe.craft_fee, 500);
+    assert_eq!(large_recipe.revision, 4);
+
+    // 2 ingredients (6 accounts) + Skin output (4) > LargeRecipe::MAX_CRAFT_ACCOUNTS
+    let output = |kind| RecipeOutput {
+        kind,
+        mint: Pubkey::new_unique(),
+        amount: 1,
+    };
+    let append = |outputs| {
+        append_large_recipe_ix(
+            &owner.pubkey(),
+            &fixture.recipe_nft,
+            mints[..2].to_vec(),
+            vec![1; 2],
+            outputs,
+        )
+    };
+    let (skin_append, fungible_append) = (
+        append(vec![output(OutputKind::Skin)]),
+        append(vec![output(OutputKind::Fungible)]),
+    );
+    let result = process(&mut fixture.banks_client, owner, &[skin_append], &[]).await;
+    assert_error(result, ErrorCode::CraftAccountsExceeded);
+    // Fungible output (2) fits
+    process(&mut fixture.banks_client, owner, &[fungible_append], &[])
+        .await
+        .unwrap();
+    let large_recipe = get_large_recipe(&mut fixture.banks_client, &fixture.recipe_nft.mint).await;
+    assert_eq!(large_recipe.mints(), &mints[..2]);
+    assert_eq!(large_recipe.output_count, 1);
+}
+
+#[tokio::test]
+async fn only_recipe_holder_appends() {
+    let mut fixture = fixture(0).await;
+    let user = &fixture.user;
+    // user does not hold the Recipe NFT
+    let result = process(
+        &mut fixture.banks_client,
+        user,
+        &[append_large_recipe_ix(
+            &user.pubkey(),
+            &fixture.recipe_nft,
+            vec![Pubkey::new_unique()],
+            vec![1],
+            vec![],
+        )],
+        &[],
+    )
+    .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn rejects_invalid_outputs() {
+    let mut fixture = fixture(0).await;
+    let owner = &fixture.owner;
+    let result = process(
+        &mut fixture.banks_client,
+        owner,
+        &[append_large_recipe_ix(
+            &owner.pubkey(),
+            &fixture.recipe_nft,
+            vec![],
+            vec![],
+            vec![RecipeOutput {
+                kind: OutputKind::Fungible,
+                mint: Pubkey::new_unique(),
+                amount: 0,
+            }],
+        )],
+        &[],
+    )
+    .await;
+    assert_error(result, ErrorCode::OutputAmountInvalid);
+}
+
+#[tokio::test]
+async fn crafts_skin_with_ingredients_read_in_place() {
+    let mut fixture = fixture(LargeRecipe::MAX_INGREDIENTS).await;
+    let owner = &fixture.owner;
+    let user = &fixture.user;
+    process(
+        &mut fixture.banks_client,
+        owner,
+        &[
+            append_large_recipe_ix(
+                &owner.pubkey(),
+                &fixture.recipe_nft,
+                fixture.mints.clone(),
+                vec![1; fixture.mints.len()],
+                vec![],
+            ),
+            add_skin_ix(&owner.pubkey(), &fixture.recipe_nft, &fixture.skin_nft),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let craft = craft_large_skin_ix(
+        &owner.pubkey(),
+        &user.pubkey(),
+        &fixture.admin.pubkey(),
+        &fixture.recipe_nft,
+        &fixture.skin_nft,
+        &fixture.mints,
+    );
+    process(&mut fixture.banks_client, user, &[craft], &[owner])
+        .await
+        .unwrap();
+
+    let user_skin = get_associated_token_address(&user.pubkey(), &fixture.skin_nft.mint);
+    assert_eq!(token_amount(&mut fixture.banks_client, &user_skin).await, 1);
+    for mint in fixture.mints.iter() {
+        let user_token = get_associated_token_address(&user.pubkey(), mint);
+        let recipe_vault = recipe_vault_pda(&large_recipe_pda(&fixture.recipe_nft.mint));
+        let escrow_token = get_associated_token_address(&recipe_vault, mint);
+        assert_eq!(
+            token_amount(&mut fixture.banks_client, &user_token).await,
+            0
+        );
+        assert_eq!(
+            token_amount(&mut fixture.banks_client, &escrow_token).await,
+            1
+        );
+    }
+}
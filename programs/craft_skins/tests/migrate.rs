@@ -0,0 +1,226 @@
+// cargo test-bpf --test migrate
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_lang::{AccountSerialize, AnchorSerialize, Discriminator};
+use common::*;
+use craft_skins::utils::{ErrorCode, Recipe, RecipeCategory, RecipeMode};
+use craft_skins::Manager;
+use solana_program_test::{tokio, BanksClient};
+use solana_sdk::{
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+};
+
+/*
+    accounts as the program wrote them before versioning, both allocated at 240 bytes
+      Recipe  => mints + amounts only
+      Manager => admin only
+    every later field (bumps, version) reads from the zero padding
+*/
+const V0_SPACE: usize = 240;
+
+fn account_v0(discriminator: [u8; 8], fields: impl AnchorSerialize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    fields.serialize(&mut data).unwrap();
+    data.resize(V0_SPACE, 0);
+    data
+}
+
+fn recipe_v0(ingredient: Pubkey) -> Vec<u8> {
+    // mints, amounts
+    account_v0(Recipe::discriminator(), (vec![ingredient], vec![2u64]))
+}
+
+fn manager_v0(admin: &Pubkey) -> Vec<u8> {
+    account_v0(Manager::discriminator(), *admin)
+}
+
+fn recipe_data(ingredient: Pubkey, bump: u8, version: u8) -> Vec<u8> {
+    let recipe = Recipe {
+        mints: vec![ingredient],
+        amounts: vec![2],
+        craft_fee: 1_000,
+        royalty_bps: 0,
+        outputs: vec![],
+        mode: RecipeMode::Fixed,
+        loot_table: vec![],
+        randomness_program: None,
+        reveal_delay: 0,
+        reveal_timeout: 0,
+        craft_duration: 0,
+        speed_up_mint: None,
+        speed_up_amount: 0,
+        cancel_penalty_bps: 0,
+        salvage_bps: 0,
+        input_skins: vec![],
+        revision: 3,
+        gate: None,
+        allowlist_root: None,
+        bump,
+        vault_bump: 0,
+        version,
+        name: String::new(),
+        description: String::new(),
+        category: RecipeCategory::Uncategorized,
+        sort_order: 0,
+        open_crafts: 0,
+    };
+    let mut data = vec![];
+    recipe.try_serialize(&mut data).unwrap();
+    data
+}
+
+// owner's Recipe + the Manager in their pre-versioning layouts
+async fn accounts_v0() -> (BanksClient, Keypair, Nft, Pubkey) {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let ingredient = add_mint(&mut program_test, &owner.pubkey(), 0);
+
+    add_program_account(
+        &mut program_test,
+        recipe_pda(&recipe_nft.mint),
+        recipe_v0(ingredient),
+    );
+    add_program_account(
+        &mut program_test,
+        manager_pda(),
+        manager_v0(&owner.pubkey()),
+    );
+
+    let (banks_client, _payer, _) = program_test.start().await;
+    (banks_client, owner, recipe_nft, ingredient)
+}
+
+#[tokio::test]
+async fn update_refuses_recipe_of_newer_version() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let ingredient = add_mint(&mut program_test, &owner.pubkey(), 0);
+    let (recipe, bump) =
+        Pubkey::find_program_address(&[b"recipe", recipe_nft.mint.as_ref()], &craft_skins::id());
+    add_program_account(
+        &mut program_test,
+        recipe,
+        recipe_data(ingredient, bump, Recipe::VERSION + 1),
+    );
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let args = recipe_args(vec![ingredient], vec![5]);
+    let instruction = update_recipe_ix(&owner.pubkey(), &recipe_nft, args);
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::AccountVersionUnknown);
+}
+
+#[tokio::test]
+async fn update_refuses_version_0_recipe_without_bump() {
+    let (mut banks_client, owner, recipe_nft, ingredient) = accounts_v0().await;
+
+    // stored bump reads 0 until migrate_recipe finds it
+    let args = recipe_args(vec![ingredient], vec![5]);
+    let instruction = update_recipe_ix(&owner.pubkey(), &recipe_nft, args);
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintSeeds);
+}
+
+#[tokio::test]
+async fn migrate_recipe_grows_account_and_keeps_settings() {
+    let (mut banks_client, owner, recipe_nft, ingredient) = accounts_v0().await;
+    let recipe = recipe_pda(&recipe_nft.mint);
+
+    let instruction = migrate_recipe_ix(&owner.pubkey(), &recipe_nft.mint);
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let account = banks_client.get_account(recipe).await.unwrap().unwrap();
+    assert_eq!(account.data.len(), Recipe::LEN);
+    assert!(account.lamports >= Rent::default().minimum_balance(Recipe::LEN));
+    let migrated: Recipe = get_account(&mut banks_client, &recipe).await;
+    let (_, bump) =
+        Pubkey::find_program_address(&[b"recipe", recipe_nft.mint.as_ref()], &craft_skins::id());
+    let (_, vault_bump) =
+        Pubkey::find_program_address(&[b"vault", recipe.as_ref()], &craft_skins::id());
+    assert_eq!(migrated.version, Recipe::VERSION);
+    assert_eq!(migrated.bump, bump);
+    assert_eq!(migrated.vault_bump, vault_bump);
+    assert_eq!(migrated.mints, vec![ingredient]);
+    assert_eq!(migrated.amounts, vec![2]);
+    assert_eq!(migrated.craft_fee, 0);
+    assert!(migrated.mode == RecipeMode::Fixed);
+
+    // migrated recipes are updated as usual, migrating again changes nothing
+    let args = recipe_args(vec![ingredient], vec![5]);
+    let instructions = [
+        update_recipe_ix(&owner.pubkey(), &recipe_nft, args),
+        migrate_recipe_ix(&owner.pubkey(), &recipe_nft.mint),
+    ];
+    process(&mut banks_client, &owner, &instructions, &[])
+        .await
+        .unwrap();
+    let updated: Recipe = get_account(&mut banks_client, &recipe).await;
+    assert_eq!(updated.amounts, vec![5]);
+    assert_eq!(updated.revision, 1);
+    assert_eq!(updated.bump, bump);
+    assert_eq!(updated.version, Recipe::VERSION);
+}
+
+#[tokio::test]
+async fn migrate_manager_keeps_admin_and_stores_bumps() {
+    let (mut banks_client, owner, _, _) = accounts_v0().await;
+    let before: Manager = get_account(&mut banks_client, &manager_pda()).await;
+    assert_eq!(before.version, 0);
+    assert_eq!(before.bump, 0);
+
+    let instruction = migrate_manager_ix(&owner.pubkey());
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let manager: Manager = get_account(&mut banks_client, &manager_pda()).await;
+    assert_eq!(manager.version, Manager::VERSION);
+    assert_eq!(manager.admin, owner.pubkey());
+    assert_eq!(
+        manager.bump,
+        Pubkey::find_program_address(&[b"manager"], &craft_skins::id()).1
+    );
+    assert_eq!(
+        manager.signer_bump,
+        Pubkey::find_program_address(&[b"signer"], &craft_skins::id()).1
+    );
+}
+
+#[tokio::test]
+async fn migrate_rejects_accounts_of_other_types() {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    // Manager data at the recipe PDA is not a Recipe
+    add_program_account(
+        &mut program_test,
+        recipe_pda(&recipe_nft.mint),
+        manager_v0(&owner.pubkey()),
+    );
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let instruction = migrate_recipe_ix(&owner.pubkey(), &recipe_nft.mint);
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(
+        result,
+        anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch,
+    );
+}
+
+#[tokio::test]
+async fn migrate_recipe_rejects_account_off_the_recipe_pda() {
+    let (mut banks_client, owner, recipe_nft, _) = accounts_v0().await;
+
+    let mut instruction = migrate_recipe_ix(&owner.pubkey(), &recipe_nft.mint);
+    instruction.accounts[1].pubkey = manager_pda();
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(result, anchor_lang::error::ErrorCode::ConstraintSeeds);
+}
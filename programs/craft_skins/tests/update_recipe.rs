@@ -0,0 +1,129 @@
+// cargo test-bpf --test update_recipe
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_lang::AccountSerialize;
+use common::*;
+use craft_skins::utils::{ErrorCode, Recipe, RecipeCategory};
+use solana_program_test::{tokio, BanksClient};
+use solana_sdk::signature::{Keypair, Signer};
+
+// owner's Recipe created with one ingredient + another funded wallet
+async fn recipe_created() -> (BanksClient, Keypair, Nft, Keypair) {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let other = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let ingredient = add_mint(&mut program_test, &owner.pubkey(), 0);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let instruction = create_recipe_ix(&owner.pubkey(), &recipe_nft, vec![ingredient], vec![2]);
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+    (banks_client, owner, recipe_nft, other)
+}
+
+#[tokio::test]
+async fn update_replaces_settings_and_bumps_revision() {
+    let (mut banks_client, owner, recipe_nft, _) = recipe_created().await;
+    let recipe: Recipe = get_account(&mut banks_client, &recipe_pda(&recipe_nft.mint)).await;
+    assert_eq!(recipe.revision, 1);
+
+    let mut args = recipe_args(recipe.mints.clone(), vec![5]);
+    args.craft_fee = 1_000;
+    args.royalty_bps = 500;
+    args.name = "Scrap Blade".to_string();
+    args.description = "Forged from scrap".to_string();
+    args.category = RecipeCategory::Weapon;
+    args.sort_order = 3;
+    let instruction = update_recipe_ix(&owner.pubkey(), &recipe_nft, args);
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+
+    let updated: Recipe = get_account(&mut banks_client, &recipe_pda(&recipe_nft.mint)).await;
+    assert_eq!(updated.mints, recipe.mints);
+    assert_eq!(updated.amounts, vec![5]);
+    assert_eq!(updated.craft_fee, 1_000);
+    assert_eq!(updated.royalty_bps, 500);
+    assert_eq!(updated.revision, 2);
+    assert_eq!(updated.bump, recipe.bump);
+    assert_eq!(updated.name, "Scrap Blade");
+    assert_eq!(updated.description, "Forged from scrap");
+    assert!(updated.category == RecipeCategory::Weapon);
+    assert_eq!(updated.sort_order, 3);
+
+    // shorter settings leave only zeros after the serialized Recipe
+    let instruction = update_recipe_ix(&owner.pubkey(), &recipe_nft, recipe_args(vec![], vec![]));
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+    let recipe_key = recipe_pda(&recipe_nft.mint);
+    let cleared: Recipe = get_account(&mut banks_client, &recipe_key).await;
+    let mut serialized = vec![];
+    cleared.try_serialize(&mut serialized).unwrap();
+    let account = banks_client.get_account(recipe_key).await.unwrap().unwrap();
+    assert_eq!(account.data[..serialized.len()], serialized[..]);
+    assert!(account.data[serialized.len()..]
+        .iter()
+        .all(|byte| *byte == 0));
+}
+
+#[tokio::test]
+async fn update_checks_settings() {
+    let (mut banks_client, owner, recipe_nft, _) = recipe_created().await;
+
+    let mut args = recipe_args(vec![], vec![]);
+    args.royalty_bps = 10_001;
+    let instruction = update_recipe_ix(&owner.pubkey(), &recipe_nft, args);
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::RoyaltyInvalid);
+}
+
+#[tokio::test]
+async fn update_by_other_wallet_fails() {
+    let (mut banks_client, _owner, recipe_nft, other) = recipe_created().await;
+
+    // other signs with the owner's Recipe NFT token account
+    let instruction = update_recipe_ix(&other.pubkey(), &recipe_nft, recipe_args(vec![], vec![]));
+    let result = process(&mut banks_client, &other, &[instruction], &[]).await;
+    assert!(result.is_err());
+    let recipe: Recipe = get_account(&mut banks_client, &recipe_pda(&recipe_nft.mint)).await;
+    assert_eq!(recipe.revision, 1);
+}
+
+#[tokio::test]
+async fn close_refunds_rent_to_owner() {
+    let (mut banks_client, owner, recipe_nft, other) = recipe_created().await;
+    let recipe = recipe_pda(&recipe_nft.mint);
+    let rent = lamports(&mut banks_client, &recipe).await;
+    let before = lamports(&mut banks_client, &owner.pubkey()).await;
+
+    // other pays the fee so owner receives exactly the rent
+    let instruction = close_recipe_ix(&owner.pubkey(), &recipe_nft);
+    process(&mut banks_client, &other, &[instruction], &[&owner])
+        .await
+        .unwrap();
+
+    assert!(banks_client.get_account(recipe).await.unwrap().is_none());
+    assert_eq!(
+        lamports(&mut banks_client, &owner.pubkey()).await,
+        before + rent
+    );
+}
+
+#[tokio::test]
+async fn close_by_other_wallet_fails() {
+    let (mut banks_client, _owner, recipe_nft, other) = recipe_created().await;
+
+    let instruction = close_recipe_ix(&other.pubkey(), &recipe_nft);
+    let result = process(&mut banks_client, &other, &[instruction], &[]).await;
+    assert!(result.is_err());
+    assert!(banks_client
+        .get_account(recipe_pda(&recipe_nft.mint))
+        .await
+        .unwrap()
+        .is_some());
+}
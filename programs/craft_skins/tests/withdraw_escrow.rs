@@ -0,0 +1,97 @@
+// cargo test-bpf --test withdraw_escrow
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use craft_skins::utils::ErrorCode;
+use solana_program_test::{tokio, BanksClient};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+// Manager initialized by admin, program_signer escrows 10 of mint
+struct Escrow {
+    banks_client: BanksClient,
+    admin: Keypair,
+    other: Keypair,
+    mint: Pubkey,
+    escrow: Pubkey,
+}
+
+async fn escrow() -> Escrow {
+    let mut program_test = program_test();
+    let admin = add_wallet(&mut program_test);
+    let other = add_wallet(&mut program_test);
+    let mint = add_mint(&mut program_test, &admin.pubkey(), 10);
+    let escrow = add_token_account(&mut program_test, &mint, &signer_pda(), 10);
+    add_token_account(&mut program_test, &mint, &admin.pubkey(), 0);
+    add_token_account(&mut program_test, &mint, &other.pubkey(), 0);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let instruction = initialize_ix(&admin.pubkey());
+    process(&mut banks_client, &admin, &[instruction], &[])
+        .await
+        .unwrap();
+    Escrow {
+        banks_client,
+        admin,
+        other,
+        mint,
+        escrow,
+    }
+}
+
+#[tokio::test]
+async fn admin_withdraws_to_destination() {
+    let Escrow {
+        mut banks_client,
+        admin,
+        mint,
+        escrow,
+        ..
+    } = escrow().await;
+    let destination = get_associated_token_address(&admin.pubkey(), &mint);
+
+    let instruction = withdraw_escrow_ix(&admin.pubkey(), &mint, &destination, 4);
+    process(&mut banks_client, &admin, &[instruction], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(token_amount(&mut banks_client, &escrow).await, 6);
+    assert_eq!(token_amount(&mut banks_client, &destination).await, 4);
+}
+
+#[tokio::test]
+async fn withdraw_more_than_escrowed() {
+    let Escrow {
+        mut banks_client,
+        admin,
+        mint,
+        ..
+    } = escrow().await;
+    let destination = get_associated_token_address(&admin.pubkey(), &mint);
+
+    let instruction = withdraw_escrow_ix(&admin.pubkey(), &mint, &destination, 11);
+    let result = process(&mut banks_client, &admin, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::NotEnoughToken);
+}
+
+#[tokio::test]
+async fn withdraw_by_other_wallet() {
+    let Escrow {
+        mut banks_client,
+        other,
+        mint,
+        escrow,
+        ..
+    } = escrow().await;
+    let destination = get_associated_token_address(&other.pubkey(), &mint);
+
+    let instruction = withdraw_escrow_ix(&other.pubkey(), &mint, &destination, 1);
+    let result = process(&mut banks_client, &other, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::AdminInvalid);
+    assert_eq!(token_amount(&mut banks_client, &escrow).await, 10);
+}
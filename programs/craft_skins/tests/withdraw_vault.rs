@@ -0,0 +1,164 @@
+// cargo test-bpf --test withdraw_vault
+#![cfg(feature = "test-bpf")]
+
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use common::*;
+use craft_skins::utils::ErrorCode;
+use solana_program_test::{tokio, BanksClient};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+// owner's Recipe, its vault + the vault of another recipe stock 10 of mint
+struct Vault {
+    banks_client: BanksClient,
+    owner: Keypair,
+    other: Keypair,
+    recipe_nft: Nft,
+    other_recipe: Pubkey,
+    mint: Pubkey,
+    vault_token: Pubkey,
+}
+
+async fn vault() -> Vault {
+    let mut program_test = program_test();
+    let owner = add_wallet(&mut program_test);
+    let other = add_wallet(&mut program_test);
+    let recipe_nft = add_nft(&mut program_test, &owner.pubkey(), &owner.pubkey(), None);
+    let ingredient = add_mint(&mut program_test, &owner.pubkey(), 0);
+    let mint = add_mint(&mut program_test, &owner.pubkey(), 10);
+    let vault = recipe_vault_pda(&recipe_pda(&recipe_nft.mint));
+    let vault_token = add_token_account(&mut program_test, &mint, &vault, 10);
+    let other_recipe = recipe_pda(&Pubkey::new_unique());
+    let other_vault = recipe_vault_pda(&other_recipe);
+    add_token_account(&mut program_test, &mint, &other_vault, 10);
+    add_token_account(&mut program_test, &mint, &owner.pubkey(), 0);
+    add_token_account(&mut program_test, &mint, &other.pubkey(), 0);
+    let (mut banks_client, _payer, _) = program_test.start().await;
+
+    let instruction = create_recipe_ix(&owner.pubkey(), &recipe_nft, vec![ingredient], vec![1]);
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+    Vault {
+        banks_client,
+        owner,
+        other,
+        recipe_nft,
+        other_recipe,
+        mint,
+        vault_token,
+    }
+}
+
+#[tokio::test]
+async fn holder_withdraws_to_destination() {
+    let Vault {
+        mut banks_client,
+        owner,
+        recipe_nft,
+        mint,
+        vault_token,
+        ..
+    } = vault().await;
+    let destination = get_associated_token_address(&owner.pubkey(), &mint);
+    let recipe = recipe_pda(&recipe_nft.mint);
+
+    let instruction = withdraw_vault_ix(
+        &owner.pubkey(),
+        &recipe,
+        &recipe_nft,
+        &mint,
+        &destination,
+        4,
+    );
+    process(&mut banks_client, &owner, &[instruction], &[])
+        .await
+        .unwrap();
+
+    assert_eq!(token_amount(&mut banks_client, &vault_token).await, 6);
+    assert_eq!(token_amount(&mut banks_client, &destination).await, 4);
+}
+
+#[tokio::test]
+async fn withdraw_more_than_stocked() {
+    let Vault {
+        mut banks_client,
+        owner,
+        recipe_nft,
+        mint,
+        ..
+    } = vault().await;
+    let destination = get_associated_token_address(&owner.pubkey(), &mint);
+    let recipe = recipe_pda(&recipe_nft.mint);
+
+    let instruction = withdraw_vault_ix(
+        &owner.pubkey(),
+        &recipe,
+        &recipe_nft,
+        &mint,
+        &destination,
+        11,
+    );
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::NotEnoughToken);
+}
+
+#[tokio::test]
+async fn withdraw_by_other_wallet() {
+    let Vault {
+        mut banks_client,
+        other,
+        recipe_nft,
+        mint,
+        vault_token,
+        ..
+    } = vault().await;
+    let destination = get_associated_token_address(&other.pubkey(), &mint);
+    let recipe = recipe_pda(&recipe_nft.mint);
+
+    let instruction = withdraw_vault_ix(
+        &other.pubkey(),
+        &recipe,
+        &recipe_nft,
+        &mint,
+        &destination,
+        1,
+    );
+    let result = process(&mut banks_client, &other, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::RecipeHolderInvalid);
+    assert_eq!(token_amount(&mut banks_client, &vault_token).await, 10);
+}
+
+#[tokio::test]
+async fn withdraw_from_vault_of_other_recipe() {
+    // holder of one Recipe NFT names the vault of a recipe it does not hold
+    let Vault {
+        mut banks_client,
+        owner,
+        recipe_nft,
+        other_recipe,
+        mint,
+        ..
+    } = vault().await;
+    let destination = get_associated_token_address(&owner.pubkey(), &mint);
+
+    let instruction = withdraw_vault_ix(
+        &owner.pubkey(),
+        &other_recipe,
+        &recipe_nft,
+        &mint,
+        &destination,
+        1,
+    );
+    let result = process(&mut banks_client, &owner, &[instruction], &[]).await;
+    assert_error(result, ErrorCode::DerivedKeyInvalid);
+    let other_vault_token = get_associated_token_address(&recipe_vault_pda(&other_recipe), &mint);
+    assert_eq!(
+        token_amount(&mut banks_client, &other_vault_token).await,
+        10
+    );
+}
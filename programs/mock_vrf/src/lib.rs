@@ -0,0 +1,60 @@
+// handlers return anchor_lang::Result, the size of its Error is set by anchor
+#![allow(clippy::result_large_err)]
+
+use anchor_lang::prelude::*;
+
+declare_id!("7UKQbXNXF61htDpQ8UAAYvs81C6zg7kqW8MgKRUJdj4W");
+
+/*
+    Stand-in for a VRF program in localnet tests
+    request_randomness opens an unfulfilled VrfResult at ["randomness", recipe, user, request]
+    fulfill_randomness writes caller-chosen randomness, the oracle's job on a real VRF
+    craft_skins reads the 32 bytes after the Anchor discriminator
+*/
+#[program]
+pub mod mock_vrf {
+    use super::*;
+
+    pub fn request_randomness(
+        _ctx: Context<RequestRandomness>,
+        _recipe: Pubkey,
+        _user: Pubkey,
+        _request: u64,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    pub fn fulfill_randomness(ctx: Context<FulfillRandomness>, randomness: [u8; 32]) -> Result<()> {
+        ctx.accounts.vrf_result.randomness = randomness;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(recipe: Pubkey, user: Pubkey, request: u64)]
+pub struct RequestRandomness<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + 32,
+        seeds = [b"randomness", recipe.as_ref(), user.as_ref(), &request.to_le_bytes()],
+        bump
+    )]
+    pub vrf_result: Account<'info, VrfResult>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FulfillRandomness<'info> {
+    #[account(mut)]
+    pub vrf_result: Account<'info, VrfResult>,
+}
+
+#[account]
+pub struct VrfResult {
+    pub randomness: [u8; 32],
+}
@@ -3,6 +3,7 @@ import { PublicKey, AccountMeta } from "@solana/web3.js";
 import { BN } from "bn.js";
 import { Program } from "@project-serum/anchor";
 import { CraftSkins } from "../target/types/craft_skins";
+import { MockVrf } from "../target/types/mock_vrf";
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
   Token,
@@ -16,7 +17,11 @@ import {
   createSkin,
   verifySkinCollection,
   getRecipeAccount,
+  getRecipeVault,
+  getPlayerProfile,
+  recipeSettings,
   createNewIngredient,
+  createOutputMint,
   airdropIngredient
 } from './utils/utils'
 import {
@@ -38,6 +43,8 @@ let wallet = (provider.wallet as anchor.Wallet).payer
 describe("craft_skins", () => {
 
   const program = anchor.workspace.CraftSkins as Program<CraftSkins>;
+  // stands in for a VRF program in Random mode recipes
+  const mock_vrf = anchor.workspace.MockVrf as Program<MockVrf>;
 
   // admin: human
   let manager: anchor.web3.Keypair
@@ -61,6 +68,8 @@ describe("craft_skins", () => {
   let skin_ata: anchor.web3.PublicKey
 
   let ingredient: anchor.web3.Keypair = anchor.web3.Keypair.generate();
+  // bonus fungible output, minted by the recipe vault on craft
+  let xp_mint: anchor.web3.Keypair = anchor.web3.Keypair.generate();
 
 /** ============================================================================================
                                         I N I T I A L I Z E   
@@ -155,11 +164,33 @@ describe("craft_skins", () => {
     let ingredientAmounts = []
     ingredientAmounts.push(new BN(10));
 
+    // lamports paid per craft, 10% royalty to Recipe NFT holder
+    let craftFee = new BN(anchor.web3.LAMPORTS_PER_SOL / 100);
+    let royaltyBps = 1000;
+
+    // recipe vault mints 5 XP tokens to user on every craft
+    [program_signer, program_signer_bump] =
+      await anchor.web3.PublicKey.findProgramAddress(
+        [Buffer.from("signer")],
+        program.programId
+      );
+    let [recipe_vault, _vault_bump] = await getRecipeVault(recipe_account, program.programId);
+    let xp_tx = await createOutputMint(
+      xp_mint.publicKey,
+      provider.wallet.publicKey,
+      recipe_vault,
+      lamports
+    );
+    await provider.sendAndConfirm(xp_tx, [xp_mint]);
+    let outputs = [
+      { kind: { fungible: {} }, mint: xp_mint.publicKey, amount: new BN(5) }
+    ];
+
     // call anchor program create_recipe
     try {
-      const create_recipe_tx = await program.methods.createRecipe(
-        ingredientMints, ingredientAmounts
-        )
+      const create_recipe_tx = await program.methods.createRecipe(recipeSettings({
+        ingredientMints, ingredientAmounts, craftFee, royaltyBps, outputs, salvageBps: 5000
+        }))
         .accounts({
           owner: provider.wallet.publicKey,
           recipe: recipe_account,
@@ -182,6 +213,8 @@ describe("craft_skins", () => {
       console.log(`${display.white}`,'created recipe ->');
       console.log('mints ', created_recipe.mints.toString());
       console.log('amounts', created_recipe.amounts.map(num => num.toNumber()));
+      console.log('craft fee', created_recipe.craftFee.toNumber(), 'royalty bps', created_recipe.royaltyBps);
+      console.log('outputs', created_recipe.outputs.map(output => output.mint.toString()));
 
       let recipe_metadata = await provider.connection.getAccountInfo(
         recipe_metadata_PDA
@@ -365,6 +398,8 @@ describe("craft_skins", () => {
     );
     // find recipe PDA from collectionMint
     let [skinRecipePDA, _] = await getRecipeAccount(skinCollectionMint, program.programId);
+    // recipe vault, mints the fungible outputs
+    let [skinRecipeVault, _vault_bump] = await getRecipeVault(skinRecipePDA, program.programId);
     // recipe account (mints[], amounts[])
     let skinRecipe = await program.account.recipe.fetch(skinRecipePDA);
 
@@ -387,15 +422,15 @@ describe("craft_skins", () => {
         program.programId
       );
 
-    // find program escrow PDAs to transfer each ingredient mint to
+    // find recipe vault ATAs to escrow each ingredient mint in
     let escrow_tokens: anchor.web3.PublicKey[] = [];
     for (let x = 0; x < user_tokens.length; x++) {
-      // program escrow PDA derived from user ingredient token account + "escrow" string
+      // recipe vault ATA of the ingredient mint
       let escrow = await Token.getAssociatedTokenAddress(
         ASSOCIATED_TOKEN_PROGRAM_ID, // always associated token program id
         TOKEN_PROGRAM_ID, // always token program id
         skinRecipe.mints[x], // mint
-        program_signer, // token account authority,
+        skinRecipeVault, // token account authority,
         true
       );
       escrow_tokens.push(escrow);
@@ -408,20 +443,49 @@ describe("craft_skins", () => {
       remaining_accounts.push({pubkey: user_tokens[k], isSigner: false, isWritable: true});
       // add user_token.mint as Mint account
       remaining_accounts.push({pubkey: skinRecipe.mints[k], isSigner: false, isWritable: true})
-      // add program escrow PDAs to hold user tokens
+      // add recipe vault ATAs to hold user tokens
       remaining_accounts.push({pubkey: escrow_tokens[k], isSigner: false, isWritable: true})
     }
+    // add recipe outputs after ingredient triplets
+    for (let output of skinRecipe.outputs) {
+      let user_output = await Token.getAssociatedTokenAddress(
+        ASSOCIATED_TOKEN_PROGRAM_ID,
+        TOKEN_PROGRAM_ID,
+        output.mint,
+        user.publicKey
+      );
+      // fungible output => [mint, user token account]
+      remaining_accounts.push({pubkey: output.mint, isSigner: false, isWritable: true});
+      remaining_accounts.push({pubkey: user_output, isSigner: false, isWritable: true});
+    }
+    // recipe has salvageBps, salvage owed for the skin is recorded after the outputs
+    let [salvage_record, _salvage_bump] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("salvage"), skinRecipePDA.toBuffer(), skinToBuy.toBuffer()],
+      program.programId
+    );
+    remaining_accounts.push({pubkey: salvage_record, isSigner: false, isWritable: true});
     console.log('remaining accounts ', remaining_accounts.map(account => {
       return account.pubkey.toString()
     }))
 
+    // crafting statistics of user, created by first craft
+    let [player_profile, ____] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("profile"), user.publicKey.toBuffer()],
+      program.programId
+    );
+
     try {
-      const craft_skin_tx = await program.methods.craftSkin()
+      const craft_skin_tx = await program.methods.craftSkin(0, [])
         .accounts({
           owner: provider.wallet.publicKey,
           user: user.publicKey,
           programSigner: program_signer,
+          programManager: program_manager_acc,
+          admin: manager.publicKey,
+          recipeHolder: provider.wallet.publicKey,
+          playerProfile: player_profile,
           recipe: skinRecipePDA,
+          recipeVault: skinRecipeVault,
           recipeTokenAccount: skinCollectionATA,
           recipeMint: skinCollectionMint,
           recipeMetadata: skinCollectionMetadata,
@@ -440,10 +504,520 @@ describe("craft_skins", () => {
         .signers([wallet, user])
         .rpc()
       console.log(`${display.green}`,`${display.grapes} CraftSkin transaction signature `, craft_skin_tx);
+
+      // recipe holder receives royalty share of craft fee
+      let holder_balance = await provider.connection.getBalance(provider.wallet.publicKey);
+      let admin_balance = await provider.connection.getBalance(manager.publicKey);
+      console.log('recipe holder balance: ', holder_balance, ' admin balance: ', admin_balance);
+
+      let profile = await program.account.playerProfile.fetch(player_profile);
+      console.log('player total crafts: ', profile.totalCrafts.toNumber(), ' recipe crafts: ', profile.recipes.map(count => {
+        return count.recipe.toString() + ' => ' + count.crafts
+      }));
     } catch (err) {
       console.log(`${display.red}`,`${display.bomb} craft_skin failed`, err);
     }
 
   }); // end craftSkin
 
+/** ============================================================================================
+                              C R A F T      R A N D O M      S K I N   
+    ============================================================================================   
+**/
+
+  it("Craft random skin", async () => {
+
+    // new Recipe NFT for a Random mode recipe
+    let lamports = await Token.getMinBalanceRentForExemptMint(provider.connection);
+    const data = recipe_nft_data(manager.publicKey);
+    let [
+      random_recipe_mint,
+      random_recipe_metadata_PDA,
+      random_recipe_mint_tx,
+      random_recipe_ata,
+      random_recipe_master_edition
+    ] = await createRecipe(
+      provider.wallet.publicKey, // authority/payer
+      provider.wallet.publicKey, // destination (owner)
+      lamports,
+      data, // metadata account
+      recipe_json_url // metadata URI
+    );
+    await provider.sendAndConfirm(random_recipe_mint_tx, [random_recipe_mint]);
+    let [random_recipe_account, _] = await getRecipeAccount(
+      random_recipe_mint.publicKey,
+      program.programId
+    );
+
+    let [random_recipe_vault, __] = await getRecipeVault(random_recipe_account, program.programId);
+
+    // loot pools: common (90%) and rare (10%) fungible tokens minted by the recipe vault
+    let common_mint = anchor.web3.Keypair.generate();
+    let rare_mint = anchor.web3.Keypair.generate();
+    for (let pool of [common_mint, rare_mint]) {
+      let pool_tx = await createOutputMint(
+        pool.publicKey,
+        provider.wallet.publicKey,
+        random_recipe_vault,
+        lamports
+      );
+      await provider.sendAndConfirm(pool_tx, [pool]);
+    }
+    let lootTable = [
+      { weight: 90, output: { kind: { fungible: {} }, mint: common_mint.publicKey, amount: new BN(1) } },
+      { weight: 10, output: { kind: { fungible: {} }, mint: rare_mint.publicKey, amount: new BN(1) } },
+    ];
+
+    try {
+      const create_recipe_tx = await program.methods.createRecipe(recipeSettings({
+        ingredientMints: [ingredient.publicKey], ingredientAmounts: [new BN(10)],
+        mode: { random: {} }, lootTable
+        }))
+        .accounts({
+          owner: provider.wallet.publicKey,
+          recipe: random_recipe_account,
+          recipeTokenAccount: random_recipe_ata,
+          recipeMint: random_recipe_mint.publicKey,
+          recipeMetadata: random_recipe_metadata_PDA,
+          recipeMasterEdition: random_recipe_master_edition,
+          rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId
+        })
+        .signers([wallet])
+        .rpc()
+      console.log(`${display.green}`,`${display.unicorn} CreateRecipe (random) transaction signature `, create_recipe_tx);
+    } catch (err) {
+      console.log(`${display.red}`,`${display.bomb} create_recipe (random) failed`, err);
+    }
+
+    // create test user holding the ingredient
+    const user = anchor.web3.Keypair.generate();
+    let airdrop = await provider.connection.requestAirdrop(user.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    let airdrop_tx = await airdropIngredient(
+      ingredient.publicKey, // ingredient mint to transfer
+      provider.wallet.publicKey, // owner
+      user.publicKey, // new owner
+      10 // amount to transfer
+    );
+    await provider.sendAndConfirm(airdrop_tx);
+
+    // ingredient triplet, then accounts for every loot entry
+    let user_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, user.publicKey
+    );
+    let escrow_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, random_recipe_vault, true
+    );
+    let remaining_accounts: AccountMeta[] = [
+      {pubkey: user_ingredient, isSigner: false, isWritable: true},
+      {pubkey: ingredient.publicKey, isSigner: false, isWritable: true},
+      {pubkey: escrow_ingredient, isSigner: false, isWritable: true},
+    ];
+    for (let loot of lootTable) {
+      let user_loot = await Token.getAssociatedTokenAddress(
+        ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, loot.output.mint, user.publicKey
+      );
+      remaining_accounts.push({pubkey: loot.output.mint, isSigner: false, isWritable: true});
+      remaining_accounts.push({pubkey: user_loot, isSigner: false, isWritable: true});
+    }
+
+    // roll is recorded in CraftEvent
+    let listener = program.addEventListener("CraftEvent", (event, slot) => {
+      console.log('craft event: skin ', event.skinMint.toString(), ' roll ', event.roll);
+    });
+
+    let [player_profile, _profile_bump] = await getPlayerProfile(user.publicKey, program.programId);
+
+    try {
+      // allowance + proof are ignored without an allowlist
+      const craft_random_tx = await program.methods.craftRandomSkin(0, [])
+        .accounts({
+          user: user.publicKey,
+          programSigner: program_signer,
+          programManager: program_manager_acc,
+          admin: manager.publicKey,
+          recipeHolder: provider.wallet.publicKey,
+          playerProfile: player_profile,
+          recipe: random_recipe_account,
+          recipeVault: random_recipe_vault,
+          recipeTokenAccount: random_recipe_ata,
+          recipeMint: random_recipe_mint.publicKey,
+          randomness: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ataProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId
+        })
+        .remainingAccounts(remaining_accounts)
+        .signers([user])
+        .rpc()
+      console.log(`${display.green}`,`${display.grapes} CraftRandomSkin transaction signature `, craft_random_tx);
+    } catch (err) {
+      console.log(`${display.red}`,`${display.bomb} craft_random_skin failed`, err);
+    }
+    await program.removeEventListener(listener);
+
+  }); // end craftRandomSkin
+
+/** ============================================================================================
+                              C O M M I T      R E V E A L      C R A F T   
+    ============================================================================================   
+**/
+
+  it("Begin and finish craft", async () => {
+
+    // new Recipe NFT for a commit-reveal recipe rolled from slot hashes
+    let lamports = await Token.getMinBalanceRentForExemptMint(provider.connection);
+    const data = recipe_nft_data(manager.publicKey);
+    let [
+      reveal_recipe_mint,
+      reveal_recipe_metadata_PDA,
+      reveal_recipe_mint_tx,
+      reveal_recipe_ata,
+      reveal_recipe_master_edition
+    ] = await createRecipe(
+      provider.wallet.publicKey, // authority/payer
+      provider.wallet.publicKey, // destination (owner)
+      lamports,
+      data, // metadata account
+      recipe_json_url // metadata URI
+    );
+    await provider.sendAndConfirm(reveal_recipe_mint_tx, [reveal_recipe_mint]);
+    let [reveal_recipe_account, _] = await getRecipeAccount(
+      reveal_recipe_mint.publicKey,
+      program.programId
+    );
+    let [reveal_recipe_vault, ___] = await getRecipeVault(reveal_recipe_account, program.programId);
+
+    // loot pools minted by the recipe vault
+    let common_mint = anchor.web3.Keypair.generate();
+    let rare_mint = anchor.web3.Keypair.generate();
+    for (let pool of [common_mint, rare_mint]) {
+      let pool_tx = await createOutputMint(
+        pool.publicKey,
+        provider.wallet.publicKey,
+        reveal_recipe_vault,
+        lamports
+      );
+      await provider.sendAndConfirm(pool_tx, [pool]);
+    }
+    let lootTable = [
+      { weight: 3, output: { kind: { fungible: {} }, mint: common_mint.publicKey, amount: new BN(1) } },
+      { weight: 1, output: { kind: { fungible: {} }, mint: rare_mint.publicKey, amount: new BN(1) } },
+    ];
+
+    // reveal 2 slots after begin_craft, refund allowed 512 slots after reveal keeps 10%
+    await program.methods.createRecipe(recipeSettings({
+      ingredientMints: [ingredient.publicKey], ingredientAmounts: [new BN(10)],
+      mode: { random: {} }, lootTable, revealDelay: new BN(2), revealTimeout: new BN(512),
+      cancelPenaltyBps: 1000
+      }))
+      .accounts({
+        owner: provider.wallet.publicKey,
+        recipe: reveal_recipe_account,
+        recipeTokenAccount: reveal_recipe_ata,
+        recipeMint: reveal_recipe_mint.publicKey,
+        recipeMetadata: reveal_recipe_metadata_PDA,
+        recipeMasterEdition: reveal_recipe_master_edition,
+        rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId
+      })
+      .signers([wallet])
+      .rpc()
+
+    // create test user holding the ingredient
+    const user = anchor.web3.Keypair.generate();
+    let airdrop = await provider.connection.requestAirdrop(user.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    let airdrop_tx = await airdropIngredient(
+      ingredient.publicKey, // ingredient mint to transfer
+      provider.wallet.publicKey, // owner
+      user.publicKey, // new owner
+      10 // amount to transfer
+    );
+    await provider.sendAndConfirm(airdrop_tx);
+
+    let user_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, user.publicKey
+    );
+    let [pending_craft, __] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("pending"), reveal_recipe_account.toBuffer(), user.publicKey.toBuffer()],
+      program.programId
+    );
+    // pending craft holds the escrow until finish / refund / cancel
+    let escrow_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, pending_craft, true
+    );
+    let vault_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, reveal_recipe_vault, true
+    );
+
+    let [player_profile, _profile_bump] = await getPlayerProfile(user.publicKey, program.programId);
+
+    try {
+      // request nonce only binds VRF results, slot hashes recipes pass 0
+      const begin_tx = await program.methods.beginCraft(new BN(0), 0, [])
+        .accounts({
+          user: user.publicKey,
+          programManager: program_manager_acc,
+          admin: manager.publicKey,
+          recipeHolder: provider.wallet.publicKey,
+          playerProfile: player_profile,
+          recipe: reveal_recipe_account,
+          recipeTokenAccount: reveal_recipe_ata,
+          recipeMint: reveal_recipe_mint.publicKey,
+          pendingCraft: pending_craft,
+          randomness: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+          rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ataProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId
+        })
+        .remainingAccounts([
+          {pubkey: user_ingredient, isSigner: false, isWritable: true},
+          {pubkey: ingredient.publicKey, isSigner: false, isWritable: true},
+          {pubkey: escrow_ingredient, isSigner: false, isWritable: true},
+        ])
+        .signers([user])
+        .rpc()
+      console.log(`${display.green}`,`${display.grapes} BeginCraft transaction signature `, begin_tx);
+    } catch (err) {
+      console.log(`${display.red}`,`${display.bomb} begin_craft failed`, err);
+    }
+
+    // wait for reveal slot to pass
+    let pending = await program.account.pendingCraft.fetch(pending_craft);
+    while (await provider.connection.getSlot() <= pending.revealSlot.toNumber()) {
+      await new Promise(resolve => setTimeout(resolve, 400));
+    }
+
+    // roll is deterministic once revealed, finish with the entry that matches it
+    for (let entry = 0; entry < lootTable.length; entry++) {
+      let mint = lootTable[entry].output.mint;
+      let user_loot = await Token.getAssociatedTokenAddress(
+        ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, mint, user.publicKey
+      );
+      try {
+        const finish_tx = await program.methods.finishCraft(entry)
+          .accounts({
+            payer: provider.wallet.publicKey,
+            user: user.publicKey,
+            recipe: reveal_recipe_account,
+            recipeVault: reveal_recipe_vault,
+            pendingCraft: pending_craft,
+            randomness: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+            rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            ataProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId
+          })
+          .remainingAccounts([
+            // escrow settles into the recipe vault before the loot entry
+            {pubkey: escrow_ingredient, isSigner: false, isWritable: true},
+            {pubkey: ingredient.publicKey, isSigner: false, isWritable: false},
+            {pubkey: vault_ingredient, isSigner: false, isWritable: true},
+            {pubkey: mint, isSigner: false, isWritable: true},
+            {pubkey: user_loot, isSigner: false, isWritable: true},
+          ])
+          .signers([wallet])
+          .rpc()
+        console.log(`${display.green}`,`${display.grapes} FinishCraft transaction signature `, finish_tx, ' entry ', entry);
+        break;
+      } catch (err) {
+        console.log(`${display.magenta}`,`finish_craft entry ${entry} not rolled`);
+      }
+    }
+
+  }); // end beginCraft / finishCraft
+
+/** ============================================================================================
+                              V R F      C O M M I T      R E V E A L   
+    ============================================================================================   
+**/
+
+  it("Begin and finish VRF craft", async () => {
+
+    // new Recipe NFT for a commit-reveal recipe rolled from mock VRF results
+    let lamports = await Token.getMinBalanceRentForExemptMint(provider.connection);
+    const data = recipe_nft_data(manager.publicKey);
+    let [
+      vrf_recipe_mint,
+      vrf_recipe_metadata_PDA,
+      vrf_recipe_mint_tx,
+      vrf_recipe_ata,
+      vrf_recipe_master_edition
+    ] = await createRecipe(
+      provider.wallet.publicKey, // authority/payer
+      provider.wallet.publicKey, // destination (owner)
+      lamports,
+      data, // metadata account
+      recipe_json_url // metadata URI
+    );
+    await provider.sendAndConfirm(vrf_recipe_mint_tx, [vrf_recipe_mint]);
+    let [vrf_recipe_account, _] = await getRecipeAccount(
+      vrf_recipe_mint.publicKey,
+      program.programId
+    );
+    let [vrf_recipe_vault, __] = await getRecipeVault(vrf_recipe_account, program.programId);
+
+    // single loot pool minted by the recipe vault
+    let loot_mint = anchor.web3.Keypair.generate();
+    let loot_tx = await createOutputMint(
+      loot_mint.publicKey,
+      provider.wallet.publicKey,
+      vrf_recipe_vault,
+      lamports
+    );
+    await provider.sendAndConfirm(loot_tx, [loot_mint]);
+    let lootTable = [
+      { weight: 1, output: { kind: { fungible: {} }, mint: loot_mint.publicKey, amount: new BN(1) } },
+    ];
+
+    // reveal 2 slots after begin_craft, VRF results must be requested through begin_craft
+    await program.methods.createRecipe(recipeSettings({
+      ingredientMints: [ingredient.publicKey], ingredientAmounts: [new BN(10)],
+      mode: { random: {} }, lootTable, randomnessProgram: mock_vrf.programId,
+      revealDelay: new BN(2), cancelPenaltyBps: 1000
+      }))
+      .accounts({
+        owner: provider.wallet.publicKey,
+        recipe: vrf_recipe_account,
+        recipeTokenAccount: vrf_recipe_ata,
+        recipeMint: vrf_recipe_mint.publicKey,
+        recipeMetadata: vrf_recipe_metadata_PDA,
+        recipeMasterEdition: vrf_recipe_master_edition,
+        rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId
+      })
+      .signers([wallet])
+      .rpc()
+
+    // create test user holding the ingredient
+    const user = anchor.web3.Keypair.generate();
+    let airdrop = await provider.connection.requestAirdrop(user.publicKey, anchor.web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop);
+    let airdrop_tx = await airdropIngredient(
+      ingredient.publicKey, // ingredient mint to transfer
+      provider.wallet.publicKey, // owner
+      user.publicKey, // new owner
+      10 // amount to transfer
+    );
+    await provider.sendAndConfirm(airdrop_tx);
+
+    let user_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, user.publicKey
+    );
+    let [pending_craft, ___] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("pending"), vrf_recipe_account.toBuffer(), user.publicKey.toBuffer()],
+      program.programId
+    );
+    let escrow_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, pending_craft, true
+    );
+    let vault_ingredient = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, ingredient.publicKey, vrf_recipe_vault, true
+    );
+
+    // VRF result account of request 0, bound to recipe + user
+    let request = new BN(0);
+    let [vrf_result, ____] = await anchor.web3.PublicKey.findProgramAddress(
+      [
+        Buffer.from("randomness"),
+        vrf_recipe_account.toBuffer(),
+        user.publicKey.toBuffer(),
+        request.toArrayLike(Buffer, "le", 8)
+      ],
+      mock_vrf.programId
+    );
+    await mock_vrf.methods.requestRandomness(vrf_recipe_account, user.publicKey, request)
+      .accounts({
+        payer: provider.wallet.publicKey,
+        vrfResult: vrf_result,
+        systemProgram: anchor.web3.SystemProgram.programId
+      })
+      .rpc()
+
+    let [player_profile, _profile_bump] = await getPlayerProfile(user.publicKey, program.programId);
+
+    try {
+      const begin_tx = await program.methods.beginCraft(request, 0, [])
+        .accounts({
+          user: user.publicKey,
+          programManager: program_manager_acc,
+          admin: manager.publicKey,
+          recipeHolder: provider.wallet.publicKey,
+          playerProfile: player_profile,
+          recipe: vrf_recipe_account,
+          recipeTokenAccount: vrf_recipe_ata,
+          recipeMint: vrf_recipe_mint.publicKey,
+          pendingCraft: pending_craft,
+          randomness: vrf_result,
+          rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ataProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId
+        })
+        .remainingAccounts([
+          {pubkey: user_ingredient, isSigner: false, isWritable: true},
+          {pubkey: ingredient.publicKey, isSigner: false, isWritable: true},
+          {pubkey: escrow_ingredient, isSigner: false, isWritable: true},
+        ])
+        .signers([user])
+        .rpc()
+      console.log(`${display.green}`,`${display.grapes} BeginCraft (VRF) transaction signature `, begin_tx);
+    } catch (err) {
+      console.log(`${display.red}`,`${display.bomb} begin_craft (VRF) failed`, err);
+    }
+
+    // oracle fulfills the request after the commit
+    await mock_vrf.methods.fulfillRandomness(Array.from(anchor.web3.Keypair.generate().publicKey.toBytes()))
+      .accounts({ vrfResult: vrf_result })
+      .rpc()
+
+    // wait for reveal slot to pass
+    let pending = await program.account.pendingCraft.fetch(pending_craft);
+    while (await provider.connection.getSlot() <= pending.revealSlot.toNumber()) {
+      await new Promise(resolve => setTimeout(resolve, 400));
+    }
+
+    let user_loot = await Token.getAssociatedTokenAddress(
+      ASSOCIATED_TOKEN_PROGRAM_ID, TOKEN_PROGRAM_ID, loot_mint.publicKey, user.publicKey
+    );
+    try {
+      const finish_tx = await program.methods.finishCraft(0)
+        .accounts({
+          payer: provider.wallet.publicKey,
+          user: user.publicKey,
+          recipe: vrf_recipe_account,
+          recipeVault: vrf_recipe_vault,
+          pendingCraft: pending_craft,
+          randomness: vrf_result,
+          rentAccount: anchor.web3.SYSVAR_RENT_PUBKEY,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          ataProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId
+        })
+        .remainingAccounts([
+          {pubkey: escrow_ingredient, isSigner: false, isWritable: true},
+          {pubkey: ingredient.publicKey, isSigner: false, isWritable: false},
+          {pubkey: vault_ingredient, isSigner: false, isWritable: true},
+          {pubkey: loot_mint.publicKey, isSigner: false, isWritable: true},
+          {pubkey: user_loot, isSigner: false, isWritable: true},
+        ])
+        .signers([wallet])
+        .rpc()
+      console.log(`${display.green}`,`${display.grapes} FinishCraft (VRF) transaction signature `, finish_tx);
+    } catch (err) {
+      console.log(`${display.red}`,`${display.bomb} finish_craft (VRF) failed`, err);
+    }
+
+  }); // end VRF beginCraft / finishCraft
+
 });
@@ -333,6 +333,67 @@ export const getRecipeAccount = async (
   );
 }
 
+export const getRecipeVault = async (
+  recipe: anchor.web3.PublicKey,
+  programId: anchor.web3.PublicKey
+): Promise<[anchor.web3.PublicKey, number]> => {
+  return (
+    // recipe vault PDA, stocks skin outputs + escrowed ingredients, mint authority of fungible outputs
+    await anchor.web3.PublicKey.findProgramAddress(
+      [
+        Buffer.from("vault"),
+        recipe.toBuffer() // Recipe account PDA
+      ],
+      programId
+    )
+  );
+}
+
+export const getPlayerProfile = async (
+  user: anchor.web3.PublicKey,
+  programId: anchor.web3.PublicKey
+): Promise<[anchor.web3.PublicKey, number]> => {
+  return (
+    // crafting statistics of user, created by first craft
+    await anchor.web3.PublicKey.findProgramAddress(
+      [
+        Buffer.from("profile"),
+        user.toBuffer()
+      ],
+      programId
+    )
+  );
+}
+
+// RecipeSettings arg of create_recipe / update_recipe: Fixed mode, no fee, outputs, timing or gates
+export const recipeSettings = (settings: object) => {
+  return {
+    ingredientMints: [],
+    ingredientAmounts: [],
+    craftFee: new BN(0),
+    royaltyBps: 0,
+    outputs: [],
+    mode: { fixed: {} },
+    lootTable: [],
+    randomnessProgram: null,
+    revealDelay: new BN(0),
+    revealTimeout: new BN(0),
+    craftDuration: new BN(0),
+    speedUpMint: null,
+    speedUpAmount: new BN(0),
+    cancelPenaltyBps: 0,
+    salvageBps: 0,
+    inputSkins: [],
+    gate: null,
+    allowlistRoot: null,
+    name: "",
+    description: "",
+    category: { uncategorized: {} },
+    sortOrder: 0,
+    ...settings
+  };
+}
+
 export const createRecipeAccount = async (
   recipe_mint: anchor.web3.PublicKey,
   programId: anchor.web3.PublicKey,
@@ -414,6 +475,36 @@ export async function createNewIngredient(
   return tx;
 }
 
+export async function createOutputMint(
+  mint: PublicKey,
+  fee_payer: PublicKey,
+  mint_authority: PublicKey,
+  lamports
+): Promise<programs.core.Transaction> {
+  const tx = new Transaction({ feePayer: fee_payer });
+
+  tx.add(
+    // create mint
+    SystemProgram.createAccount({
+      fromPubkey: fee_payer,
+      newAccountPubkey: mint,
+      space: MintLayout.span,
+      lamports: lamports,
+      programId: TOKEN_PROGRAM_ID,
+    }),
+    // fungible token mint, minted by program on craft
+    Token.createInitMintInstruction(
+      TOKEN_PROGRAM_ID,
+      mint,
+      0,
+      mint_authority,
+      null
+    )
+  );
+
+  return tx;
+}
+
 export const airdropIngredient = async (
   ingredientMint: PublicKey,
   owner: PublicKey,
@@ -1,5 +1,6 @@
 /*
     UNIT TESTS
+      solana-program-test suites in programs/craft_skins/tests (cargo test-bpf)
       initialize()
 
       create_recipe()